@@ -1,60 +1,602 @@
 use hdk::prelude::{holo_hash::DnaHash, *};
 
 use common::{
-    compose_entry_hash_path, compose_paths, create_sensemaker_entry_parse, get_latest_linked_entry,
-    mk_application_se, remote_get_sensemaker_entry_by_path,
-    remote_get_sensemaker_entry_by_path_with_hh, remote_initialize_sm_data,
-    remote_set_sensemaker_entry_parse_rl_expr, remote_step_sm, remote_step_sm_path,
-    sensemaker_cell_id_anchor, sensemaker_cell_id_fns, util, CreateSensemakerEntryInputParse,
-    SensemakerCellId, SensemakerEntry,
+    compose_entry_hash_path, compose_paths, get_latest_linked_entry, remote_initialize_sm_data,
+    sensemaker_cell_id_anchor, sensemaker_cell_id_fns, util, SensemakerCellId, SensemakerEntry,
 };
 use rep_lang_runtime::eval::{FlatValue, Value};
 use social_sensemaker_core::{OWNER_TAG, SM_COMP_TAG, SM_DATA_TAG, SM_INIT_TAG};
+use widget_core::{
+    buckets::{day_bucket, DayBuckets, Page},
+    config::dna_properties,
+    error::WidgetError,
+};
+use widget_sensemaker::{
+    apply_expr, eval_int, eval_value, get_exact_links, int_expr, int_value, replay,
+    sm_ops::{query_own_entries, ArmedSmOp, CompletedSmOp, FailedSmOp, PendingSmOp, SmOp},
+    validate_sm_link, widget_sensemaker_fns, SmExprProposal, SmExprSource,
+};
 
-use memez_core::{types::Meme, MEMEZ_PATH, MEME_TAG};
+use memez_core::{
+    types::{
+        AgentRepPaths, Board, CreateScorePresetInput, FeedItem, HideComp, ListResult, Meme,
+        MemeBattle, MemeThumbnail, MemezConfig, PublishedScorePreset, ScorePreset,
+        SimulateFeedInput, SimulatedScore, UploadMemeInput, UploadMemeOutcome,
+    },
+    AGENT_REP_PATHS_TAG, BATTLE_PATH, BATTLE_TAG, BOARD_MEME_TAG, BOARD_TAG, CLAP_TAG,
+    CONTENT_HASH_TAG, DEFAULT_AGENT_REP, FLAG_TAG, HIDE_COMP_TAG, MEMEZ_PATH, MEME_TAG,
+    MODERATION_PATH, NEAR_DUPLICATE_MAX_DISTANCE, PERCEPTUAL_HASH_BANDS, PERCEPTUAL_HASH_TAG,
+    SCORE_PRESET_TAG, STARTER_HIDE_COMP, THUMBNAIL_TAG, TRENDING_WINDOW_SECS,
+};
 use paperz_core::AGENT_PATH;
 
 entry_defs![
     Meme::entry_def(),
+    MemeThumbnail::entry_def(),
+    ScorePreset::entry_def(),
+    HideComp::entry_def(),
+    AgentRepPaths::entry_def(),
+    MemeBattle::entry_def(),
+    Board::entry_def(),
+    SmExprSource::entry_def(),
+    SmExprProposal::entry_def(),
+    PendingSmOp::entry_def(),
+    ArmedSmOp::entry_def(),
+    CompletedSmOp::entry_def(),
+    FailedSmOp::entry_def(),
     SensemakerCellId::entry_def(),
     PathEntry::entry_def(),
     SensemakerEntry::entry_def()
 ];
 
 sensemaker_cell_id_fns! {}
+widget_sensemaker_fns! {
+    paths: [MEMEZ_PATH, MODERATION_PATH, BATTLE_PATH],
+    tags: [SM_INIT_TAG, SM_COMP_TAG],
+}
+
+fn entry_not_found(err: WasmError) -> WasmError {
+    WidgetError::EntryNotFound(err.to_string()).into()
+}
+
+/// the entry at `eh`, and the agent who committed it.
+fn get_with_author<T>(eh: EntryHash) -> ExternResult<(T, AgentPubKey)>
+where
+    T: TryFrom<SerializedBytes, Error = SerializedBytesError>,
+{
+    let element = get(eh.clone(), GetOptions::content())?
+        .ok_or_else(|| WidgetError::EntryNotFound(format!("no element for {}", eh)))?;
+    let entry = element
+        .entry()
+        .to_app_option::<T>()
+        .map_err(WasmError::Serialize)?
+        .ok_or_else(|| WidgetError::EntryNotFound(format!("no app entry at {}", eh)))?;
+    Ok((entry, element.header().author().clone()))
+}
+
+/// see `DayBuckets`. the legacy anchor is the single one all memez used to hang off.
+const MEME_BUCKETS: DayBuckets = DayBuckets {
+    anchor_type: "memez_by_day",
+    legacy_anchor_type: "memez",
+    tag: MEME_TAG,
+};
+
+#[hdk_extern]
+/// every meme bucket, newest first.
+fn list_meme_buckets(_: ()) -> ExternResult<Vec<String>> {
+    MEME_BUCKETS.list_buckets()
+}
+
+#[hdk_extern]
+/// at least `min_items` memez (fewer only on the last page), newest first, starting with the
+/// bucket `from_bucket`, or the newest one if that is `None`. see `DayBuckets::get_page`.
+fn get_memez_page(
+    (from_bucket, min_items): (Option<String>, u32),
+) -> ExternResult<Page<(EntryHash, MemeThumbnail)>> {
+    let (meme_links, next_bucket) = MEME_BUCKETS.get_page(from_bucket, min_items as usize)?;
+    let mut items: Vec<(EntryHash, MemeThumbnail)> = Vec::new();
+    let mut errors: Vec<(EntryHash, String)> = Vec::new();
+    for lnk in meme_links {
+        let meme_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+        match get_meme_thumbnail(meme_eh.clone()) {
+            Ok(thumbnail) => items.push((meme_eh, thumbnail)),
+            Err(err) => errors.push((meme_eh, err.to_string())),
+        }
+    }
+    Ok(Page {
+        items,
+        errors,
+        next_bucket,
+    })
+}
+
+#[hdk_extern]
+/// move every meme on the legacy single anchor into its day bucket. see `DayBuckets::migrate`.
+fn migrate_meme_anchor(_: ()) -> ExternResult<u32> {
+    MEME_BUCKETS.migrate()
+}
+
+fn content_hash_anchor(content_hash: String) -> ExternResult<EntryHash> {
+    anchor("meme_content_hash".into(), content_hash)
+}
+
+/// one anchor per (band index, band value), so that memez sharing a band share an anchor.
+fn perceptual_hash_band_anchors(perceptual_hash: &str) -> ExternResult<Vec<EntryHash>> {
+    let band_len = perceptual_hash.len() / PERCEPTUAL_HASH_BANDS;
+    (0..PERCEPTUAL_HASH_BANDS)
+        .map(|idx| {
+            let band = &perceptual_hash[idx * band_len..(idx + 1) * band_len];
+            anchor("meme_perceptual_hash".into(), format!("{}.{}", idx, band))
+        })
+        .collect()
+}
+
+fn parse_perceptual_hash(perceptual_hash: &str) -> ExternResult<u64> {
+    if perceptual_hash.len() != 16 {
+        return Err(WidgetError::ValidationFailed(format!(
+            "perceptual hash must be 16 hex digits, got {:?}",
+            perceptual_hash
+        ))
+        .into());
+    }
+    u64::from_str_radix(perceptual_hash, 16).map_err(|err| {
+        WidgetError::ValidationFailed(format!("invalid perceptual hash: {}", err)).into()
+    })
+}
+
+/// memez whose perceptual hash is within `NEAR_DUPLICATE_MAX_DISTANCE` bits of `perceptual_hash`.
+fn find_near_duplicates(perceptual_hash: &str) -> ExternResult<Vec<(EntryHash, MemeThumbnail)>> {
+    let hash = parse_perceptual_hash(perceptual_hash)?;
+    let mut meme_ehs: Vec<EntryHash> = Vec::new();
+    for band_anchor in perceptual_hash_band_anchors(perceptual_hash)? {
+        for lnk in get_links(band_anchor, Some(LinkTag::new(PERCEPTUAL_HASH_TAG)))? {
+            // anyone can link to a band anchor, so one bad tag mustn't block every upload
+            let other_hash = match std::str::from_utf8(&lnk.tag.0[PERCEPTUAL_HASH_TAG.len()..])
+                .map_err(|err| err.to_string())
+                .and_then(|other_hash| {
+                    parse_perceptual_hash(other_hash).map_err(|err| err.to_string())
+                }) {
+                Ok(other_hash) => other_hash,
+                Err(err) => {
+                    debug!("find_near_duplicates: malformed link tag: {}", err);
+                    continue;
+                }
+            };
+            let meme_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+            if (hash ^ other_hash).count_ones() <= NEAR_DUPLICATE_MAX_DISTANCE
+                && !meme_ehs.contains(&meme_eh)
+            {
+                meme_ehs.push(meme_eh);
+            }
+        }
+    }
+    meme_ehs
+        .into_iter()
+        .map(|meme_eh| Ok((meme_eh.clone(), get_meme_thumbnail(meme_eh)?)))
+        .collect()
+}
+
+#[hdk_extern]
+/// the DNA properties, with `MemezConfig::default` standing in for any they leave out. see
+/// `widget_core::config::dna_properties`.
+fn get_config(_: ()) -> ExternResult<MemezConfig> {
+    dna_properties()
+}
 
-fn meme_anchor() -> ExternResult<EntryHash> {
-    anchor("memez".into(), "".into())
+/// reject memez which the config doesn't allow.
+fn check_meme_allowed(config: &MemezConfig, meme: &Meme) -> ExternResult<()> {
+    let size = base64::decode(&meme.blob_str)
+        .map_err(|err| {
+            WasmError::from(WidgetError::ValidationFailed(format!(
+                "meme blob_str is not base64: {}",
+                err
+            )))
+        })?
+        .len() as u64;
+    if size > config.max_meme_bytes {
+        return Err(WidgetError::ValidationFailed(format!(
+            "meme is {} bytes, but at most {} are allowed",
+            size, config.max_meme_bytes
+        ))
+        .into());
+    }
+    let extension = meme
+        .filename
+        .rsplit_once('.')
+        .map(|(_name, extension)| extension.to_lowercase())
+        .unwrap_or_default();
+    if !config.allowed_extensions.is_empty() && !config.allowed_extensions.contains(&extension) {
+        return Err(WidgetError::ValidationFailed(format!(
+            "memez must be one of: {}",
+            config.allowed_extensions.join(", ")
+        ))
+        .into());
+    }
+    Ok(())
 }
 
 #[hdk_extern]
-fn upload_meme(meme: Meme) -> ExternResult<(EntryHash, HeaderHash)> {
+fn upload_meme(input: UploadMemeInput) -> ExternResult<UploadMemeOutcome> {
+    let UploadMemeInput {
+        meme,
+        thumbnail_blob_str,
+        perceptual_hash,
+        allow_near_duplicate,
+    } = input;
     debug!(
         "upload_meme: received input of length {}",
         meme.blob_str.len()
     );
+    check_meme_allowed(&get_config(())?, &meme)?;
+
+    // the filename doesn't take part, so the same image under another name is still a duplicate
+    let content_hash: String = hash_sha256(meme.blob_str.clone().into_bytes())?
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    let content_anchor = content_hash_anchor(content_hash)?;
+    let content_links = get_links(content_anchor.clone(), Some(LinkTag::new(CONTENT_HASH_TAG)))?;
+    if let Some(lnk) = content_links.into_iter().next() {
+        let meme_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+        return Ok(UploadMemeOutcome::Duplicate(meme_eh));
+    }
+    if let Some(perceptual_hash) = &perceptual_hash {
+        if !allow_near_duplicate {
+            let near_duplicates = find_near_duplicates(perceptual_hash)?;
+            if !near_duplicates.is_empty() {
+                return Ok(UploadMemeOutcome::NearDuplicates(near_duplicates));
+            }
+        }
+    }
 
     let meme_hh = create_entry(&meme)?;
     let meme_eh = hash_entry(&meme)?;
+    MEME_BUCKETS.link(meme_eh.clone())?;
+
+    if let Some(blob_str) = thumbnail_blob_str {
+        let thumbnail = MemeThumbnail {
+            filename: meme.filename.clone(),
+            blob_str,
+        };
+        create_entry(&thumbnail)?;
+        let thumbnail_eh = hash_entry(&thumbnail)?;
+        create_link(
+            meme_eh.clone(),
+            thumbnail_eh,
+            LinkType(0),
+            LinkTag::new(THUMBNAIL_TAG),
+        )?;
+    }
+
     create_link(
-        meme_anchor()?,
+        content_anchor,
         meme_eh.clone(),
         LinkType(0),
-        LinkTag::new(MEME_TAG),
+        LinkTag::new(CONTENT_HASH_TAG),
     )?;
+    if let Some(perceptual_hash) = perceptual_hash {
+        parse_perceptual_hash(&perceptual_hash)?;
+        let tag = format!("{}{}", PERCEPTUAL_HASH_TAG, perceptual_hash);
+        for band_anchor in perceptual_hash_band_anchors(&perceptual_hash)? {
+            create_link(
+                band_anchor,
+                meme_eh.clone(),
+                LinkType(0),
+                LinkTag::new(tag.clone()),
+            )?;
+        }
+    }
 
     // init SM data for meme
     // this requires the SM_INIT to have been already set...
-    let cell_id = get_sensemaker_cell_id(())?;
-    let payload = (MEMEZ_PATH.to_string(), meme_eh.clone());
-    remote_initialize_sm_data(cell_id, None, payload)?;
+    // the moderation state is only initialized once the meme is first flagged. see `flag_meme`.
+    run_sm_op(SmOp::InitSmData(MEMEZ_PATH.into(), meme_eh.clone()))?;
 
-    Ok((meme_eh, meme_hh))
+    Ok(UploadMemeOutcome::Uploaded(meme_eh, meme_hh))
+}
+
+#[hdk_extern]
+fn get_meme(meme_eh: EntryHash) -> ExternResult<Meme> {
+    util::try_get_and_convert(meme_eh, GetOptions::content()).map_err(entry_not_found)
+}
+
+fn get_meme_thumbnail(meme_eh: EntryHash) -> ExternResult<MemeThumbnail> {
+    let thumbnail_links = get_links(meme_eh.clone(), Some(LinkTag::new(THUMBNAIL_TAG)))?;
+    match thumbnail_links.into_iter().next() {
+        Some(lnk) => {
+            let thumbnail_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+            util::try_get_and_convert(thumbnail_eh, GetOptions::content()).map_err(entry_not_found)
+        }
+        // memez uploaded without a thumbnail fall back to the full image
+        None => {
+            let meme: Meme = util::try_get_and_convert(meme_eh, GetOptions::content())
+                .map_err(entry_not_found)?;
+            Ok(MemeThumbnail {
+                filename: meme.filename,
+                blob_str: meme.blob_str,
+            })
+        }
+    }
 }
 
 #[hdk_extern]
 fn clap_for_meme(meme_eh: EntryHash) -> ExternResult<()> {
-    step_sm_remote((MEMEZ_PATH.into(), meme_eh, "1".into()))
+    // recorded so that feed score computations can see *when* memez were clapped for
+    let agent_pk = agent_info()?.agent_initial_pubkey;
+    create_link(
+        meme_eh.clone(),
+        EntryHash::from(agent_pk),
+        LinkType(0),
+        clap_tag(sys_time()?),
+    )?;
+    run_sm_op(SmOp::StepSm(MEMEZ_PATH.into(), meme_eh, "1".into()))
+}
+
+#[hdk_extern]
+fn flag_meme(meme_eh: EntryHash) -> ExternResult<()> {
+    let agent_pk = agent_info()?.agent_initial_pubkey;
+    let flagger_eh = EntryHash::from(agent_pk.clone());
+    let flag_links = get_links(meme_eh.clone(), Some(LinkTag::new(FLAG_TAG)))?;
+    if flag_links
+        .into_iter()
+        .any(|lnk| lnk.target.into_entry_hash() == Some(flagger_eh.clone()))
+    {
+        return Err(WidgetError::ValidationFailed("flag_meme: already flagged".into()).into());
+    }
+    create_link(
+        meme_eh.clone(),
+        flagger_eh,
+        LinkType(0),
+        LinkTag::new(FLAG_TAG),
+    )?;
+
+    // flags are weighted by the flagger's paperz score, but always count for something
+    let weight = match get_paperz_sm_data(agent_pk)? {
+        Some((_eh, _hh, se)) => match se.output_flat_value {
+            FlatValue(Value::VInt(x)) => x.max(1),
+            _ => 1,
+        },
+        None => 1,
+    };
+    // a meme has no moderation state until it is first flagged. if the sensemaker can't be asked,
+    // the init is queued along with the step.
+    if !matches!(get_moderation_sm_data(meme_eh.clone()), Ok(Some(_))) {
+        run_sm_op(SmOp::InitSmData(MODERATION_PATH.into(), meme_eh.clone()))?;
+    }
+    run_sm_op(SmOp::StepSm(
+        MODERATION_PATH.into(),
+        meme_eh,
+        weight.to_string(),
+    ))
+}
+
+fn moderation_anchor() -> ExternResult<EntryHash> {
+    anchor("moderation".into(), "".into())
+}
+
+#[hdk_extern]
+/// set the `rep_lang` computation which decides whether a meme is hidden. see `STARTER_HIDE_COMP`.
+/// until one is set, `MemezConfig::hide_comp` is in effect. only sm_admins may set it.
+fn set_hide_comp(expr: String) -> ExternResult<bool> {
+    ensure_sm_admin()?;
+    if !matches!(eval_int(apply_expr(&expr, &[int_expr(0)])), Ok(Some(_))) {
+        return Err(WidgetError::InvalidExpression(format!(
+            "a hide computation must take a meme's moderation state and return an integer: {}",
+            expr
+        ))
+        .into());
+    }
+    let hide_comp = HideComp { expr };
+    create_entry(&hide_comp)?;
+    create_link(
+        moderation_anchor()?,
+        hash_entry(&hide_comp)?,
+        LinkType(0),
+        LinkTag::new(HIDE_COMP_TAG),
+    )?;
+    Ok(true)
+}
+
+/// whether `hide_comp` hides a meme with the moderation state `flags`. a hide computation which
+/// fails to evaluate (e.g. one set before they were checked) falls back to `STARTER_HIDE_COMP`,
+/// and the meme is shown if that fails too, so a bad computation can't break the feed.
+fn is_hidden(hide_comp: &str, flags: i64) -> bool {
+    let hide = |hide_comp: &str| eval_int(apply_expr(hide_comp, &[int_expr(flags)]));
+    match hide(hide_comp) {
+        Ok(Some(x)) => x > 0,
+        _ => matches!(hide(STARTER_HIDE_COMP), Ok(Some(x)) if x > 0),
+    }
+}
+
+#[hdk_extern]
+fn get_hide_comp(_: ()) -> ExternResult<String> {
+    let hide_comp_links = get_exact_links(moderation_anchor()?, HIDE_COMP_TAG)?;
+    match hide_comp_links.into_iter().max_by_key(|lnk| lnk.timestamp) {
+        None => Ok(get_config(())?.hide_comp),
+        Some(lnk) => {
+            let hide_comp_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+            let hide_comp: HideComp =
+                util::try_get_and_convert(hide_comp_eh, GetOptions::content())?;
+            Ok(hide_comp.expr)
+        }
+    }
+}
+
+fn battle_anchor() -> ExternResult<EntryHash> {
+    anchor("battles".into(), "".into())
+}
+
+#[hdk_extern]
+/// record that the agent preferred `winner_eh` over `loser_eh`, and step both memez' battle ratings.
+/// see `BATTLE_SM_COMP`. each agent gets one vote per pair of memez.
+fn record_battle((winner_eh, loser_eh): (EntryHash, EntryHash)) -> ExternResult<()> {
+    if winner_eh == loser_eh {
+        return Err(WidgetError::ValidationFailed(
+            "record_battle: a meme can't battle itself".into(),
+        )
+        .into());
+    }
+    let is_pair = |battle: &MemeBattle| {
+        (battle.winner == winner_eh && battle.loser == loser_eh)
+            || (battle.winner == loser_eh && battle.loser == winner_eh)
+    };
+    if query_own_entries::<MemeBattle>(entry_type!(MemeBattle)?)?
+        .iter()
+        .any(|(_eh, battle)| is_pair(battle))
+    {
+        return Err(WidgetError::ValidationFailed(
+            "record_battle: already voted on this pair".into(),
+        )
+        .into());
+    }
+    let path_string = BATTLE_PATH.to_string();
+    if get_sm_init(path_string.clone())?.is_none() || get_sm_comp(path_string)?.is_none() {
+        return Err(WidgetError::ValidationFailed(
+            "record_battle: battle ratings are not installed".into(),
+        )
+        .into());
+    }
+    // both updates use the ratings from *before* this battle
+    let winner_rating = get_or_init_battle_rating(winner_eh.clone())?;
+    let loser_rating = get_or_init_battle_rating(loser_eh.clone())?;
+
+    let battle = MemeBattle {
+        winner: winner_eh.clone(),
+        loser: loser_eh.clone(),
+    };
+    create_entry(&battle)?;
+    create_link(
+        battle_anchor()?,
+        hash_entry(&battle)?,
+        LinkType(0),
+        LinkTag::new(BATTLE_TAG),
+    )?;
+    run_sm_op(SmOp::StepSm(
+        BATTLE_PATH.into(),
+        winner_eh,
+        (2 * loser_rating + 1).to_string(),
+    ))?;
+    run_sm_op(SmOp::StepSm(
+        BATTLE_PATH.into(),
+        loser_eh,
+        (2 * winner_rating).to_string(),
+    ))
+}
+
+fn get_battle_rating(meme_eh: EntryHash) -> ExternResult<Option<i64>> {
+    let path_string = compose_entry_hash_path(&BATTLE_PATH.into(), meme_eh);
+    let opt_eh_hh_se = get_sm_generic_with_hh(path_string, SM_DATA_TAG.to_string())?;
+    Ok(
+        opt_eh_hh_se.and_then(|(_eh, _hh, se)| match se.output_flat_value {
+            FlatValue(Value::VInt(x)) => Some(x),
+            _ => None,
+        }),
+    )
+}
+
+/// memez only get battle state once they first battle. if that has to be queued (see `run_sm_op`),
+/// the meme still starts from the battle sm_init.
+fn get_or_init_battle_rating(meme_eh: EntryHash) -> ExternResult<i64> {
+    if let Some(rating) = get_battle_rating(meme_eh.clone())? {
+        return Ok(rating);
+    }
+    run_sm_op(SmOp::InitSmData(BATTLE_PATH.into(), meme_eh.clone()))?;
+    let rating = match get_battle_rating(meme_eh)? {
+        Some(rating) => Some(rating),
+        None => {
+            get_sm_init(BATTLE_PATH.into())?.and_then(|(_eh, se)| int_value(&se.output_flat_value))
+        }
+    };
+    rating.ok_or_else(|| {
+        WidgetError::InvalidExpression(
+            "get_or_init_battle_rating: battle sm_init is not an integer".into(),
+        )
+        .into()
+    })
+}
+
+/// claps are tagged with the day they happened on, so that `windowed_clap_count` only fetches the
+/// days in its window. `get_links` matches tags by prefix, so `CLAP_TAG` alone still finds every
+/// clap, including those recorded before the day was added.
+fn clap_tag(timestamp: Timestamp) -> LinkTag {
+    LinkTag::new(format!("{}:{}", CLAP_TAG, day_bucket(timestamp)))
+}
+
+/// the number of claps `meme_eh` received between `since_micros` and `now_micros`.
+fn windowed_clap_count(
+    meme_eh: EntryHash,
+    since_micros: i64,
+    now_micros: i64,
+) -> ExternResult<i64> {
+    const DAY_MICROS: i64 = 24 * 60 * 60 * 1_000_000;
+    let mut count = 0;
+    let mut day_micros = since_micros - since_micros.rem_euclid(DAY_MICROS);
+    while day_micros <= now_micros {
+        let clap_links = get_links(
+            meme_eh.clone(),
+            Some(clap_tag(Timestamp::from_micros(day_micros))),
+        )?;
+        count += clap_links
+            .into_iter()
+            .filter(|lnk| lnk.timestamp.as_micros() >= since_micros)
+            .count() as i64;
+        day_micros += DAY_MICROS;
+    }
+    Ok(count)
+}
+
+/// whether `feed_score_comp` takes the `(lam [meme agent] ...)` form feed score computations had
+/// before `age` & `window` were added. those are still accepted, so that saved computations and
+/// presets keep working. an error if it takes neither form.
+fn is_legacy_feed_score_comp(feed_score_comp: &str) -> ExternResult<bool> {
+    // ones rather than zeros, so that computations which divide by an argument evaluate
+    let ones = |n: usize| vec!["1".to_string(); n];
+    if let Ok(Some(_)) = eval_int(apply_expr(feed_score_comp, &ones(4))) {
+        return Ok(false);
+    }
+    if let Ok(Some(_)) = eval_int(apply_expr(feed_score_comp, &ones(2))) {
+        return Ok(true);
+    }
+    Err(WidgetError::InvalidExpression(format!(
+        "feed score computation must take (meme agent age window) and return an integer: {}",
+        feed_score_comp
+    ))
+    .into())
+}
+
+/// the arguments a feed score computation is applied to, as `rep_lang` source. see
+/// `STARTER_FEED_SCORE_COMP` & `is_legacy_feed_score_comp`.
+fn feed_score_args(
+    legacy: bool,
+    meme: i64,
+    agent_rep: i64,
+    age_secs: i64,
+    window_claps: i64,
+) -> Vec<String> {
+    if legacy {
+        vec![int_expr(meme), int_expr(agent_rep)]
+    } else {
+        vec![
+            int_expr(meme),
+            int_expr(agent_rep),
+            int_expr(age_secs),
+            int_expr(window_claps),
+        ]
+    }
+}
+
+/// `agent_pk`'s reputation: the sum of its states on each of `get_agent_rep_paths`, each
+/// defaulting to `DEFAULT_AGENT_REP`. summed, so that the arity of feed score computations doesn't
+/// depend on the number of paths.
+fn agent_rep(agent_pk: AgentPubKey) -> ExternResult<i64> {
+    let mut agent_rep: i64 = 0;
+    for path in get_agent_rep_paths(())? {
+        let path_rep = get_agent_rep_sm_data(path, agent_pk.clone())?
+            .and_then(|(_eh, _hh, se)| int_value(&se.output_flat_value))
+            .unwrap_or(DEFAULT_AGENT_REP);
+        agent_rep = agent_rep.saturating_add(path_rep);
+    }
+    Ok(agent_rep)
 }
 
 #[hdk_extern]
@@ -69,140 +611,461 @@ fn meme_clap_count(meme_eh: EntryHash) -> ExternResult<Option<i64>> {
 }
 
 #[hdk_extern]
+/// every meme, highest `feed_score_comp` score first. the computations are evaluated in place, so
+/// reading the feed writes nothing.
 fn get_all_memez(
     (feed_score_comp, agent_pk): (String, AgentPubKey),
-) -> ExternResult<Vec<(EntryHash, Meme, i64)>> {
-    let meme_entry_links = get_links(meme_anchor()?, Some(LinkTag::new(MEME_TAG)))?;
-    let mut memez: Vec<(EntryHash, Meme, i64)> = Vec::new();
-    let mut opt_err = None;
-    for lnk in meme_entry_links {
-        let res: ExternResult<(EntryHash, Meme, i64)> = {
-            let meme_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
-            let (meme, _hh) =
-                util::try_get_and_convert_with_hh(meme_eh.clone(), GetOptions::content())?;
-            let meme_score_eh_hh_se = match get_sm_data(meme_eh.clone())? {
-                Some(x) => x,
-                None => panic!("impossible"),
-            };
-            let agent_score_eh_hh_se = match get_paperz_sm_data(agent_pk.clone())? {
-                Some(x) => x,
-                None => panic!("impossible"),
-            };
-            let score_comp_hh_se =
-                create_sensemaker_entry_parse(CreateSensemakerEntryInputParse {
-                    expr: feed_score_comp.clone(),
-                    args: vec![],
-                })?;
-            let application_se = mk_application_se(vec![
-                score_comp_hh_se.0,
-                meme_score_eh_hh_se.1,
-                agent_score_eh_hh_se.1,
-            ])?;
-
-            let meme_score = match application_se.output_flat_value {
-                FlatValue(Value::VInt(x)) => {
-                    debug!("x: {}", x);
-                    x
-                }
-                _ => {
-                    debug!("score is None!");
-                    0
-                }
-            };
-            Ok((meme_eh, meme, meme_score))
+) -> ExternResult<ListResult<FeedItem>> {
+    let now_micros = sys_time()?.as_micros();
+    let window_start_micros = now_micros - TRENDING_WINDOW_SECS * 1_000_000;
+    let agent_rep = agent_rep(agent_pk)?;
+    let legacy = is_legacy_feed_score_comp(&feed_score_comp)?;
+    let hide_comp = get_hide_comp(())?;
+
+    // a closure, rather than a block, so that `?` only abandons the one meme
+    let get_feed_item = |meme_eh: EntryHash, age_secs: i64| -> ExternResult<FeedItem> {
+        let thumbnail = get_meme_thumbnail(meme_eh.clone())?;
+        let meme_state = match get_sm_data(meme_eh.clone())? {
+            Some((_eh, _hh, se)) => int_value(&se.output_flat_value).ok_or_else(|| {
+                WidgetError::InvalidExpression("meme sensemaker data is not an integer".into())
+            })?,
+            None => {
+                return Err(WidgetError::SmDataMissing("meme has no sensemaker data".into()).into())
+            }
+        };
+        let window_claps = windowed_clap_count(meme_eh.clone(), window_start_micros, now_micros)?;
+        let args = feed_score_args(legacy, meme_state, agent_rep, age_secs, window_claps);
+        let meme_score = eval_int(apply_expr(&feed_score_comp, &args))?.unwrap_or_else(|| {
+            debug!("score is None!");
+            0
+        });
+        let hidden = match get_moderation_sm_data(meme_eh.clone())?
+            .and_then(|(_eh, _hh, se)| int_value(&se.output_flat_value))
+        {
+            None => false,
+            Some(flags) => is_hidden(&hide_comp, flags),
         };
+        let battle_rating = get_battle_rating(meme_eh.clone())?;
+        Ok(FeedItem {
+            meme_eh,
+            thumbnail,
+            score: meme_score,
+            hidden,
+            battle_rating,
+        })
+    };
 
-        match res {
-            Ok(tup) => memez.push(tup),
+    let meme_entry_links = MEME_BUCKETS.get_links()?;
+    let mut memez: Vec<FeedItem> = Vec::new();
+    let mut errors: Vec<(EntryHash, String)> = Vec::new();
+    for lnk in meme_entry_links {
+        let age_secs = (now_micros - MEME_BUCKETS.linked_micros(&lnk)) / 1_000_000;
+        let meme_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+        match get_feed_item(meme_eh.clone(), age_secs) {
+            Ok(item) => memez.push(item),
             Err(err) => {
                 debug!("err in fetching meme: {}", err);
-                opt_err = Some(err);
+                errors.push((meme_eh, err.to_string()));
             }
         }
     }
-    match opt_err {
-        None => Ok(memez),
-        Some(err) => Err(WasmError::Guest(format!("get_all_memez: {:?}", err))),
+    memez.sort_by(|a, b| b.score.cmp(&a.score));
+    Ok(ListResult {
+        items: memez,
+        errors,
+    })
+}
+
+#[hdk_extern]
+/// score every meme as `get_all_memez` would for the calling agent, both as things are and with the
+/// candidate computations in `input`, without writing anything. a candidate sm_comp is replayed
+/// over each meme's recorded claps, from the sm_init in use.
+fn simulate_feed_scores(input: SimulateFeedInput) -> ExternResult<ListResult<SimulatedScore>> {
+    let now_micros = sys_time()?.as_micros();
+    let window_start_micros = now_micros - TRENDING_WINDOW_SECS * 1_000_000;
+    let agent_rep = agent_rep(agent_info()?.agent_initial_pubkey)?;
+    let current_legacy = is_legacy_feed_score_comp(&input.current_feed_score_comp)?;
+    let simulated_legacy = is_legacy_feed_score_comp(&input.feed_score_comp)?;
+    let sm_init = match get_sm_init(MEMEZ_PATH.into())? {
+        Some((_eh, se)) => se.output_flat_value,
+        None => eval_value(get_config(())?.sm_init_expr)?,
+    };
+
+    // a closure, rather than a block, so that `?` only abandons the one meme
+    let simulate_meme = |meme_eh: EntryHash, age_secs: i64| -> ExternResult<SimulatedScore> {
+        let current_state = get_sm_data(meme_eh.clone())?
+            .and_then(|(_eh, _hh, se)| int_value(&se.output_flat_value));
+        let simulated_state = match &input.sm_comp {
+            None => current_state,
+            Some(sm_comp) => {
+                let claps = get_links(meme_eh.clone(), Some(LinkTag::new(CLAP_TAG)))?.len();
+                int_value(&replay(
+                    sm_init.clone(),
+                    sm_comp,
+                    &vec!["1".to_string(); claps],
+                )?)
+            }
+        };
+        let window_claps = windowed_clap_count(meme_eh.clone(), window_start_micros, now_micros)?;
+        let score = |feed_score_comp: &str,
+                     legacy: bool,
+                     state: Option<i64>|
+         -> ExternResult<Option<i64>> {
+            match state {
+                None => Ok(None),
+                Some(state) => {
+                    let args = feed_score_args(legacy, state, agent_rep, age_secs, window_claps);
+                    eval_int(apply_expr(feed_score_comp, &args))
+                }
+            }
+        };
+        Ok(SimulatedScore {
+            current: score(
+                &input.current_feed_score_comp,
+                current_legacy,
+                current_state,
+            )?,
+            simulated: score(&input.feed_score_comp, simulated_legacy, simulated_state)?,
+            meme_eh,
+        })
+    };
+
+    let mut items: Vec<SimulatedScore> = Vec::new();
+    let mut errors: Vec<(EntryHash, String)> = Vec::new();
+    for lnk in MEME_BUCKETS.get_links()? {
+        let age_secs = (now_micros - MEME_BUCKETS.linked_micros(&lnk)) / 1_000_000;
+        let meme_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+        match simulate_meme(meme_eh.clone(), age_secs) {
+            Ok(item) => items.push(item),
+            Err(err) => errors.push((meme_eh, err.to_string())),
+        }
     }
+    Ok(ListResult { items, errors })
 }
 
-fn get_paperz_sm_data(
-    agent_pk: AgentPubKey,
-) -> ExternResult<Option<(EntryHash, HeaderHash, SensemakerEntry)>> {
-    let agent_b64: String = base64::encode(agent_pk.clone().into_inner());
-    let path_string = compose_paths(&AGENT_PATH.into(), &agent_b64);
-    get_sm_generic_with_hh(path_string, SM_DATA_TAG.to_string())
+#[hdk_extern]
+/// initialize the missing sensemaker data of every meme. `upload_meme` commits the meme before
+/// calling into the sensemaker, so a failed call there leaves the meme without any. memez which
+/// needed no repair are left out of the result.
+///
+/// moderation state is left alone, as memez only get it once they are flagged.
+fn reconcile_sm_data(_: ()) -> ExternResult<ListResult<EntryHash>> {
+    let cell_id = sensemaker_cell_id()?;
+    // a closure, rather than a block, so that `?` only abandons the one meme
+    let reconcile_meme = |meme_eh: EntryHash| -> ExternResult<bool> {
+        if get_sm_data(meme_eh.clone())?.is_some() {
+            return Ok(false);
+        }
+        let payload = (MEMEZ_PATH.to_string(), meme_eh);
+        remote_initialize_sm_data(cell_id.clone(), None, payload).map_err(sensemaker_error)?;
+        Ok(true)
+    };
+
+    let mut repaired: Vec<EntryHash> = Vec::new();
+    let mut errors: Vec<(EntryHash, String)> = Vec::new();
+    for lnk in MEME_BUCKETS.get_links()? {
+        let meme_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+        if repaired.contains(&meme_eh) {
+            continue;
+        }
+        match reconcile_meme(meme_eh.clone()) {
+            Ok(true) => repaired.push(meme_eh),
+            Ok(false) => {}
+            Err(err) => {
+                debug!("err in reconciling meme: {}", err);
+                errors.push((meme_eh, err.to_string()));
+            }
+        }
+    }
+    Ok(ListResult {
+        items: repaired,
+        errors,
+    })
+}
+
+fn score_preset_anchor() -> ExternResult<EntryHash> {
+    anchor("score_presets".into(), "".into())
 }
 
 #[hdk_extern]
-fn get_sm_data(
-    target_eh: EntryHash,
-) -> ExternResult<Option<(EntryHash, HeaderHash, SensemakerEntry)>> {
-    let path_string = compose_entry_hash_path(&MEMEZ_PATH.into(), target_eh);
-    get_sm_generic_with_hh(path_string, SM_DATA_TAG.to_string())
+fn create_score_preset(input: CreateScorePresetInput) -> ExternResult<(EntryHash, HeaderHash)> {
+    if input.name.trim().is_empty() {
+        return Err(WidgetError::ValidationFailed("a score preset needs a name".into()).into());
+    }
+    let score_preset = ScorePreset {
+        name: input.name,
+        description: input.description,
+        expr: input.expr,
+    };
+    let score_preset_hh = create_entry(&score_preset)?;
+    let score_preset_eh = hash_entry(&score_preset)?;
+    create_link(
+        score_preset_anchor()?,
+        score_preset_eh.clone(),
+        LinkType(0),
+        LinkTag::new(SCORE_PRESET_TAG),
+    )?;
+    Ok((score_preset_eh, score_preset_hh))
 }
 
 #[hdk_extern]
-fn get_sm_init(path_string: String) -> ExternResult<Option<(EntryHash, SensemakerEntry)>> {
-    get_sm_generic(path_string, SM_INIT_TAG.into())
+fn get_all_score_presets(_: ()) -> ExternResult<ListResult<(EntryHash, PublishedScorePreset)>> {
+    let score_preset_links =
+        get_links(score_preset_anchor()?, Some(LinkTag::new(SCORE_PRESET_TAG)))?;
+    let mut score_presets: Vec<(EntryHash, PublishedScorePreset)> = Vec::new();
+    let mut errors: Vec<(EntryHash, String)> = Vec::new();
+    for lnk in score_preset_links {
+        let score_preset_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+        match get_with_author(score_preset_eh.clone()) {
+            Ok((preset, author)) => {
+                score_presets.push((score_preset_eh, PublishedScorePreset { preset, author }))
+            }
+            Err(err) => {
+                debug!("err in fetching ScorePreset: {}", err);
+                errors.push((score_preset_eh, err.to_string()));
+            }
+        }
+    }
+    Ok(ListResult {
+        items: score_presets,
+        errors,
+    })
+}
+
+fn board_anchor() -> ExternResult<EntryHash> {
+    anchor("boards".into(), "".into())
 }
 
 #[hdk_extern]
-fn get_sm_comp(path_string: String) -> ExternResult<Option<(EntryHash, SensemakerEntry)>> {
-    get_sm_generic(path_string, SM_COMP_TAG.into())
+fn create_board(name: String) -> ExternResult<(EntryHash, HeaderHash)> {
+    let board = Board {
+        name,
+        author: agent_info()?.agent_initial_pubkey,
+    };
+    let board_hh = create_entry(&board)?;
+    let board_eh = hash_entry(&board)?;
+    create_link(
+        board_anchor()?,
+        board_eh.clone(),
+        LinkType(0),
+        LinkTag::new(BOARD_TAG),
+    )?;
+    Ok((board_eh, board_hh))
 }
 
-fn get_sm_generic(
-    path_string: String,
-    link_tag_string: String,
-) -> ExternResult<Option<(EntryHash, SensemakerEntry)>> {
-    let cell_id = get_sensemaker_cell_id(())?;
-    remote_get_sensemaker_entry_by_path(cell_id, None, (path_string, link_tag_string))
+#[hdk_extern]
+fn get_all_boards(_: ()) -> ExternResult<ListResult<(EntryHash, Board)>> {
+    let board_links = get_links(board_anchor()?, Some(LinkTag::new(BOARD_TAG)))?;
+    let mut boards: Vec<(EntryHash, Board)> = Vec::new();
+    let mut errors: Vec<(EntryHash, String)> = Vec::new();
+    for lnk in board_links {
+        let board_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+        match util::try_get_and_convert(board_eh.clone(), GetOptions::content())
+            .map_err(entry_not_found)
+        {
+            Ok(board) => boards.push((board_eh, board)),
+            Err(err) => {
+                debug!("err in fetching Board: {}", err);
+                errors.push((board_eh, err.to_string()));
+            }
+        }
+    }
+    Ok(ListResult {
+        items: boards,
+        errors,
+    })
 }
 
-fn get_sm_generic_with_hh(
-    path_string: String,
-    link_tag_string: String,
-) -> ExternResult<Option<(EntryHash, HeaderHash, SensemakerEntry)>> {
-    let cell_id = get_sensemaker_cell_id(())?;
-    remote_get_sensemaker_entry_by_path_with_hh(cell_id, None, (path_string, link_tag_string))
+fn get_board_meme_links(board_eh: EntryHash, meme_eh: EntryHash) -> ExternResult<Vec<Link>> {
+    let board_meme_links = get_links(board_eh, Some(LinkTag::new(BOARD_MEME_TAG)))?;
+    Ok(board_meme_links
+        .into_iter()
+        .filter(|lnk| lnk.target.clone().into_entry_hash() == Some(meme_eh.clone()))
+        .collect())
+}
+
+/// only a board's author may change which memez are on it. `validate` enforces the same for agents
+/// who bypass the externs.
+fn ensure_board_author(board_eh: EntryHash) -> ExternResult<()> {
+    let board: Board =
+        util::try_get_and_convert(board_eh, GetOptions::content()).map_err(entry_not_found)?;
+    if board.author != agent_info()?.agent_initial_pubkey {
+        return Err(WidgetError::NotAuthorized(format!(
+            "board {:?} belongs to another agent",
+            board.name
+        ))
+        .into());
+    }
+    Ok(())
 }
 
 #[hdk_extern]
-/// set the sm_init state for the path_string to the `rep_lang` interpretation of `expr_str`
-pub fn set_sm_init((path_string, expr_str): (String, String)) -> ExternResult<bool> {
-    set_sensemaker_entry(path_string, SM_INIT_TAG.into(), expr_str)
+/// adding a meme which is already on the board is a no-op.
+fn add_meme_to_board((board_eh, meme_eh): (EntryHash, EntryHash)) -> ExternResult<bool> {
+    ensure_board_author(board_eh.clone())?;
+    if !get_board_meme_links(board_eh.clone(), meme_eh.clone())?.is_empty() {
+        return Ok(false);
+    }
+    create_link(board_eh, meme_eh, LinkType(0), LinkTag::new(BOARD_MEME_TAG))?;
+    Ok(true)
 }
 
 #[hdk_extern]
-/// set the sm_comp state for the path_string to the `rep_lang` interpretation of `expr_str`
-pub fn set_sm_comp((path_string, expr_str): (String, String)) -> ExternResult<bool> {
-    set_sensemaker_entry(path_string, SM_COMP_TAG.into(), expr_str)
+fn remove_meme_from_board((board_eh, meme_eh): (EntryHash, EntryHash)) -> ExternResult<bool> {
+    ensure_board_author(board_eh.clone())?;
+    let board_meme_links = get_board_meme_links(board_eh, meme_eh)?;
+    let removed = !board_meme_links.is_empty();
+    for lnk in board_meme_links {
+        delete_link(lnk.create_link_hash)?;
+    }
+    Ok(removed)
 }
 
-fn set_sensemaker_entry(
-    path_string: String,
-    link_tag_string: String,
-    expr_str: String,
-) -> ExternResult<bool> {
-    let cell_id = get_sensemaker_cell_id(())?;
-    remote_set_sensemaker_entry_parse_rl_expr(
-        cell_id,
-        None,
-        (path_string, link_tag_string, expr_str),
+/// the tags of the links only sm admins may create, besides `widget_sensemaker::SM_ADMIN_TAGS`.
+/// they are read with `get_exact_links`.
+const ADMIN_TAGS: &[&str] = &[HIDE_COMP_TAG, AGENT_REP_PATHS_TAG];
+
+#[hdk_extern]
+/// `ensure_board_author` & `ensure_sm_admin` only guard the externs, so their rules are also
+/// enforced here, for every agent: a board names its actual author, only that author may add memez
+/// to it or remove them, and only sm admins may propose, approve & set sensemaker expressions, the
+/// hide computation and the agent reputation paths.
+fn validate(op: Op) -> ExternResult<ValidateCallbackResult> {
+    match op {
+        Op::StoreEntry { header, entry } => {
+            let header = header.hashed.content;
+            if *header.entry_type() != entry_type!(Board)? {
+                return Ok(ValidateCallbackResult::Valid);
+            }
+            validate_board_author(&Board::try_from(&entry), header.author())
+        }
+        Op::RegisterCreateLink { create_link } => {
+            let create_link = create_link.hashed.content;
+            match validate_sm_link(&create_link, ADMIN_TAGS)? {
+                ValidateCallbackResult::Valid => {
+                    validate_board_meme_link(&create_link, &create_link.author)
+                }
+                invalid => Ok(invalid),
+            }
+        }
+        Op::RegisterDeleteLink {
+            delete_link,
+            create_link,
+        } => validate_board_meme_link(&create_link, &delete_link.hashed.content.author),
+        _ => Ok(ValidateCallbackResult::Valid),
+    }
+}
+
+fn validate_board_author<E>(
+    board: &Result<Board, E>,
+    author: &AgentPubKey,
+) -> ExternResult<ValidateCallbackResult> {
+    Ok(match board {
+        Err(_) => ValidateCallbackResult::Invalid("not a board".into()),
+        Ok(board) if board.author != *author => ValidateCallbackResult::Invalid(format!(
+            "board {:?} belongs to another agent",
+            board.name
+        )),
+        Ok(_) => ValidateCallbackResult::Valid,
+    })
+}
+
+/// `author` created or deleted `create_link`.
+fn validate_board_meme_link(
+    create_link: &CreateLink,
+    author: &AgentPubKey,
+) -> ExternResult<ValidateCallbackResult> {
+    if create_link.tag != LinkTag::new(BOARD_MEME_TAG) {
+        return Ok(ValidateCallbackResult::Valid);
+    }
+    let board_entry = must_get_entry(create_link.base_address.clone())?;
+    validate_board_author(&Board::try_from(board_entry.as_content()), author)
+}
+
+#[hdk_extern]
+fn get_board_memez(board_eh: EntryHash) -> ExternResult<ListResult<(EntryHash, MemeThumbnail)>> {
+    let board_meme_links = get_links(board_eh, Some(LinkTag::new(BOARD_MEME_TAG)))?;
+    let mut memez: Vec<(EntryHash, MemeThumbnail)> = Vec::new();
+    let mut errors: Vec<(EntryHash, String)> = Vec::new();
+    for lnk in board_meme_links {
+        let meme_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+        match get_meme_thumbnail(meme_eh.clone()) {
+            Ok(thumbnail) => memez.push((meme_eh, thumbnail)),
+            Err(err) => {
+                debug!("err in fetching board meme: {}", err);
+                errors.push((meme_eh, err.to_string()));
+            }
+        }
+    }
+    Ok(ListResult {
+        items: memez,
+        errors,
+    })
+}
+
+fn feed_config_anchor() -> ExternResult<EntryHash> {
+    anchor("feed_config".into(), "".into())
+}
+
+#[hdk_extern]
+/// set the agent reputation paths which are summed into the `agent` argument of feed score
+/// computations. only sm_admins may set them.
+fn set_agent_rep_paths(paths: Vec<String>) -> ExternResult<bool> {
+    ensure_sm_admin()?;
+    let agent_rep_paths = AgentRepPaths { paths };
+    create_entry(&agent_rep_paths)?;
+    create_link(
+        feed_config_anchor()?,
+        hash_entry(&agent_rep_paths)?,
+        LinkType(0),
+        LinkTag::new(AGENT_REP_PATHS_TAG),
     )?;
     Ok(true)
 }
 
 #[hdk_extern]
-fn step_sm_remote((path_string, entry_hash, act): (String, EntryHash, String)) -> ExternResult<()> {
-    let cell_id = get_sensemaker_cell_id(())?;
-    remote_step_sm(cell_id, None, (path_string, entry_hash, act))
+fn get_agent_rep_paths(_: ()) -> ExternResult<Vec<String>> {
+    let agent_rep_paths_links = get_exact_links(feed_config_anchor()?, AGENT_REP_PATHS_TAG)?;
+    match agent_rep_paths_links
+        .into_iter()
+        .max_by_key(|lnk| lnk.timestamp)
+    {
+        None => Ok(vec![AGENT_PATH.into()]),
+        Some(lnk) => {
+            let agent_rep_paths_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+            let agent_rep_paths: AgentRepPaths =
+                util::try_get_and_convert(agent_rep_paths_eh, GetOptions::content())?;
+            Ok(agent_rep_paths.paths)
+        }
+    }
+}
+
+fn get_agent_rep_sm_data(
+    path: String,
+    agent_pk: AgentPubKey,
+) -> ExternResult<Option<(EntryHash, HeaderHash, SensemakerEntry)>> {
+    let agent_b64: String = base64::encode(agent_pk.into_inner());
+    let path_string = compose_paths(&path, &agent_b64);
+    get_sm_generic_with_hh(path_string, SM_DATA_TAG.to_string())
+}
+
+fn get_paperz_sm_data(
+    agent_pk: AgentPubKey,
+) -> ExternResult<Option<(EntryHash, HeaderHash, SensemakerEntry)>> {
+    get_agent_rep_sm_data(AGENT_PATH.into(), agent_pk)
 }
 
-// TODO figure out how to automate / streamline all these high-indirection methods
 #[hdk_extern]
-fn step_sm_path_remote(payload: (String, String, String)) -> ExternResult<()> {
-    let cell_id = get_sensemaker_cell_id(())?;
-    remote_step_sm_path(cell_id, None, payload)
+fn get_sm_data(
+    target_eh: EntryHash,
+) -> ExternResult<Option<(EntryHash, HeaderHash, SensemakerEntry)>> {
+    let path_string = compose_entry_hash_path(&MEMEZ_PATH.into(), target_eh);
+    get_sm_generic_with_hh(path_string, SM_DATA_TAG.to_string())
+}
+
+fn get_moderation_sm_data(
+    meme_eh: EntryHash,
+) -> ExternResult<Option<(EntryHash, HeaderHash, SensemakerEntry)>> {
+    let path_string = compose_entry_hash_path(&MODERATION_PATH.into(), meme_eh);
+    get_sm_generic_with_hh(path_string, SM_DATA_TAG.to_string())
 }
@@ -1,4 +1,77 @@
 pub mod types;
 
 pub const MEME_TAG: &str = "memez_meme";
+pub const THUMBNAIL_TAG: &str = "memez_thumbnail";
+pub const CLAP_TAG: &str = "memez_clap";
+pub const SCORE_PRESET_TAG: &str = "memez_score_preset";
+pub const FLAG_TAG: &str = "memez_flag";
+pub const HIDE_COMP_TAG: &str = "memez_hide_comp";
+pub const AGENT_REP_PATHS_TAG: &str = "memez_agent_rep_paths";
+pub const BATTLE_TAG: &str = "memez_battle";
+pub const BOARD_TAG: &str = "memez_board";
+pub const BOARD_MEME_TAG: &str = "memez_board_meme";
+pub const CONTENT_HASH_TAG: &str = "memez_content_hash";
+/// perceptual hash links carry the full hash after this prefix, so near-duplicate candidates can be
+/// compared without fetching anything.
+pub const PERCEPTUAL_HASH_TAG: &str = "memez_perceptual_hash:";
 pub const MEMEZ_PATH: &str = "widget.memez.memez";
+/// each flag steps a meme's moderation state with the flagger's paperz score as the action, so
+/// the moderation `sm_comp` can weigh flags by reputation (or ignore that, as it sees fit).
+pub const MODERATION_PATH: &str = "widget.memez.moderation";
+/// each meme's battle rating. see `BATTLE_SM_COMP`.
+pub const BATTLE_PATH: &str = "widget.memez.battle";
+
+/// claps younger than this are counted in the `window` argument to a feed score computation.
+pub const TRENDING_WINDOW_SECS: i64 = 24 * 60 * 60;
+
+/// perceptual hashes are indexed in this many equal bands (of 8 bits each). any two hashes within
+/// `PERCEPTUAL_HASH_BANDS - 1` bits of each other must share at least one band, so a lookup by band
+/// finds every near-duplicate.
+pub const PERCEPTUAL_HASH_BANDS: usize = 8;
+
+/// memez whose perceptual hashes differ in at most this many bits are considered near-duplicates.
+pub const NEAR_DUPLICATE_MAX_DISTANCE: u32 = 6;
+
+/// 4 MiB.
+pub const DEFAULT_MAX_MEME_BYTES: u64 = 4 * 1024 * 1024;
+
+/// the reputation used for an agent who has no sensemaker state on an agent reputation path.
+pub const DEFAULT_AGENT_REP: i64 = 0;
+
+/// a feed score computation is applied to `meme agent age window`, where:
+/// - `meme` is the meme's sensemaker state
+/// - `agent` is the sum of the viewing agent's sensemaker states on each of the configured agent
+///   reputation paths (`DEFAULT_AGENT_REP` for each one it is absent from). by default, that is
+///   just paperz's.
+/// - `age` is the number of seconds since the meme was uploaded
+/// - `window` is the number of claps the meme got in the last `TRENDING_WINDOW_SECS`
+///
+/// computations of the older `(lam [meme agent] ...)` form are still accepted.
+pub const FEED_SCORE_COMP_PARAMS: [&str; 4] = ["meme", "agent", "age", "window"];
+
+/// see `FEED_SCORE_COMP_PARAMS` for the arguments.
+pub const STARTER_FEED_SCORE_COMP: &str = "(lam [meme agent age window] (+ meme agent))";
+
+/// "gravity" decay: the meme's score plus its recent claps (so those count double), divided by the
+/// square of the meme's age in hours (offset by 2, so brand new memez don't divide by ~0).
+pub const TRENDING_FEED_SCORE_COMP: &str = "(lam [meme agent age window] (/ (* 1000 (+ meme window)) (* (+ 2 (/ age 3600)) (+ 2 (/ age 3600)))))";
+
+/// a hide computation is applied to a meme's moderation state. the meme is hidden if the result is
+/// positive, so this hides memez once more than 5 (weighted) flags have accumulated.
+pub const STARTER_HIDE_COMP: &str = "(lam [flags] (- flags 5))";
+
+/// every meme enters battle with the same rating.
+pub const BATTLE_SM_INIT: &str = "1500";
+
+/// an integer Elo update, with a K-factor of 32.
+///
+/// `rep_lang` state machines take a single action, so `record_battle` packs the opponent's rating
+/// and the outcome into one: `act = 2 * opponent + won`, where `won` is 1 for the winner and 0 for
+/// the loser. the expected score (scaled by K) is approximated linearly as
+/// `16 + (rating - opponent) / 25`, which tracks the logistic curve closely for rating gaps of up
+/// to a few hundred points, and is clamped to `[0, 32]` beyond that, so a win never costs rating
+/// and a loss never gains any.
+///
+/// `rep_lang` has no ordering comparisons, so the clamp is built from `relu x = max(0, x)`, which is
+/// `x * ((x + B) / B)` for any `|x| < B`.
+pub const BATTLE_SM_COMP: &str = "(lam [st act] ((lam [relu] (+ st (- (* 32 (- act (* 2 (/ act 2)))) (- 32 (relu (- 32 (relu (+ 16 (/ (- st (/ act 2)) 25))))))))) (lam [x] (* x (/ (+ x 1000000000) 1000000000)))))";
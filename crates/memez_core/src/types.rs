@@ -1,5 +1,8 @@
 use hdk::prelude::*;
 
+use widget_core::config::SensemakerProperties;
+pub use widget_core::types::ListResult;
+
 #[hdk_entry]
 #[derive(Clone)]
 pub struct Meme {
@@ -8,3 +11,160 @@ pub struct Meme {
     // encoded file bytes payload
     pub blob_str: String,
 }
+
+/// a small rendition of a `Meme`, linked from it, which is what the feed renders.
+#[hdk_entry]
+#[derive(Clone)]
+pub struct MemeThumbnail {
+    // must include extension
+    pub filename: String,
+    // encoded file bytes payload
+    pub blob_str: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UploadMemeInput {
+    pub meme: Meme,
+    /// encoded thumbnail bytes. if absent, the feed falls back to the full `Meme`.
+    pub thumbnail_blob_str: Option<String>,
+    /// a 64-bit perceptual hash of the image, as 16 hex digits. if absent, only exact duplicates
+    /// are detected.
+    pub perceptual_hash: Option<String>,
+    /// upload even if near-duplicates exist. exact duplicates are always rejected.
+    pub allow_near_duplicate: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum UploadMemeOutcome {
+    Uploaded(EntryHash, HeaderHash),
+    /// the exact same meme already exists. nothing was uploaded.
+    Duplicate(EntryHash),
+    /// similar looking memez already exist. nothing was uploaded.
+    NearDuplicates(Vec<(EntryHash, MemeThumbnail)>),
+}
+
+/// a named, shareable feed score computation.
+#[hdk_entry]
+#[derive(Clone)]
+pub struct ScorePreset {
+    pub name: String,
+    pub description: String,
+    /// a `rep_lang` feed score computation. see `FEED_SCORE_COMP_PARAMS` for its arguments.
+    pub expr: String,
+}
+
+/// a `ScorePreset` as listed by `get_all_score_presets`. `author` is whoever committed the preset's
+/// entry, rather than anything the preset claims about itself.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublishedScorePreset {
+    pub preset: ScorePreset,
+    pub author: AgentPubKey,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateScorePresetInput {
+    pub name: String,
+    pub description: String,
+    pub expr: String,
+}
+
+/// the latest of these (by link timestamp) decides which memez are hidden.
+#[hdk_entry]
+#[derive(Clone)]
+pub struct HideComp {
+    /// see `STARTER_HIDE_COMP`.
+    pub expr: String,
+}
+
+/// the latest of these (by link timestamp) decides which agent reputation paths feed score
+/// computations are applied to.
+#[hdk_entry]
+#[derive(Clone)]
+pub struct AgentRepPaths {
+    pub paths: Vec<String>,
+}
+
+/// the outcome of one pairwise comparison in battle mode.
+#[hdk_entry]
+#[derive(Clone)]
+pub struct MemeBattle {
+    pub winner: EntryHash,
+    pub loser: EntryHash,
+}
+
+/// a named collection of memez, curated by its author.
+#[hdk_entry]
+#[derive(Clone)]
+pub struct Board {
+    pub name: String,
+    pub author: AgentPubKey,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FeedItem {
+    pub meme_eh: EntryHash,
+    pub thumbnail: MemeThumbnail,
+    pub score: i64,
+    /// whether the community's hide computation hides this meme. hidden memez are still returned,
+    /// so that UIs can blur them & offer to show them anyway.
+    pub hidden: bool,
+    /// `None` if the meme hasn't been in a battle yet.
+    pub battle_rating: Option<i64>,
+}
+
+/// the candidate computations `simulate_feed_scores` tries out.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SimulateFeedInput {
+    /// a candidate sm_comp for `MEMEZ_PATH`. `None` keeps the memez' current states.
+    pub sm_comp: Option<String>,
+    /// the feed score computation in use, which the current scores are computed with.
+    pub current_feed_score_comp: String,
+    /// the candidate feed score computation. may be the current one, to only try out `sm_comp`.
+    pub feed_score_comp: String,
+}
+
+/// a meme's feed score as things are, next to the one the candidate computations would give it.
+/// `None` if there is no score, e.g. as the meme has no sensemaker data yet.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SimulatedScore {
+    pub meme_eh: EntryHash,
+    pub current: Option<i64>,
+    pub simulated: Option<i64>,
+}
+
+/// the memez DNA properties. see `widget_core::config`.
+#[derive(Serialize, Deserialize, SerializedBytes, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct MemezConfig {
+    #[serde(flatten)]
+    pub sensemaker: SensemakerProperties,
+    /// the `rep_lang` expressions the UI starts out with for the memez & moderation paths.
+    pub sm_init_expr: String,
+    pub sm_comp_expr: String,
+    /// the feed score computation used until the agent picks another. see
+    /// `STARTER_FEED_SCORE_COMP`.
+    pub feed_score_comp: String,
+    /// the hide computation in effect until one is set with `set_hide_comp`.
+    pub hide_comp: String,
+    /// the largest meme accepted, in (decoded) bytes. the UI downscales images well below this.
+    pub max_meme_bytes: u64,
+    /// lowercase file extensions accepted for memez. empty means any.
+    pub allowed_extensions: Vec<String>,
+}
+
+impl Default for MemezConfig {
+    fn default() -> Self {
+        Self {
+            sensemaker: SensemakerProperties::default(),
+            sm_init_expr: widget_core::config::STARTER_SM_INIT_EXPR.into(),
+            sm_comp_expr: widget_core::config::STARTER_SM_COMP_EXPR.into(),
+            feed_score_comp: crate::STARTER_FEED_SCORE_COMP.into(),
+            hide_comp: crate::STARTER_HIDE_COMP.into(),
+            max_meme_bytes: crate::DEFAULT_MAX_MEME_BYTES,
+            allowed_extensions: ["png", "jpg", "jpeg", "gif", "webp"]
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+        }
+    }
+}
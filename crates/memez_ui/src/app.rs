@@ -1,23 +1,69 @@
 use base64::encode;
+use std::collections::BTreeMap;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
-use web_sys::HtmlInputElement as InputElement;
+use web_sys::{HtmlInputElement as InputElement, HtmlSelectElement as SelectElement};
 use weblog::{console_error, console_log};
 use yew::{html::Scope, prelude::*};
 
 // use social_sensemaker_core::SENSEMAKER_ZOME_NAME;
 
 use holochain_client_wrapper::{
-    AdminWebsocket, AdminWsCmd, AdminWsCmdResponse, AppWebsocket, AppWsCmd, AppWsCmdResponse,
-    CellId, DeserializeFromJsObj, EntryHashRaw, EntryHeaderHashPairRaw, SerializeToJsObj,
+    agent_pk_to_vec_u8, AdminWebsocket, AdminWsCmd, AdminWsCmdResponse, AppWebsocket, AppWsCmd,
+    AppWsCmdResponse, CellId, DeserializeFromJsObj, EntryHashRaw, SerializeToJsObj,
+};
+use memez_core::{
+    types::{
+        Board, CreateScorePresetInput, Meme, MemeThumbnail, MemezConfig, PublishedScorePreset,
+        SimulateFeedInput, UploadMemeInput,
+    },
+    BATTLE_PATH, BATTLE_SM_COMP, BATTLE_SM_INIT, FEED_SCORE_COMP_PARAMS, MEMEZ_PATH,
+    MODERATION_PATH, STARTER_FEED_SCORE_COMP, TRENDING_FEED_SCORE_COMP,
+};
+use widget_core::error::WidgetError;
+use widget_helpers::{
+    expr_builder::{ExprBuilder, SM_COMP_PARAMS},
+    file_upload::{FileBytes, FileUploadApp},
+    image_preprocess::ImagePreprocessConfig,
+    rep_lang_check::{check_int_fn, check_sm_comp, check_sm_init, view_expr_error, ExprError},
+    score_preview::{view_score_preview, ScoreDiffRow, ScorePreview, SimulatedScores},
+    sensemaker_discovery::{
+        find_sensemaker_cells, get_sensemaker_cell_id, set_sensemaker_cell_id, SensemakerCandidate,
+    },
+    sm_config_history::{
+        get_sm_config_history, rollback_sm_expr, view_sm_config_history, SmConfigHistoryRaw,
+    },
+    sm_exprs::{view_sm_expr_note, SmExprRaw, SmExprsRaw},
+    sm_op_retry::SmOpRetryRaw,
+    sm_proposals::{approve_sm_proposal, get_sm_proposals, view_sm_proposals, SmProposalRaw},
 };
-use memez_core::{types::Meme, MEMEZ_PATH};
-use widget_helpers::file_upload::{FileBytes, FileUploadApp};
 
 use crate::js_ser_de::*;
 
 const MEMEZ_ZOME_NAME: &str = "memez_main_zome";
-// TODO get rid of this once we're using proper sensemaker app name
-const TEST_APP_NAME: &str = "test-app";
+/// localStorage key under which the chosen `feed_score_comp` survives reloads.
+const FEED_SCORE_COMP_STORAGE_KEY: &str = "memez.feed_score_comp";
+
+/// (path, label) for each sensemaker path memez configures.
+const SM_PATHS: [(&str, &str); 3] = [
+    (MEMEZ_PATH, "meme"),
+    (MODERATION_PATH, "moderation"),
+    (BATTLE_PATH, "battle"),
+];
+
+/// (name, description, expr) for the presets which are built into memez, rather than on the DHT.
+const BUILTIN_SCORE_PRESETS: [(&str, &str, &str); 2] = [
+    (
+        "top",
+        "meme score plus your paperz score",
+        STARTER_FEED_SCORE_COMP,
+    ),
+    (
+        "trending",
+        "recent claps, decayed by age",
+        TRENDING_FEED_SCORE_COMP,
+    ),
+];
 
 pub enum Msg {
     AdminWs(WsMsg<AdminWsCmd, AdminWsCmdResponse>),
@@ -25,11 +71,95 @@ pub enum Msg {
     Log(String),
     Error(String),
     ZomeCallResponse(ZomeCallResponse),
-    BrowserUploadedMeme(Meme),
+    BrowserUploadedMeme(UploadMemeInput),
+    Config(MemezConfig),
     SensemakerPresent(bool),
-    SmInitSubmit(String),
-    SmCompSubmit(String),
+    /// the sensemaker cells found, and the one the memez cell currently uses.
+    SensemakerDiscovered(Vec<SensemakerCandidate>, Option<CellId>),
+    /// point the memez cell at `sensemaker_candidates[i]`.
+    SelectSensemaker(usize),
+    SensemakerCellIdSet(CellId),
+    ToggleSensemakerSettings,
+    /// (path, expr)
+    SmInitSubmit(String, String),
+    /// (path, expr)
+    SmCompSubmit(String, String),
+    /// the expressions currently set on the DHT for a path.
+    SmExprs(String, SmExprsRaw),
+    /// (path, expr), once `set_sm_init` succeeded.
+    SmInitSet(String, String),
+    /// (path, expr), once `set_sm_comp` succeeded.
+    SmCompSet(String, String),
+    /// the path whose `set_sm_init` / `set_sm_comp` was proposed, rather than set, as it needs more
+    /// approvals.
+    SmProposed(String),
+    SmSetFailed(String),
+    DismissSmSetError,
+    /// (path, the changes proposed to its expressions which haven't been set yet)
+    SmProposals(String, Result<Vec<SmProposalRaw>, String>),
+    /// approve the i-th of the path's proposals.
+    ApproveSmProposal(String, usize),
+    /// (path, whether the approval got the proposal set)
+    SmProposalApproved(String, bool),
+    /// show or hide the history of a path's expressions.
+    ToggleSmHistory(String),
+    /// (path, the first page of the changes made to its expressions, newest first)
+    SmHistory(String, Result<SmConfigHistoryRaw, String>),
+    /// fetch the next (older) page of the history which is shown.
+    LoadMoreSmHistory,
+    /// (path, the next page of its history)
+    SmHistoryPage(String, Result<SmConfigHistoryRaw, String>),
+    /// roll the path whose history is shown back to the i-th change in it.
+    RollbackSm(usize),
+    /// (the path which was rolled back, whether the rollback was set, rather than proposed)
+    RolledBack(String, bool),
+    /// (input label, submitted expr, why it was rejected)
+    ExprInvalid(String, String, ExprError),
+    /// the expression submitted via the labelled input passed the checks.
+    ExprValid(String),
     ClapForMeme(EntryHashRaw),
+    FlagMeme(EntryHashRaw),
+    RevealMeme(EntryHashRaw),
+    HideCompSubmit(String),
+    /// why `set_hide_comp` or `set_agent_rep_paths` failed.
+    ModerationSetFailed(String),
+    /// comma-separated agent reputation paths
+    AgentRepPathsSubmit(String),
+    FeedScoreCompSubmit(String),
+    /// simulate a change to the feed scores, before it can be applied.
+    PreviewScores(PreviewedChange),
+    ScoresPreviewed(Result<ListResultRaw<SimulatedScoreRaw>, String>),
+    ConfirmScorePreview,
+    CancelScorePreview,
+    RefreshMemez,
+    PublishScorePreset,
+    OpenMeme(EntryHashRaw),
+    CloseMeme,
+    /// set the battle path's sm_init & sm_comp to the built-in Elo update.
+    InstallBattleSm,
+    /// one-time move of memez off the legacy single anchor.
+    MigrateMemeAnchor,
+    StartBattle,
+    /// (winner, loser)
+    PickBattleWinner(EntryHashRaw, EntryHashRaw),
+    EndBattle,
+    RankByBattle(bool),
+    CreateBoard(String),
+    OpenBoard(EntryHashRaw),
+    CloseBoard,
+    /// (board, meme)
+    SaveToBoard(EntryHashRaw, EntryHashRaw),
+    /// (board, meme)
+    RemoveFromBoard(EntryHashRaw, EntryHashRaw),
+    /// re-upload the meme from the `DuplicatePrompt`, accepting near-duplicates.
+    UploadAnyway,
+    DismissDuplicatePrompt,
+    DismissError,
+    /// initialize the sensemaker data of any memez which are missing it, then refetch.
+    ReconcileSmData,
+    /// apply the sensemaker operations (e.g. claps) queued while the sensemaker was unreachable,
+    /// then refetch.
+    RetryPendingSmOps,
 }
 
 pub enum WsMsg<WSCMD, WSCMDRESP> {
@@ -38,26 +168,86 @@ pub enum WsMsg<WSCMD, WSCMDRESP> {
 }
 
 pub enum ZomeCallResponse {
-    Memes(Vec<(EntryHashRaw, Meme, i64)>),
-    UploadMeme(EntryHashRaw, Meme),
+    Memes(ListResultRaw<FeedItemRaw>),
+    HideComp(String),
+    AgentRepPaths(Vec<String>),
+    UploadMeme(EntryHashRaw, MemeThumbnail),
+    DuplicateMeme(DuplicatePrompt),
+    Meme(EntryHashRaw, Meme),
+    ScorePresets(Vec<(EntryHashRaw, PublishedScorePreset)>),
+    Boards(Vec<(EntryHashRaw, Board)>),
+    BoardMemez(EntryHashRaw, Vec<(EntryHashRaw, MemeThumbnail)>),
+}
+
+/// shown when an upload was turned down for (nearly) duplicating existing memez.
+pub struct DuplicatePrompt {
+    /// `None` for exact duplicates, which can't be uploaded anyway.
+    retry_input: Option<UploadMemeInput>,
+    existing: Vec<(EntryHashRaw, MemeThumbnail)>,
+}
+
+/// a change which alters feed scores, held back by a `ScorePreview`.
+pub enum PreviewedChange {
+    /// a new `MEMEZ_PATH` sm_comp.
+    SmComp(String),
+    /// a new feed score computation, which is only kept locally.
+    FeedScoreComp(String),
 }
 
 pub struct Model {
     admin_ws: AdminWebsocket,
     app_ws: AppWebsocket,
     memez_cell_id: CellId,
-    memez: Vec<(EntryHashRaw, Meme, i64)>,
+    memez: Vec<FeedItemRaw>,
+    /// memez which `get_all_memez` couldn't load, with the reason.
+    memez_errors: Vec<(EntryHashRaw, String)>,
+    /// hidden memez which the user chose to show anyway.
+    revealed_memez: Vec<EntryHashRaw>,
+    /// the meme currently opened at full resolution. `None` for the `Meme` means it's loading.
+    opened_meme: Option<(EntryHashRaw, Option<Meme>)>,
     /// None means we don't know yet (no response). for `Some(b)`, `b == True` indicates presence.
     sensemaker_present: Option<bool>,
-    /// (sm_init_expr_string, sm_comp_expr_string)
-    meme_sm: (String, String),
+    /// path -> its (sm_init, sm_comp) expressions, as in use on the DHT. missing until known.
+    /// the defaults are shown for those which aren't set, or whose source isn't known.
+    sm_exprs: BTreeMap<String, SmExprsRaw>,
+    /// why the last `set_sm_init` / `set_sm_comp` / `rollback_sm_expr` failed, until dismissed.
+    sm_set_error: Option<String>,
+    /// the path whose history is shown, and its changes. `None` for the changes until loaded.
+    sm_history: Option<(String, Option<SmConfigHistoryRaw>)>,
+    /// path -> its pending proposals.
+    sm_proposals: BTreeMap<String, Vec<SmProposalRaw>>,
+    /// input label -> (submitted expr, why it was rejected), for expressions which failed to check.
+    expr_errors: BTreeMap<String, (String, ExprError)>,
+    score_preview: Option<ScorePreview<PreviewedChange>>,
     feed_score_comp: String,
+    hide_comp: String,
+    /// the memez DNA's properties. defaults until `get_config` responds.
+    config: MemezConfig,
+    agent_rep_paths: Vec<String>,
+    score_presets: Vec<(EntryHashRaw, PublishedScorePreset)>,
+    preset_name_ref: NodeRef,
+    preset_description_ref: NodeRef,
+    /// the pair of memez currently up against each other in battle mode.
+    battle: Option<(FeedItemRaw, FeedItemRaw)>,
+    /// whether the feed is ranked by battle rating, rather than by `feed_score_comp`.
+    rank_by_battle: bool,
+    boards: Vec<(EntryHashRaw, Board)>,
+    /// the board currently being viewed. `None` for its memez means they're loading.
+    opened_board: Option<(EntryHashRaw, Option<Vec<(EntryHashRaw, MemeThumbnail)>>)>,
+    duplicate_prompt: Option<DuplicatePrompt>,
+    /// the most recent zome error which the user hasn't dismissed yet.
+    last_error: Option<WidgetError>,
+    /// set when the memez cell doesn't know the sensemaker cell id yet.
+    sensemaker_cell_not_set: bool,
+    sensemaker_candidates: Vec<SensemakerCandidate>,
+    /// the sensemaker cell the memez cell uses, if it has been set.
+    sensemaker_cell_id: Option<CellId>,
+    show_sensemaker_settings: bool,
+    /// whether `reconcile_sm_data` has already been triggered automatically, so that memez it
+    /// can't repair don't cause a refetch loop.
+    sm_data_reconcile_attempted: bool,
 }
 
-const STARTER_SM_INIT_EXPR_STRING: &str = "0";
-const STARTER_SM_COMP_EXPR_STRING: &str = "+";
-const STARTER_FEED_SCORE_COMP: &str = "+";
-
 #[derive(Properties, PartialEq)]
 pub struct ModelProps {
     pub admin_ws_js: JsValue,
@@ -72,28 +262,55 @@ impl Component for Model {
     fn create(ctx: &Context<Self>) -> Self {
         let props = ctx.props();
         let cell_id = CellId::deserialize_from_js_obj(props.cell_id_js.clone());
-        let cell_id_ = cell_id.clone();
         let app_ws: AppWebsocket = props.app_ws_js.clone().into();
-        let app_ws_: AppWebsocket = app_ws.clone();
+        let config = MemezConfig::default();
+        let feed_score_comp = local_storage()
+            .and_then(|storage| storage.get_item(FEED_SCORE_COMP_STORAGE_KEY).ok().flatten())
+            .unwrap_or_else(|| config.feed_score_comp.clone());
+        ctx.link().send_future(get_all_memez(
+            app_ws.clone(),
+            cell_id.clone(),
+            feed_score_comp.clone(),
+        ));
+        ctx.link()
+            .send_future(get_all_score_presets(app_ws.clone(), cell_id.clone()));
+        ctx.link()
+            .send_future(get_all_boards(app_ws.clone(), cell_id.clone()));
+        let app_ws_ = app_ws.clone();
+        let cell_id_ = cell_id.clone();
+        ctx.link().send_future(async move {
+            let cmd = AppWsCmd::CallZome {
+                cell_id: cell_id_.clone(),
+                zome_name: MEMEZ_ZOME_NAME.into(),
+                fn_name: "get_hide_comp".into(),
+                payload: JsValue::NULL,
+                provenance: cell_id_.1.clone(),
+                cap: "".into(),
+            };
+            let resp = app_ws_.call(cmd).await;
+            match resp {
+                Ok(AppWsCmdResponse::CallZome(val)) => Msg::ZomeCallResponse(
+                    ZomeCallResponse::HideComp(String::deserialize_from_js_obj(val)),
+                ),
+                Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                Err(err) => Msg::Error(format!("err: {:?}", err)),
+            }
+        });
+        let app_ws_ = app_ws.clone();
+        let cell_id_ = cell_id.clone();
         ctx.link().send_future(async move {
             let cmd = AppWsCmd::CallZome {
                 cell_id: cell_id_.clone(),
                 zome_name: MEMEZ_ZOME_NAME.into(),
-                fn_name: "get_all_memez".into(),
-                payload: (STARTER_FEED_SCORE_COMP.to_string(), cell_id_.1.clone())
-                    .serialize_to_js_obj(),
+                fn_name: "get_config".into(),
+                payload: JsValue::NULL,
                 provenance: cell_id_.1.clone(),
                 cap: "".into(),
             };
             let resp = app_ws_.call(cmd).await;
             match resp {
                 Ok(AppWsCmdResponse::CallZome(val)) => {
-                    Msg::ZomeCallResponse(ZomeCallResponse::Memes(
-                        MemeEhScoreVec::deserialize_from_js_obj_(val)
-                            .into_iter()
-                            .map(|x| x.into())
-                            .collect(),
-                    ))
+                    Msg::Config(MemezConfig::deserialize_from_js_obj_(val))
                 }
                 Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
                 Err(err) => Msg::Error(format!("err: {:?}", err)),
@@ -101,32 +318,89 @@ impl Component for Model {
         });
         let admin_ws: AdminWebsocket = props.admin_ws_js.clone().into();
         let admin_ws_ = admin_ws.clone();
+        let app_ws_ = app_ws.clone();
+        let cell_id_ = cell_id.clone();
+        ctx.link().send_future(async move {
+            let current = match get_sensemaker_cell_id(&app_ws_, &cell_id_, MEMEZ_ZOME_NAME).await {
+                Ok(current) => current,
+                Err(err) => return Msg::Error(err),
+            };
+            match find_sensemaker_cells(&admin_ws_, &app_ws_, &cell_id_, current.as_ref()).await {
+                Ok(candidates) => Msg::SensemakerDiscovered(candidates, current),
+                Err(err) => Msg::Error(err),
+            }
+        });
+
+        let app_ws_ = app_ws.clone();
+        let cell_id_ = cell_id.clone();
         ctx.link().send_future(async move {
-            let resp = admin_ws_.call(AdminWsCmd::ListActiveApps).await;
+            let cmd = AppWsCmd::CallZome {
+                cell_id: cell_id_.clone(),
+                zome_name: MEMEZ_ZOME_NAME.into(),
+                fn_name: "get_agent_rep_paths".into(),
+                payload: JsValue::NULL,
+                provenance: cell_id_.1.clone(),
+                cap: "".into(),
+            };
+            let resp = app_ws_.call(cmd).await;
             match resp {
-                Ok(AdminWsCmdResponse::ListActiveApps(active_apps)) => {
-                    let sensemaker_present = active_apps.contains(&TEST_APP_NAME.into());
-                    Msg::SensemakerPresent(sensemaker_present)
-                }
+                Ok(AppWsCmdResponse::CallZome(val)) => Msg::ZomeCallResponse(
+                    ZomeCallResponse::AgentRepPaths(Vec::<String>::deserialize_from_js_obj_(val)),
+                ),
                 Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
                 Err(err) => Msg::Error(format!("err: {:?}", err)),
             }
         });
 
         // state machine setup
-        let meme_sm: (String, String) = (
-            STARTER_SM_INIT_EXPR_STRING.into(),
-            STARTER_SM_COMP_EXPR_STRING.into(),
-        );
+        for (path, _label) in SM_PATHS.iter() {
+            ctx.link().send_future(get_sm_exprs(
+                app_ws.clone(),
+                cell_id.clone(),
+                path.to_string(),
+            ));
+            let app_ws_ = app_ws.clone();
+            let cell_id_ = cell_id.clone();
+            ctx.link().send_future(async move {
+                let result =
+                    get_sm_proposals(&app_ws_, &cell_id_, MEMEZ_ZOME_NAME, path.to_string()).await;
+                Msg::SmProposals(path.to_string(), result)
+            });
+        }
 
         Self {
             admin_ws,
             app_ws,
             memez_cell_id: cell_id.clone(),
             memez: Vec::new(),
+            memez_errors: Vec::new(),
+            revealed_memez: Vec::new(),
+            opened_meme: None,
             sensemaker_present: None,
-            meme_sm,
-            feed_score_comp: "+".into(),
+            sm_exprs: BTreeMap::new(),
+            sm_set_error: None,
+            sm_history: None,
+            sm_proposals: BTreeMap::new(),
+            expr_errors: BTreeMap::new(),
+            score_preview: None,
+            feed_score_comp,
+            hide_comp: config.hide_comp.clone(),
+            config,
+            agent_rep_paths: Vec::new(),
+            score_presets: Vec::new(),
+            preset_name_ref: NodeRef::default(),
+            preset_description_ref: NodeRef::default(),
+            battle: None,
+            rank_by_battle: false,
+            boards: Vec::new(),
+            opened_board: None,
+            duplicate_prompt: None,
+            last_error: None,
+            sensemaker_cell_not_set: false,
+            sensemaker_candidates: Vec::new(),
+            sensemaker_cell_id: None,
+            show_sensemaker_settings: false,
+            sm_data_reconcile_attempted: false,
         }
     }
 
@@ -178,7 +452,24 @@ impl Component for Model {
 
             Msg::Error(err) => {
                 console_error!("Error: {}", err);
-                false
+                match WidgetError::decode(&err) {
+                    Some(WidgetError::SensemakerMissing(_)) => {
+                        self.sensemaker_present = Some(false);
+                    }
+                    Some(WidgetError::SensemakerCellNotSet) => {
+                        self.sensemaker_cell_not_set = true;
+                    }
+                    Some(widget_err) => {
+                        self.last_error = Some(widget_err);
+                    }
+                    None => {}
+                }
+                true
+            }
+
+            Msg::DismissError => {
+                self.last_error = None;
+                true
             }
 
             Msg::Log(err) => {
@@ -186,18 +477,101 @@ impl Component for Model {
                 false
             }
 
-            Msg::ZomeCallResponse(ZomeCallResponse::Memes(meme_vec)) => {
-                self.memez = meme_vec;
+            Msg::ZomeCallResponse(ZomeCallResponse::Memes(list_result)) => {
+                log_list_errors("get_all_memez", &list_result.errors);
+                self.memez = list_result.items;
+                self.memez_errors = list_result.errors;
                 console_log!("got meme_vec");
+                let sm_data_missing = self.memez_errors.iter().any(|(_meme_eh, err)| {
+                    matches!(
+                        WidgetError::decode(err),
+                        Some(WidgetError::SmDataMissing(_))
+                    )
+                });
+                if sm_data_missing && !self.sm_data_reconcile_attempted {
+                    self.sm_data_reconcile_attempted = true;
+                    ctx.link().send_message(Msg::ReconcileSmData);
+                }
+                true
+            }
+
+            Msg::ReconcileSmData => {
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                let feed_score_comp = self.feed_score_comp.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: MEMEZ_ZOME_NAME.into(),
+                        fn_name: "reconcile_sm_data".into(),
+                        payload: JsValue::NULL,
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        // refetch, so any repaired memez show up
+                        Ok(AppWsCmdResponse::CallZome(_val)) => {
+                            get_all_memez(app_ws_, cell_id_, feed_score_comp).await
+                        }
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Msg::Error(format!("err: {:?}", err)),
+                    }
+                });
+                false
+            }
+
+            Msg::Config(config) => {
+                // only replace what the user hasn't chosen or edited yet
+                if self.hide_comp == self.config.hide_comp {
+                    self.hide_comp = config.hide_comp.clone();
+                }
+                if self.feed_score_comp == self.config.feed_score_comp
+                    && self.feed_score_comp != config.feed_score_comp
+                {
+                    self.feed_score_comp = config.feed_score_comp.clone();
+                    ctx.link().send_future(get_all_memez(
+                        self.app_ws.clone(),
+                        self.memez_cell_id.clone(),
+                        self.feed_score_comp.clone(),
+                    ));
+                }
+                self.config = config;
                 true
             }
 
-            Msg::ZomeCallResponse(ZomeCallResponse::UploadMeme(meme_eh, meme)) => {
-                self.memez.push((meme_eh, meme, 0));
+            Msg::RefreshMemez => {
+                ctx.link().send_future(get_all_memez(
+                    self.app_ws.clone(),
+                    self.memez_cell_id.clone(),
+                    self.feed_score_comp.clone(),
+                ));
+                false
+            }
+
+            Msg::ZomeCallResponse(ZomeCallResponse::UploadMeme(meme_eh, thumbnail)) => {
+                self.memez.push(FeedItemRaw {
+                    meme_eh,
+                    thumbnail,
+                    score: 0,
+                    hidden: false,
+                    battle_rating: None,
+                });
                 true
             }
 
-            Msg::BrowserUploadedMeme(meme) => {
+            Msg::ZomeCallResponse(ZomeCallResponse::Meme(meme_eh, meme)) => {
+                match &mut self.opened_meme {
+                    Some((opened_eh, opt_meme)) if *opened_eh == meme_eh => {
+                        *opt_meme = Some(meme);
+                        true
+                    }
+                    // the user has moved on since requesting this meme
+                    _ => false,
+                }
+            }
+
+            Msg::BrowserUploadedMeme(input) => {
                 let ws = self.app_ws.clone();
                 let cell_id = self.memez_cell_id.clone();
                 ctx.link().send_future(async move {
@@ -205,16 +579,40 @@ impl Component for Model {
                         cell_id: cell_id.clone(),
                         zome_name: MEMEZ_ZOME_NAME.into(),
                         fn_name: "upload_meme".into(),
-                        payload: meme.clone().serialize_to_js_obj_(),
+                        payload: input.clone().serialize_to_js_obj_(),
                         provenance: cell_id.1.clone(),
                         cap: "".into(),
                     };
                     let resp = ws.call(cmd).await;
                     match resp {
                         Ok(AppWsCmdResponse::CallZome(val)) => {
-                            let (meme_eh, _meme_hh) =
-                                EntryHeaderHashPairRaw::deserialize_from_js_obj_(val);
-                            Msg::ZomeCallResponse(ZomeCallResponse::UploadMeme(meme_eh, meme))
+                            let thumbnail = MemeThumbnail {
+                                filename: input.meme.filename.clone(),
+                                blob_str: input
+                                    .thumbnail_blob_str
+                                    .clone()
+                                    .unwrap_or_else(|| input.meme.blob_str.clone()),
+                            };
+                            match UploadMemeOutcomeRaw::deserialize_from_js_obj_(val) {
+                                UploadMemeOutcomeRaw::Uploaded(meme_eh) => Msg::ZomeCallResponse(
+                                    ZomeCallResponse::UploadMeme(meme_eh, thumbnail),
+                                ),
+                                // it's the same image, so our own thumbnail will do
+                                UploadMemeOutcomeRaw::Duplicate(meme_eh) => Msg::ZomeCallResponse(
+                                    ZomeCallResponse::DuplicateMeme(DuplicatePrompt {
+                                        retry_input: None,
+                                        existing: vec![(meme_eh, thumbnail)],
+                                    }),
+                                ),
+                                UploadMemeOutcomeRaw::NearDuplicates(existing) => {
+                                    Msg::ZomeCallResponse(ZomeCallResponse::DuplicateMeme(
+                                        DuplicatePrompt {
+                                            retry_input: Some(input),
+                                            existing,
+                                        },
+                                    ))
+                                }
+                            }
                         }
                         Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
                         Err(err) => Msg::Error(format!("err: {:?}", err)),
@@ -223,173 +621,1726 @@ impl Component for Model {
                 true
             }
 
+            Msg::ZomeCallResponse(ZomeCallResponse::DuplicateMeme(duplicate_prompt)) => {
+                self.duplicate_prompt = Some(duplicate_prompt);
+                true
+            }
+
+            Msg::UploadAnyway => {
+                let opt_input = self
+                    .duplicate_prompt
+                    .take()
+                    .and_then(|prompt| prompt.retry_input);
+                if let Some(input) = opt_input {
+                    ctx.link()
+                        .send_message(Msg::BrowserUploadedMeme(UploadMemeInput {
+                            allow_near_duplicate: true,
+                            ..input
+                        }));
+                }
+                true
+            }
+
+            Msg::DismissDuplicatePrompt => {
+                self.duplicate_prompt = None;
+                true
+            }
+
             Msg::SensemakerPresent(sensemaker_present) => {
                 self.sensemaker_present = Some(sensemaker_present);
+                if sensemaker_present {
+                    ctx.link().send_message(Msg::RetryPendingSmOps);
+                }
+                true
+            }
+
+            Msg::SensemakerDiscovered(candidates, current) => {
+                let current_found = current.as_ref().map_or(false, |cell_id| {
+                    candidates.iter().any(|c| c.cell_id == *cell_id)
+                });
+                let current_set = current.is_some();
+                self.sensemaker_candidates = candidates;
+                self.sensemaker_cell_id = current;
+                if current_found {
+                    ctx.link().send_message(Msg::SensemakerPresent(true));
+                } else if current_set {
+                    // the chosen sensemaker isn't running. that's for the user to sort out, not to
+                    // be papered over by picking another.
+                    ctx.link().send_message(Msg::SensemakerPresent(false));
+                    self.show_sensemaker_settings = true;
+                } else if let Some(i) = self
+                    .sensemaker_candidates
+                    .iter()
+                    .position(|candidate| candidate.bundled)
+                {
+                    // the happ carries its own sensemaker, but was packed without the
+                    // `sensemaker_dna_hash` property, so the zome can't find it by itself and has
+                    // to store it. when it can, the bundled cell is `current`, and nothing is
+                    // stored.
+                    ctx.link().send_message(Msg::SelectSensemaker(i));
+                } else if self.sensemaker_candidates.len() == 1 {
+                    // there's nothing to choose between
+                    ctx.link().send_message(Msg::SelectSensemaker(0));
+                } else if self.sensemaker_candidates.is_empty() {
+                    ctx.link().send_message(Msg::SensemakerPresent(false));
+                } else {
+                    self.show_sensemaker_settings = true;
+                }
                 true
             }
 
-            Msg::SmInitSubmit(expr_str) => {
-                self.set_sm(ctx.link(), expr_str.clone(), "set_sm_init".into());
-                // TODO ideally we would wait for confirmation before setting this
-                self.meme_sm.0 = expr_str;
+            Msg::SelectSensemaker(i) => {
+                if let Some(candidate) = self.sensemaker_candidates.get(i).cloned() {
+                    let app_ws_ = self.app_ws.clone();
+                    let cell_id_ = self.memez_cell_id.clone();
+                    ctx.link().send_future(async move {
+                        let sensemaker_cell_id = candidate.cell_id;
+                        match set_sensemaker_cell_id(
+                            &app_ws_,
+                            &cell_id_,
+                            MEMEZ_ZOME_NAME,
+                            sensemaker_cell_id.clone(),
+                        )
+                        .await
+                        {
+                            Ok(()) => Msg::SensemakerCellIdSet(sensemaker_cell_id),
+                            Err(err) => Msg::Error(err),
+                        }
+                    });
+                }
+                false
+            }
+
+            Msg::SensemakerCellIdSet(sensemaker_cell_id) => {
+                self.sensemaker_cell_id = Some(sensemaker_cell_id);
+                self.sensemaker_cell_not_set = false;
+                self.show_sensemaker_settings = false;
+                ctx.link().send_message(Msg::SensemakerPresent(true));
                 true
             }
 
-            Msg::SmCompSubmit(expr_str) => {
-                self.set_sm(ctx.link(), expr_str.clone(), "set_sm_comp".into());
-                // TODO ideally we would wait for confirmation before setting this
-                self.meme_sm.1 = expr_str;
+            Msg::ToggleSensemakerSettings => {
+                self.show_sensemaker_settings = !self.show_sensemaker_settings;
                 true
             }
 
-            Msg::ClapForMeme(meme_eh) => {
+            Msg::RetryPendingSmOps => {
                 let app_ws_ = self.app_ws.clone();
                 let cell_id_ = self.memez_cell_id.clone();
-                let meme_eh_ = meme_eh.clone();
+                let feed_score_comp = self.feed_score_comp.clone();
                 ctx.link().send_future(async move {
                     let cmd = AppWsCmd::CallZome {
                         cell_id: cell_id_.clone(),
                         zome_name: MEMEZ_ZOME_NAME.into(),
-                        fn_name: "clap_for_meme".into(),
-                        payload: meme_eh_.serialize_to_js_obj(),
+                        fn_name: "retry_pending_sm_ops".into(),
+                        payload: JsValue::NULL,
                         provenance: cell_id_.1.clone(),
                         cap: "".into(),
                     };
                     let resp = app_ws_.call(cmd).await;
                     match resp {
                         Ok(AppWsCmdResponse::CallZome(val)) => {
-                            Msg::Log(format!("clap_for_meme: {:?}", val))
+                            let retry = SmOpRetryRaw::deserialize_from_js_obj(val);
+                            log_list_errors("retry_pending_sm_ops", &retry.errors);
+                            if retry.needs_another_retry() {
+                                Msg::RetryPendingSmOps
+                            } else {
+                                // refetch, so any replayed claps count towards the scores
+                                get_all_memez(app_ws_, cell_id_, feed_score_comp).await
+                            }
                         }
                         Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
                         Err(err) => Msg::Error(format!("err: {:?}", err)),
                     }
                 });
-                for triple in self.memez.iter_mut() {
-                    if triple.0 == meme_eh {
-                        triple.2 += 1;
-                    }
-                }
+                false
+            }
+
+            Msg::SmInitSubmit(path, expr_str) => {
+                self.set_sm(
+                    ctx.link(),
+                    path,
+                    expr_str,
+                    "set_sm_init".into(),
+                    Msg::SmInitSet,
+                );
+                false
+            }
+
+            Msg::SmCompSubmit(path, expr_str) => {
+                self.set_sm(
+                    ctx.link(),
+                    path,
+                    expr_str,
+                    "set_sm_comp".into(),
+                    Msg::SmCompSet,
+                );
+                false
+            }
+
+            Msg::SmExprs(path, sm_exprs) => {
+                self.sm_exprs.insert(path, sm_exprs);
                 true
             }
-        }
-    }
 
-    fn view(&self, ctx: &Context<Self>) -> Html {
-        let sensemaker_present_html = match self.sensemaker_present {
-            None => html! {},
-            Some(true) => html! {
-                <p>{"sensemaker is present"}</p>
-            },
-            Some(false) => html! {
-                <div class="alert">
-                  <h3>{"social_sensemaker is absent!"}</h3>
-                  <p>{"please install it into your `we`, as `memez` requires it to function."}</p>
-                </div>
+            Msg::SmInitSet(path, expr_str) => {
+                self.refresh_sm_history(ctx.link(), &path);
+                self.sm_exprs.entry(path).or_default().0 = SmExprRaw::Source(expr_str);
+                self.sm_set_error = None;
+                true
+            }
+
+            Msg::SmCompSet(path, expr_str) => {
+                self.refresh_sm_history(ctx.link(), &path);
+                self.sm_exprs.entry(path).or_default().1 = SmExprRaw::Source(expr_str);
+                self.sm_set_error = None;
+                true
+            }
+
+            Msg::SmProposed(path) => {
+                self.sm_set_error = None;
+                self.refresh_sm_proposals(ctx.link(), path);
+                true
+            }
+
+            Msg::SmProposals(path, result) => match result {
+                Ok(proposals) => {
+                    self.sm_proposals.insert(path, proposals);
+                    true
+                }
+                Err(err) => {
+                    console_error!("get_sm_proposals failed: {}", err);
+                    false
+                }
             },
-        };
-        //
-        let sm_init_handler = |input: String| Ok(Msg::SmInitSubmit(input));
-        let sm_comp_handler = |input: String| Ok(Msg::SmCompSubmit(input));
-        //
-        let content_name = "meme";
-        let on_file_upload: Callback<FileBytes> = {
-            let link = ctx.link().clone();
-            Callback::from(move |fb: FileBytes| {
-                let meme = Meme {
-                    filename: fb.filename,
-                    blob_str: encode(fb.bytes),
+
+            Msg::ApproveSmProposal(path, i) => {
+                let proposal = match self.sm_proposals.get(&path).and_then(|ps| ps.get(i)) {
+                    None => return false,
+                    Some(proposal) => proposal.clone(),
                 };
-                link.send_future(async { Msg::BrowserUploadedMeme(meme) })
-            })
-        };
-        let mk_meme_src =
-            |meme: Meme| -> String { "data:img;base64,".to_string() + &meme.blob_str };
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                ctx.link().send_future(async move {
+                    match approve_sm_proposal(
+                        &app_ws_,
+                        &cell_id_,
+                        MEMEZ_ZOME_NAME,
+                        path.clone(),
+                        proposal,
+                    )
+                    .await
+                    {
+                        Ok(set) => Msg::SmProposalApproved(path, set),
+                        Err(err) => Msg::SmSetFailed(format!("approve_sm_proposal: {}", err)),
+                    }
+                });
+                false
+            }
 
-        html! {
-            <div>
-                <p>{"hello, memez 👋"}</p>
-                <br/>
-                {sensemaker_present_html}
-                <br/>
-                { self.view_string_input(ctx.link(), sm_init_handler, "sm_init".into(), "meme sm_init".into(), self.meme_sm.0.clone()) }
-                <br/>
-                { self.view_string_input(ctx.link(), sm_comp_handler, "sm_comp".into(), "meme sm_comp".into(), self.meme_sm.1.clone()) }
-                <br/>
-                <FileUploadApp {content_name} {on_file_upload} />
-                <br/>
-                <h3 class="subtitle">{"memez"}</h3>
-                { for self.memez.iter().cloned().map(|triple| html!{
-                    <div>
-                        <img src={mk_meme_src(triple.1.clone())} width="95%" height="500px" />
-                        <p>{ format!("score: {}", triple.2) }</p>
-                        <button onclick={ctx.link().callback(move |_| Msg::ClapForMeme(triple.0.clone()))}>{ "👏" }</button>
-                    </div>
-                }) }
-            </div>
-        }
-    }
-}
+            Msg::SmProposalApproved(path, set) => {
+                self.sm_set_error = None;
+                if set {
+                    self.sm_path_changed(ctx.link(), &path);
+                }
+                self.refresh_sm_proposals(ctx.link(), path);
+                true
+            }
 
-impl Model {
-    // TODO dedup
-    fn view_string_input<F>(
-        &self,
-        link: &Scope<Self>,
-        f: F,
-        class: String,
-        label: String,
-        value: String,
-    ) -> Html
-    where
-        F: Fn(String) -> Result<Msg, String> + 'static,
-    {
-        let onkeypress = link.batch_callback(move |e: KeyboardEvent| {
-            if e.key() == "Enter" {
-                let input: InputElement = e.target_unchecked_into();
-                match f(input.value()) {
-                    Ok(msg) => {
-                        input.set_value("");
-                        Some(msg)
+            Msg::SmSetFailed(err) => {
+                console_error!("set_sm failed: {}", err.clone());
+                self.sm_set_error = Some(match WidgetError::decode(&err) {
+                    Some(widget_err) => widget_err.to_string(),
+                    None => err,
+                });
+                true
+            }
+
+            Msg::DismissSmSetError => {
+                self.sm_set_error = None;
+                true
+            }
+
+            Msg::ToggleSmHistory(path) => {
+                let shown =
+                    matches!(&self.sm_history, Some((shown_path, _)) if *shown_path == path);
+                if shown {
+                    self.sm_history = None;
+                } else {
+                    self.sm_history = Some((path.clone(), None));
+                    self.refresh_sm_history(ctx.link(), &path);
+                }
+                true
+            }
+
+            Msg::SmHistory(path, result) => {
+                // hidden, or another path's shown, in the meantime
+                if !matches!(&self.sm_history, Some((shown_path, _)) if *shown_path == path) {
+                    return false;
+                }
+                match result {
+                    Ok(history) => {
+                        self.sm_history = Some((path, Some(history)));
                     }
                     Err(err) => {
-                        console_error!(format!("view_string_input: {}", err));
-                        None
+                        console_error!("get_sm_config_history failed: {}", err);
+                        self.sm_history = None;
                     }
                 }
-            } else {
-                None
+                true
             }
-        });
-        html! {
-            <div>
-                <label>{format!("{}: ", label)}</label>
-                <input
-                    {class}
-                    {value}
-                    {onkeypress}
-                />
-            </div>
-        }
-    }
 
-    fn set_sm(&self, link: &Scope<Self>, expr_str: String, zome_fn: String) {
-        let app_ws_ = self.app_ws.clone();
-        let cell_id_ = self.memez_cell_id.clone();
-        link.send_future(async move {
-            let cmd = AppWsCmd::CallZome {
-                cell_id: cell_id_.clone(),
-                zome_name: MEMEZ_ZOME_NAME.into(),
-                fn_name: zome_fn.clone(),
-                payload: (MEMEZ_PATH.to_string(), expr_str).serialize_to_js_obj(),
-                provenance: cell_id_.1.clone(),
-                cap: "".into(),
-            };
-            let resp = app_ws_.call(cmd).await;
-            match resp {
-                Ok(AppWsCmdResponse::CallZome(val)) => Msg::Log(format!("{}: {:?}", zome_fn, val)),
-                Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
-                Err(err) => Msg::Error(format!("err: {:?}", err)),
+            Msg::LoadMoreSmHistory => {
+                let (path, after) = match &self.sm_history {
+                    Some((path, Some(history))) => match &history.next_after {
+                        None => return false,
+                        Some(after) => (path.clone(), after.clone()),
+                    },
+                    _ => return false,
+                };
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                ctx.link().send_future(async move {
+                    let result = get_sm_config_history(
+                        &app_ws_,
+                        &cell_id_,
+                        MEMEZ_ZOME_NAME,
+                        path.clone(),
+                        Some(after),
+                    )
+                    .await;
+                    Msg::SmHistoryPage(path, result)
+                });
+                false
             }
-        });
-    }
+
+            Msg::SmHistoryPage(path, result) => match (&mut self.sm_history, result) {
+                (Some((shown_path, Some(history))), Ok(older)) if *shown_path == path => {
+                    history.append(older);
+                    true
+                }
+                // hidden, or another path's shown, in the meantime
+                (_, Ok(_)) => false,
+                (_, Err(err)) => {
+                    console_error!("get_sm_config_history failed: {}", err);
+                    false
+                }
+            },
+
+            Msg::RollbackSm(i) => {
+                let (path, change) = match &self.sm_history {
+                    Some((path, Some(history))) => match history.changes.get(i) {
+                        None => return false,
+                        Some(change) => (path.clone(), change.clone()),
+                    },
+                    _ => return false,
+                };
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                ctx.link().send_future(async move {
+                    match rollback_sm_expr(
+                        &app_ws_,
+                        &cell_id_,
+                        MEMEZ_ZOME_NAME,
+                        path.clone(),
+                        change,
+                    )
+                    .await
+                    {
+                        Ok(set) => Msg::RolledBack(path, set),
+                        Err(err) => Msg::SmSetFailed(format!("rollback_sm_expr: {}", err)),
+                    }
+                });
+                false
+            }
+
+            Msg::RolledBack(path, set) => {
+                if set {
+                    self.sm_set_error = None;
+                    self.sm_path_changed(ctx.link(), &path);
+                } else {
+                    ctx.link().send_message(Msg::SmProposed(path));
+                }
+                true
+            }
+
+            Msg::ExprInvalid(label, expr_str, err) => {
+                self.expr_errors.insert(label, (expr_str, err));
+                true
+            }
+
+            Msg::ExprValid(label) => self.expr_errors.remove(&label).is_some(),
+
+            Msg::ClapForMeme(meme_eh) => {
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                let meme_eh_ = meme_eh.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: MEMEZ_ZOME_NAME.into(),
+                        fn_name: "clap_for_meme".into(),
+                        payload: meme_eh_.serialize_to_js_obj(),
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        Ok(AppWsCmdResponse::CallZome(val)) => {
+                            Msg::Log(format!("clap_for_meme: {:?}", val))
+                        }
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Msg::Error(format!("err: {:?}", err)),
+                    }
+                });
+                for item in self.memez.iter_mut() {
+                    if item.meme_eh == meme_eh {
+                        item.score += 1;
+                    }
+                }
+                true
+            }
+
+            Msg::FlagMeme(meme_eh) => {
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: MEMEZ_ZOME_NAME.into(),
+                        fn_name: "flag_meme".into(),
+                        payload: meme_eh.serialize_to_js_obj(),
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        Ok(AppWsCmdResponse::CallZome(val)) => {
+                            Msg::Log(format!("flag_meme: {:?}", val))
+                        }
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Msg::Error(format!("err: {:?}", err)),
+                    }
+                });
+                false
+            }
+
+            Msg::RevealMeme(meme_eh) => {
+                self.revealed_memez.push(meme_eh);
+                true
+            }
+
+            Msg::ZomeCallResponse(ZomeCallResponse::HideComp(hide_comp)) => {
+                self.hide_comp = hide_comp;
+                true
+            }
+
+            Msg::ZomeCallResponse(ZomeCallResponse::AgentRepPaths(agent_rep_paths)) => {
+                self.agent_rep_paths = agent_rep_paths;
+                true
+            }
+
+            Msg::AgentRepPathsSubmit(input) => {
+                let agent_rep_paths: Vec<String> = input
+                    .split(',')
+                    .map(|path| path.trim().to_string())
+                    .filter(|path| !path.is_empty())
+                    .collect();
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: MEMEZ_ZOME_NAME.into(),
+                        fn_name: "set_agent_rep_paths".into(),
+                        payload: agent_rep_paths.clone().serialize_to_js_obj_(),
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        Ok(AppWsCmdResponse::CallZome(_val)) => {
+                            Msg::ZomeCallResponse(ZomeCallResponse::AgentRepPaths(agent_rep_paths))
+                        }
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => {
+                            Msg::ModerationSetFailed(format!("set_agent_rep_paths: {:?}", err))
+                        }
+                    }
+                });
+                false
+            }
+
+            Msg::HideCompSubmit(expr_str) => {
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: MEMEZ_ZOME_NAME.into(),
+                        fn_name: "set_hide_comp".into(),
+                        payload: expr_str.clone().serialize_to_js_obj(),
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        Ok(AppWsCmdResponse::CallZome(_val)) => {
+                            Msg::ZomeCallResponse(ZomeCallResponse::HideComp(expr_str))
+                        }
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Msg::ModerationSetFailed(format!("set_hide_comp: {:?}", err)),
+                    }
+                });
+                false
+            }
+
+            Msg::ModerationSetFailed(err) => match WidgetError::decode(&err) {
+                // not specific to this call, so handled as any other call's
+                Some(WidgetError::SensemakerMissing(_))
+                | Some(WidgetError::SensemakerCellNotSet) => {
+                    ctx.link().send_message(Msg::Error(err));
+                    false
+                }
+                widget_err => {
+                    console_error!("Error: {}", err.clone());
+                    self.last_error =
+                        Some(widget_err.unwrap_or(WidgetError::ValidationFailed(err)));
+                    true
+                }
+            },
+
+            Msg::PreviewScores(change) => {
+                let input = match &change {
+                    PreviewedChange::SmComp(sm_comp) => SimulateFeedInput {
+                        sm_comp: Some(sm_comp.clone()),
+                        current_feed_score_comp: self.feed_score_comp.clone(),
+                        feed_score_comp: self.feed_score_comp.clone(),
+                    },
+                    PreviewedChange::FeedScoreComp(feed_score_comp) => SimulateFeedInput {
+                        sm_comp: None,
+                        current_feed_score_comp: self.feed_score_comp.clone(),
+                        feed_score_comp: feed_score_comp.clone(),
+                    },
+                };
+                self.score_preview = Some(ScorePreview::new(change));
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: MEMEZ_ZOME_NAME.into(),
+                        fn_name: "simulate_feed_scores".into(),
+                        payload: input.serialize_to_js_obj_(),
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    Msg::ScoresPreviewed(match resp {
+                        Ok(AppWsCmdResponse::CallZome(val)) => {
+                            Ok(ListResultRaw::<SimulatedScoreRaw>::deserialize_from_js_obj_(val))
+                        }
+                        Ok(resp) => Err(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Err(format!("{:?}", err)),
+                    })
+                });
+                true
+            }
+
+            Msg::ScoresPreviewed(result) => {
+                let label = |meme_eh: &EntryHashRaw| {
+                    self.memez
+                        .iter()
+                        .find(|item| &item.meme_eh == meme_eh)
+                        .map_or_else(
+                            || "(not in the feed yet)".into(),
+                            |item| item.thumbnail.filename.clone(),
+                        )
+                };
+                let result = result.map(|list_result| SimulatedScores {
+                    failed: list_result.errors.len(),
+                    rows: list_result
+                        .items
+                        .into_iter()
+                        .map(|score| ScoreDiffRow {
+                            label: label(&score.meme_eh),
+                            current: score.current,
+                            simulated: score.simulated,
+                        })
+                        .collect(),
+                });
+                match self.score_preview.as_mut() {
+                    // cancelled in the meantime
+                    None => false,
+                    Some(preview) => {
+                        preview.result = Some(result);
+                        true
+                    }
+                }
+            }
+
+            Msg::ConfirmScorePreview => {
+                if let Some(preview) = self.score_preview.take() {
+                    if let Some(Ok(_)) = preview.result {
+                        ctx.link().send_message(match preview.change {
+                            PreviewedChange::SmComp(sm_comp) => {
+                                Msg::SmCompSubmit(MEMEZ_PATH.into(), sm_comp)
+                            }
+                            PreviewedChange::FeedScoreComp(feed_score_comp) => {
+                                Msg::FeedScoreCompSubmit(feed_score_comp)
+                            }
+                        });
+                    }
+                }
+                true
+            }
+
+            Msg::CancelScorePreview => {
+                self.score_preview = None;
+                true
+            }
+
+            Msg::FeedScoreCompSubmit(feed_score_comp) => {
+                ctx.link().send_future(get_all_memez(
+                    self.app_ws.clone(),
+                    self.memez_cell_id.clone(),
+                    feed_score_comp.clone(),
+                ));
+                if let Some(storage) = local_storage() {
+                    if let Err(err) =
+                        storage.set_item(FEED_SCORE_COMP_STORAGE_KEY, &feed_score_comp)
+                    {
+                        console_error!(format!("localStorage set_item: {:?}", err));
+                    }
+                }
+                self.feed_score_comp = feed_score_comp;
+                true
+            }
+
+            Msg::ZomeCallResponse(ZomeCallResponse::ScorePresets(score_presets)) => {
+                self.score_presets = score_presets;
+                true
+            }
+
+            Msg::PublishScorePreset => {
+                let name_input = self.preset_name_ref.cast::<InputElement>();
+                let description_input = self.preset_description_ref.cast::<InputElement>();
+                let (name_input, description_input) = match (name_input, description_input) {
+                    (Some(name_input), Some(description_input)) => (name_input, description_input),
+                    _ => return false,
+                };
+                if name_input.value().trim().is_empty() {
+                    self.last_error = Some(WidgetError::ValidationFailed(
+                        "a score preset needs a name".into(),
+                    ));
+                    return true;
+                }
+                let input = CreateScorePresetInput {
+                    name: name_input.value(),
+                    description: description_input.value(),
+                    expr: self.feed_score_comp.clone(),
+                };
+                name_input.set_value("");
+                description_input.set_value("");
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: MEMEZ_ZOME_NAME.into(),
+                        fn_name: "create_score_preset".into(),
+                        payload: input.serialize_to_js_obj_(),
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        // refetch, so the new preset shows up with its author
+                        Ok(AppWsCmdResponse::CallZome(_val)) => {
+                            get_all_score_presets(app_ws_, cell_id_).await
+                        }
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Msg::Error(format!("err: {:?}", err)),
+                    }
+                });
+                false
+            }
+
+            Msg::OpenMeme(meme_eh) => {
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                let meme_eh_ = meme_eh.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: MEMEZ_ZOME_NAME.into(),
+                        fn_name: "get_meme".into(),
+                        payload: meme_eh_.clone().serialize_to_js_obj(),
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        Ok(AppWsCmdResponse::CallZome(val)) => Msg::ZomeCallResponse(
+                            ZomeCallResponse::Meme(meme_eh_, Meme::deserialize_from_js_obj_(val)),
+                        ),
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Msg::Error(format!("err: {:?}", err)),
+                    }
+                });
+                self.opened_meme = Some((meme_eh, None));
+                true
+            }
+
+            Msg::CloseMeme => {
+                self.opened_meme = None;
+                true
+            }
+
+            Msg::InstallBattleSm => {
+                ctx.link().send_message_batch(vec![
+                    Msg::SmInitSubmit(BATTLE_PATH.into(), BATTLE_SM_INIT.into()),
+                    Msg::SmCompSubmit(BATTLE_PATH.into(), BATTLE_SM_COMP.into()),
+                ]);
+                false
+            }
+
+            Msg::MigrateMemeAnchor => {
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                let feed_score_comp = self.feed_score_comp.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: MEMEZ_ZOME_NAME.into(),
+                        fn_name: "migrate_meme_anchor".into(),
+                        payload: JsValue::NULL,
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        // refetch, so any migrated memez show up
+                        Ok(AppWsCmdResponse::CallZome(_val)) => {
+                            get_all_memez(app_ws_, cell_id_, feed_score_comp).await
+                        }
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Msg::Error(format!("err: {:?}", err)),
+                    }
+                });
+                false
+            }
+
+            Msg::StartBattle => {
+                self.battle = self.random_battle();
+                if self.battle.is_none() {
+                    console_error!("StartBattle: battles need at least 2 visible memez");
+                }
+                true
+            }
+
+            Msg::PickBattleWinner(winner_eh, loser_eh) => {
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                let feed_score_comp = self.feed_score_comp.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: MEMEZ_ZOME_NAME.into(),
+                        fn_name: "record_battle".into(),
+                        payload: (winner_eh, loser_eh).serialize_to_js_obj_(),
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        // refetch, so the new battle ratings show up
+                        Ok(AppWsCmdResponse::CallZome(_val)) => {
+                            get_all_memez(app_ws_, cell_id_, feed_score_comp).await
+                        }
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Msg::Error(format!("err: {:?}", err)),
+                    }
+                });
+                self.battle = self.random_battle();
+                true
+            }
+
+            Msg::EndBattle => {
+                self.battle = None;
+                true
+            }
+
+            Msg::RankByBattle(rank_by_battle) => {
+                self.rank_by_battle = rank_by_battle;
+                true
+            }
+
+            Msg::ZomeCallResponse(ZomeCallResponse::Boards(boards)) => {
+                self.boards = boards;
+                true
+            }
+
+            Msg::ZomeCallResponse(ZomeCallResponse::BoardMemez(board_eh, board_memez)) => {
+                match &mut self.opened_board {
+                    Some((opened_eh, opt_memez)) if *opened_eh == board_eh => {
+                        *opt_memez = Some(board_memez);
+                        true
+                    }
+                    // the user has moved on since requesting this board
+                    _ => false,
+                }
+            }
+
+            Msg::CreateBoard(name) => {
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.memez_cell_id.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: MEMEZ_ZOME_NAME.into(),
+                        fn_name: "create_board".into(),
+                        payload: name.serialize_to_js_obj(),
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        // refetch, so the new board shows up with its author
+                        Ok(AppWsCmdResponse::CallZome(_val)) => {
+                            get_all_boards(app_ws_, cell_id_).await
+                        }
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Msg::Error(format!("err: {:?}", err)),
+                    }
+                });
+                false
+            }
+
+            Msg::OpenBoard(board_eh) => {
+                ctx.link().send_future(get_board_memez(
+                    self.app_ws.clone(),
+                    self.memez_cell_id.clone(),
+                    board_eh.clone(),
+                ));
+                self.opened_board = Some((board_eh, None));
+                true
+            }
+
+            Msg::CloseBoard => {
+                self.opened_board = None;
+                true
+            }
+
+            Msg::SaveToBoard(board_eh, meme_eh) => {
+                self.board_meme_call(ctx.link(), board_eh, meme_eh, "add_meme_to_board".into());
+                false
+            }
+
+            Msg::RemoveFromBoard(board_eh, meme_eh) => {
+                self.board_meme_call(
+                    ctx.link(),
+                    board_eh,
+                    meme_eh,
+                    "remove_meme_from_board".into(),
+                );
+                false
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let sensemaker_present_html = match self.sensemaker_present {
+            None => html! {},
+            Some(true) => html! {
+                <p>{"sensemaker is present"}</p>
+            },
+            Some(false) => html! {
+                <div class="alert">
+                  <h3>{"social_sensemaker is absent!"}</h3>
+                  <p>{"please install it into your `we`, as `memez` requires it to function."}</p>
+                </div>
+            },
+        };
+        let sensemaker_cell_not_set_html = if self.sensemaker_cell_not_set {
+            html! {
+                <div class="alert">
+                  <h3>{"sensemaker cell id is not set!"}</h3>
+                  <p>{"`memez` doesn't know which cell is the sensemaker yet. pick one in the sensemaker settings."}</p>
+                </div>
+            }
+        } else {
+            html! {}
+        };
+        //
+        let hide_comp_check = |input: &str| check_int_fn(input, 1);
+        let agent_rep_paths_handler = |input: String| Ok(Msg::AgentRepPathsSubmit(input));
+        // the zome still accepts the older `(lam [meme agent] ...)` form, so the check does too
+        let feed_score_comp_check = |input: &str| {
+            check_int_fn(input, FEED_SCORE_COMP_PARAMS.len())
+                .or_else(|err| check_int_fn(input, 2).map_err(|_| err))
+        };
+        let feed_score_comp_params: Vec<String> = FEED_SCORE_COMP_PARAMS
+            .iter()
+            .map(|param| param.to_string())
+            .collect();
+        //
+        let content_name = "meme";
+        let on_file_upload: Callback<FileBytes> = {
+            let link = ctx.link().clone();
+            Callback::from(move |fb: FileBytes| {
+                let input = UploadMemeInput {
+                    meme: Meme {
+                        filename: fb.filename,
+                        blob_str: encode(fb.bytes),
+                    },
+                    thumbnail_blob_str: fb.thumbnail.map(encode),
+                    perceptual_hash: fb.perceptual_hash,
+                    allow_near_duplicate: false,
+                };
+                link.send_future(async { Msg::BrowserUploadedMeme(input) })
+            })
+        };
+        let preprocess = Some(ImagePreprocessConfig::default());
+        let opened_meme_html = match &self.opened_meme {
+            None => html! {},
+            Some((_meme_eh, opt_meme)) => html! {
+                <div class="opened-meme">
+                    <button onclick={ctx.link().callback(|_| Msg::CloseMeme)}>{ "close" }</button>
+                    { match opt_meme {
+                        None => html! { <p>{"loading..."}</p> },
+                        Some(meme) => html! { <img src={mk_img_src(&meme.blob_str)} width="100%" /> },
+                    } }
+                </div>
+            },
+        };
+
+        let mut feed = self.memez.clone();
+        if self.rank_by_battle {
+            // memez which haven't battled yet go last
+            feed.sort_by(|a, b| b.battle_rating.cmp(&a.battle_rating));
+        }
+
+        html! {
+            <div>
+                <p>{"hello, memez 👋"}</p>
+                <br/>
+                {sensemaker_present_html}
+                {sensemaker_cell_not_set_html}
+                { self.view_last_error(ctx.link()) }
+                <button onclick={ctx.link().callback(|_| Msg::ToggleSensemakerSettings)}>{ "sensemaker settings" }</button>
+                { self.view_sensemaker_settings(ctx.link()) }
+                <br/>
+                { self.view_sm_set_error(ctx.link()) }
+                { for SM_PATHS.iter().map(|(path, label)| self.view_sm_inputs(ctx.link(), path, label)) }
+                <button onclick={ctx.link().callback(|_| Msg::InstallBattleSm)}>{ "install Elo battle ratings" }</button>
+                <button onclick={ctx.link().callback(|_| Msg::MigrateMemeAnchor)}>{ "migrate_meme_anchor" }</button>
+                { self.view_expr_input(ctx.link(), hide_comp_check, Msg::HideCompSubmit, "hide_comp".into(), "moderation hide_comp".into(), self.hide_comp.clone(), vec!["flags".into()]) }
+                <br/>
+                <FileUploadApp
+                    {content_name}
+                    {on_file_upload}
+                    {preprocess}
+                    allowed_extensions={self.config.allowed_extensions.clone()}
+                    max_bytes={Some(self.config.max_meme_bytes)}
+                />
+                { self.view_duplicate_prompt(ctx.link()) }
+                <br/>
+                {opened_meme_html}
+                { self.view_battle(ctx.link()) }
+                { self.view_boards(ctx.link()) }
+                <h3 class="subtitle">{"memez"}</h3>
+                { self.view_string_input(ctx.link(), agent_rep_paths_handler, "agent_rep_paths".into(), "agent reputation paths (comma-separated)".into(), self.agent_rep_paths.join(", ")) }
+                { self.view_expr_input(ctx.link(), feed_score_comp_check, |expr| Msg::PreviewScores(PreviewedChange::FeedScoreComp(expr)), "feed_score_comp".into(), "feed_score_comp".into(), self.feed_score_comp.clone(), feed_score_comp_params) }
+                { self.view_score_preview(ctx.link()) }
+                { self.view_score_presets(ctx.link()) }
+                <div>
+                    <label>{"rank by battle rating: "}</label>
+                    <input
+                        type="checkbox"
+                        checked={self.rank_by_battle}
+                        onclick={ctx.link().callback({
+                            let rank_by_battle = self.rank_by_battle;
+                            move |_| Msg::RankByBattle(!rank_by_battle)
+                        })}
+                    />
+                </div>
+                { self.view_memez_errors(ctx.link()) }
+                { for feed.into_iter().map(|item| self.view_meme(ctx.link(), item)) }
+            </div>
+        }
+    }
+}
+
+impl Model {
+    fn view_score_presets(&self, link: &Scope<Self>) -> Html {
+        let builtin_presets = BUILTIN_SCORE_PRESETS
+            .iter()
+            .map(|(name, description, expr)| {
+                (
+                    name.to_string(),
+                    description.to_string(),
+                    expr.to_string(),
+                    "memez".to_string(),
+                )
+            });
+        let dht_presets = self.score_presets.iter().map(|(_eh, published)| {
+            (
+                published.preset.name.clone(),
+                published.preset.description.clone(),
+                published.preset.expr.clone(),
+                format!("{}", published.author),
+            )
+        });
+        html! {
+            <div class="score-presets">
+                <ul>
+                    { for builtin_presets.chain(dht_presets).map(|(name, description, expr, author)| {
+                        let class = if expr == self.feed_score_comp { "selected" } else { "" };
+                        html! {
+                            <li {class}>
+                                <button onclick={link.callback(move |_| Msg::PreviewScores(PreviewedChange::FeedScoreComp(expr.clone())))}>{ name }</button>
+                                { format!(" {} (by {})", description, author) }
+                            </li>
+                        }
+                    }) }
+                </ul>
+                <div>
+                    <label>{"publish current feed_score_comp as: "}</label>
+                    <input ref={self.preset_name_ref.clone()} placeholder="name" />
+                    <input ref={self.preset_description_ref.clone()} placeholder="description" />
+                    <button onclick={link.callback(|_| Msg::PublishScorePreset)}>{ "publish" }</button>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_battle(&self, link: &Scope<Self>) -> Html {
+        match &self.battle {
+            None => html! {
+                <button onclick={link.callback(|_| Msg::StartBattle)}>{ "battle mode ⚔️" }</button>
+            },
+            Some((a, b)) => {
+                let (a_eh, b_eh) = (a.meme_eh.clone(), b.meme_eh.clone());
+                let (a_eh_, b_eh_) = (a_eh.clone(), b_eh.clone());
+                html! {
+                    <div class="battle">
+                        <p>{"which is better?"}</p>
+                        <img
+                            src={mk_img_src(&a.thumbnail.blob_str)}
+                            onclick={link.callback(move |_| Msg::PickBattleWinner(a_eh.clone(), b_eh.clone()))}
+                        />
+                        <img
+                            src={mk_img_src(&b.thumbnail.blob_str)}
+                            onclick={link.callback(move |_| Msg::PickBattleWinner(b_eh_.clone(), a_eh_.clone()))}
+                        />
+                        <button onclick={link.callback(|_| Msg::EndBattle)}>{ "done" }</button>
+                    </div>
+                }
+            }
+        }
+    }
+
+    fn view_sensemaker_settings(&self, link: &Scope<Self>) -> Html {
+        if !self.show_sensemaker_settings {
+            return html! {};
+        }
+        if self.sensemaker_candidates.is_empty() {
+            return html! {
+                <div class="sensemaker-settings">
+                    <p>{"no sensemaker cells were found."}</p>
+                </div>
+            };
+        }
+        html! {
+            <div class="sensemaker-settings">
+                <p>{"which sensemaker should `memez` use?"}</p>
+                { for self.sensemaker_candidates.iter().enumerate().map(|(i, candidate)| {
+                    let in_use = self.sensemaker_cell_id.as_ref() == Some(&candidate.cell_id);
+                    html! {
+                        <button
+                            disabled={in_use}
+                            onclick={link.callback(move |_| Msg::SelectSensemaker(i))}
+                        >
+                            { if in_use { format!("{} (in use)", candidate.label()) } else { candidate.label() } }
+                        </button>
+                    }
+                }) }
+            </div>
+        }
+    }
+
+    fn view_memez_errors(&self, link: &Scope<Self>) -> Html {
+        if self.memez_errors.is_empty() {
+            return html! {};
+        }
+        html! {
+            <div class="alert">
+                <p>{ format!("{} memez could not be loaded", self.memez_errors.len()) }</p>
+                <button onclick={link.callback(|_| Msg::RefreshMemez)}>{ "retry" }</button>
+                <button onclick={link.callback(|_| Msg::ReconcileSmData)}>{ "repair sensemaker data" }</button>
+            </div>
+        }
+    }
+
+    fn view_last_error(&self, link: &Scope<Self>) -> Html {
+        let err = match &self.last_error {
+            None => return html! {},
+            Some(err) => err,
+        };
+        html! {
+            <div class="alert">
+                <p>{err.to_string()}</p>
+                <button onclick={link.callback(|_| Msg::DismissError)}>{ "dismiss" }</button>
+            </div>
+        }
+    }
+
+    fn view_score_preview(&self, link: &Scope<Self>) -> Html {
+        let preview = match &self.score_preview {
+            None => return html! {},
+            Some(preview) => preview,
+        };
+        let title = match &preview.change {
+            PreviewedChange::SmComp(sm_comp) => format!("the feed with meme sm_comp `{}`", sm_comp),
+            PreviewedChange::FeedScoreComp(feed_score_comp) => {
+                format!("the feed with feed_score_comp `{}`", feed_score_comp)
+            }
+        };
+        view_score_preview(
+            preview,
+            title,
+            link.callback(|_| Msg::ConfirmScorePreview),
+            link.callback(|_| Msg::CancelScorePreview),
+        )
+    }
+
+    fn view_sm_set_error(&self, link: &Scope<Self>) -> Html {
+        let err = match &self.sm_set_error {
+            None => return html! {},
+            Some(err) => err,
+        };
+        html! {
+            <div class="alert">
+                <h3>{"the sensemaker expression was not set"}</h3>
+                <p>{err}</p>
+                <button onclick={link.callback(|_| Msg::DismissSmSetError)}>{ "dismiss" }</button>
+            </div>
+        }
+    }
+
+    fn view_duplicate_prompt(&self, link: &Scope<Self>) -> Html {
+        let prompt = match &self.duplicate_prompt {
+            None => return html! {},
+            Some(prompt) => prompt,
+        };
+        let upload_anyway_html = if prompt.retry_input.is_some() {
+            html! {
+                <button onclick={link.callback(|_| Msg::UploadAnyway)}>{ "no, upload mine" }</button>
+            }
+        } else {
+            html! {}
+        };
+        html! {
+            <div class="duplicate-prompt">
+                <p>{"this looks like an existing meme, clap that instead?"}</p>
+                { for prompt.existing.iter().map(|(meme_eh, thumbnail)| {
+                    let meme_eh = meme_eh.clone();
+                    html! {
+                        <div>
+                            <img src={mk_img_src(&thumbnail.blob_str)} />
+                            <button onclick={link.batch_callback(move |_| vec![Msg::ClapForMeme(meme_eh.clone()), Msg::DismissDuplicatePrompt])}>{ "👏" }</button>
+                        </div>
+                    }
+                }) }
+                {upload_anyway_html}
+                <button onclick={link.callback(|_| Msg::DismissDuplicatePrompt)}>{ "cancel" }</button>
+            </div>
+        }
+    }
+
+    /// only a board's author may change it, so the other agents aren't offered to.
+    fn is_own_board(&self, board_eh: &EntryHashRaw) -> bool {
+        let own_pk = agent_pk_to_vec_u8(self.memez_cell_id.1.clone());
+        self.boards
+            .iter()
+            .any(|(eh, board)| eh == board_eh && board.author.get_raw_39().to_vec() == own_pk)
+    }
+
+    fn view_boards(&self, link: &Scope<Self>) -> Html {
+        let create_board_handler = |input: String| {
+            if input.is_empty() {
+                Err("a board needs a name".into())
+            } else {
+                Ok(Msg::CreateBoard(input))
+            }
+        };
+        let opened_board_html = match &self.opened_board {
+            None => html! {},
+            Some((board_eh, opt_memez)) => {
+                let is_own_board = self.is_own_board(board_eh);
+                html! {
+                    <div class="opened-board">
+                        <button onclick={link.callback(|_| Msg::CloseBoard)}>{ "close board" }</button>
+                        { match opt_memez {
+                            None => html! { <p>{"loading..."}</p> },
+                            Some(memez) if memez.is_empty() => html! { <p>{"this board is empty"}</p> },
+                            Some(memez) => html! {
+                                <div>
+                                { for memez.iter().map(|(meme_eh, thumbnail)| {
+                                    let open_eh = meme_eh.clone();
+                                    let remove_args = (board_eh.clone(), meme_eh.clone());
+                                    html! {
+                                        <div>
+                                            <img
+                                                src={mk_img_src(&thumbnail.blob_str)}
+                                                onclick={link.callback(move |_| Msg::OpenMeme(open_eh.clone()))}
+                                            />
+                                            { if is_own_board {
+                                                html! { <button onclick={link.callback(move |_| Msg::RemoveFromBoard(remove_args.0.clone(), remove_args.1.clone()))}>{ "remove from board" }</button> }
+                                            } else {
+                                                html! {}
+                                            } }
+                                        </div>
+                                    }
+                                }) }
+                                </div>
+                            },
+                        } }
+                    </div>
+                }
+            }
+        };
+        html! {
+            <div class="boards">
+                <h3 class="subtitle">{"boards"}</h3>
+                <ul>
+                    { for self.boards.iter().map(|(board_eh, board)| {
+                        let board_eh = board_eh.clone();
+                        html! {
+                            <li>
+                                <button onclick={link.callback(move |_| Msg::OpenBoard(board_eh.clone()))}>{ board.name.clone() }</button>
+                                { format!(" (by {})", board.author) }
+                            </li>
+                        }
+                    }) }
+                </ul>
+                { self.view_string_input(link, create_board_handler, "create_board".into(), "new board".into(), "".into()) }
+                {opened_board_html}
+            </div>
+        }
+    }
+
+    /// a "save to board" dropdown for `meme_eh`, listing the agent's own boards by index.
+    fn view_save_to_board(&self, link: &Scope<Self>, meme_eh: EntryHashRaw) -> Html {
+        let own_boards: Vec<&(EntryHashRaw, Board)> = self
+            .boards
+            .iter()
+            .filter(|(board_eh, _board)| self.is_own_board(board_eh))
+            .collect();
+        let board_ehs: Vec<EntryHashRaw> = own_boards
+            .iter()
+            .map(|(board_eh, _board)| board_eh.clone())
+            .collect();
+        let onchange = link.batch_callback(move |e: Event| {
+            let select: SelectElement = e.target_unchecked_into();
+            let opt_board_eh = select
+                .value()
+                .parse::<usize>()
+                .ok()
+                .and_then(|idx| board_ehs.get(idx).cloned());
+            // reset, so the same board can be picked again for another meme
+            select.set_value("");
+            opt_board_eh.map(|board_eh| Msg::SaveToBoard(board_eh, meme_eh.clone()))
+        });
+        html! {
+            <select {onchange}>
+                <option value="" selected=true>{ "save to board..." }</option>
+                { for own_boards.iter().enumerate().map(|(idx, (_board_eh, board))| html! {
+                    <option value={idx.to_string()}>{ board.name.clone() }</option>
+                }) }
+            </select>
+        }
+    }
+
+    fn board_meme_call(
+        &self,
+        link: &Scope<Self>,
+        board_eh: EntryHashRaw,
+        meme_eh: EntryHashRaw,
+        zome_fn: String,
+    ) {
+        let app_ws_ = self.app_ws.clone();
+        let cell_id_ = self.memez_cell_id.clone();
+        let refetch = matches!(&self.opened_board, Some((opened_eh, _)) if *opened_eh == board_eh);
+        link.send_future(async move {
+            let cmd = AppWsCmd::CallZome {
+                cell_id: cell_id_.clone(),
+                zome_name: MEMEZ_ZOME_NAME.into(),
+                fn_name: zome_fn.clone(),
+                payload: (board_eh.clone(), meme_eh).serialize_to_js_obj_(),
+                provenance: cell_id_.1.clone(),
+                cap: "".into(),
+            };
+            let resp = app_ws_.call(cmd).await;
+            match resp {
+                // refetch, so the opened board reflects the change
+                Ok(AppWsCmdResponse::CallZome(_val)) if refetch => {
+                    get_board_memez(app_ws_, cell_id_, board_eh).await
+                }
+                Ok(AppWsCmdResponse::CallZome(val)) => Msg::Log(format!("{}: {:?}", zome_fn, val)),
+                Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                Err(err) => Msg::Error(format!("err: {:?}", err)),
+            }
+        });
+    }
+
+    /// two distinct, visible memez, picked at random.
+    fn random_battle(&self) -> Option<(FeedItemRaw, FeedItemRaw)> {
+        let candidates: Vec<&FeedItemRaw> = self.memez.iter().filter(|item| !item.hidden).collect();
+        if candidates.len() < 2 {
+            return None;
+        }
+        let random_idx = |n: usize| (js_sys::Math::random() * n as f64) as usize % n;
+        let a_idx = random_idx(candidates.len());
+        // pick from the remaining memez, so `b` is never `a`
+        let b_idx = (a_idx + 1 + random_idx(candidates.len() - 1)) % candidates.len();
+        Some((candidates[a_idx].clone(), candidates[b_idx].clone()))
+    }
+
+    fn view_sm_inputs(&self, link: &Scope<Self>, path: &str, label: &str) -> Html {
+        // fall back to what memez would install by default, until something is set
+        let (default_init, default_comp) = if path == BATTLE_PATH {
+            (BATTLE_SM_INIT.to_string(), BATTLE_SM_COMP.to_string())
+        } else {
+            (
+                self.config.sm_init_expr.clone(),
+                self.config.sm_comp_expr.clone(),
+            )
+        };
+        let SmExprsRaw(sm_init_raw, sm_comp_raw) =
+            self.sm_exprs.get(path).cloned().unwrap_or_default();
+        let sm_init = sm_init_raw.source().map_or(default_init, str::to_string);
+        let sm_comp = sm_comp_raw.source().map_or(default_comp, str::to_string);
+        let path_ = path.to_string();
+        let sm_init_handler = move |input: String| Msg::SmInitSubmit(path_.clone(), input);
+        let path_ = path.to_string();
+        // changes to the memez path's sm_comp alter the feed, so they are previewed first
+        let sm_comp_handler = move |input: String| {
+            if path_ == MEMEZ_PATH {
+                Msg::PreviewScores(PreviewedChange::SmComp(input))
+            } else {
+                Msg::SmCompSubmit(path_.clone(), input)
+            }
+        };
+        // a new sm_comp has to fit the sm_init which is currently set. if that one's source isn't
+        // known, the sm_comp can only be checked by itself.
+        let sm_init_ = match sm_init_raw {
+            SmExprRaw::SetOutsideWidget => None,
+            _ => Some(sm_init.clone()),
+        };
+        let sm_comp_check = move |input: &str| match &sm_init_ {
+            Some(sm_init) => check_sm_comp(sm_init, input),
+            None => check_sm_init(input),
+        };
+        let path_ = path.to_string();
+        html! {
+            <div>
+                { self.view_expr_input(link, check_sm_init, sm_init_handler, "sm_init".into(), format!("{} sm_init", label), sm_init, Vec::new()) }
+                { view_sm_expr_note(&sm_init_raw, &format!("{} sm_init", label)) }
+                <br/>
+                { self.view_expr_input(link, sm_comp_check, sm_comp_handler, "sm_comp".into(), format!("{} sm_comp", label), sm_comp, SM_COMP_PARAMS.iter().map(|p| p.to_string()).collect()) }
+                { view_sm_expr_note(&sm_comp_raw, &format!("{} sm_comp", label)) }
+                <button onclick={link.callback(move |_| Msg::ToggleSmHistory(path_.clone()))}>{ format!("{} history", label) }</button>
+                { self.view_sm_proposals(link, path) }
+                { self.view_sm_history(link, path) }
+                <br/>
+            </div>
+        }
+    }
+
+    fn view_sm_proposals(&self, link: &Scope<Self>, path: &str) -> Html {
+        let proposals = match self.sm_proposals.get(path) {
+            None => return html! {},
+            Some(proposals) => proposals,
+        };
+        let path_ = path.to_string();
+        view_sm_proposals(
+            proposals,
+            &base64::encode(agent_pk_to_vec_u8(self.memez_cell_id.1.clone())),
+            link.callback(move |i| Msg::ApproveSmProposal(path_.clone(), i)),
+        )
+    }
+
+    fn view_sm_history(&self, link: &Scope<Self>, path: &str) -> Html {
+        match &self.sm_history {
+            Some((shown_path, changes)) if shown_path == path => match changes {
+                None => html! { <p>{"loading the sensemaker history…"}</p> },
+                Some(history) => view_sm_config_history(
+                    history,
+                    &base64::encode(agent_pk_to_vec_u8(self.memez_cell_id.1.clone())),
+                    link.callback(Msg::RollbackSm),
+                    link.callback(|_| Msg::LoadMoreSmHistory),
+                ),
+            },
+            _ => html! {},
+        }
+    }
+
+    /// refetch what depends on `path`'s expressions, once a rollback or approval has set them.
+    fn sm_path_changed(&self, link: &Scope<Self>, path: &str) {
+        self.refresh_sm_history(link, path);
+        link.send_future(get_sm_exprs(
+            self.app_ws.clone(),
+            self.memez_cell_id.clone(),
+            path.to_string(),
+        ));
+        // the memez path's sm_comp feeds into the scores
+        if path == MEMEZ_PATH {
+            link.send_message(Msg::RefreshMemez);
+        }
+    }
+
+    fn refresh_sm_proposals(&self, link: &Scope<Self>, path: String) {
+        let app_ws_ = self.app_ws.clone();
+        let cell_id_ = self.memez_cell_id.clone();
+        link.send_future(async move {
+            let result = get_sm_proposals(&app_ws_, &cell_id_, MEMEZ_ZOME_NAME, path.clone()).await;
+            Msg::SmProposals(path, result)
+        });
+    }
+
+    /// reload `path`'s history, if it is shown.
+    fn refresh_sm_history(&self, link: &Scope<Self>, path: &str) {
+        if !matches!(&self.sm_history, Some((shown_path, _)) if shown_path == path) {
+            return;
+        }
+        let app_ws_ = self.app_ws.clone();
+        let cell_id_ = self.memez_cell_id.clone();
+        let path = path.to_string();
+        link.send_future(async move {
+            let result =
+                get_sm_config_history(&app_ws_, &cell_id_, MEMEZ_ZOME_NAME, path.clone(), None)
+                    .await;
+            Msg::SmHistory(path, result)
+        });
+    }
+
+    fn view_meme(&self, link: &Scope<Self>, item: FeedItemRaw) -> Html {
+        let FeedItemRaw {
+            meme_eh,
+            thumbnail,
+            score,
+            hidden,
+            battle_rating,
+        } = item;
+        let blurred = hidden && !self.revealed_memez.contains(&meme_eh);
+        let open_eh = meme_eh.clone();
+        let clap_eh = meme_eh.clone();
+        let flag_eh = meme_eh.clone();
+        let save_to_board_html = self.view_save_to_board(link, meme_eh.clone());
+        let reveal_html = if blurred {
+            html! {
+                <div class="hidden-meme-notice">
+                    <p>{"hidden by community moderation"}</p>
+                    <button onclick={link.callback(move |_| Msg::RevealMeme(meme_eh.clone()))}>{ "show anyway" }</button>
+                </div>
+            }
+        } else {
+            html! {}
+        };
+        html! {
+            <div>
+                {reveal_html}
+                <img
+                    src={mk_img_src(&thumbnail.blob_str)}
+                    style={ if blurred { "filter: blur(24px);" } else { "" } }
+                    onclick={link.callback(move |_| Msg::OpenMeme(open_eh.clone()))}
+                />
+                <p>{ format!("score: {}", score) }</p>
+                { match battle_rating {
+                    Some(rating) => html! { <p>{ format!("battle rating: {}", rating) }</p> },
+                    None => html! {},
+                } }
+                <button onclick={link.callback(move |_| Msg::ClapForMeme(clap_eh.clone()))}>{ "👏" }</button>
+                <button onclick={link.callback(move |_| Msg::FlagMeme(flag_eh.clone()))}>{ "🚩" }</button>
+                {save_to_board_html}
+            </div>
+        }
+    }
+
+    // TODO dedup
+    fn view_string_input<F>(
+        &self,
+        link: &Scope<Self>,
+        f: F,
+        class: String,
+        label: String,
+        value: String,
+    ) -> Html
+    where
+        F: Fn(String) -> Result<Msg, String> + 'static,
+    {
+        let onkeypress = link.batch_callback(move |e: KeyboardEvent| {
+            if e.key() == "Enter" {
+                let input: InputElement = e.target_unchecked_into();
+                match f(input.value()) {
+                    Ok(msg) => {
+                        input.set_value("");
+                        Some(msg)
+                    }
+                    Err(err) => {
+                        console_error!(format!("view_string_input: {}", err));
+                        None
+                    }
+                }
+            } else {
+                None
+            }
+        });
+        html! {
+            <div>
+                <label>{format!("{}: ", label)}</label>
+                <input
+                    {class}
+                    {value}
+                    {onkeypress}
+                />
+            </div>
+        }
+    }
+
+    /// like `view_string_input`, but the `rep_lang` expression is only submitted if it passes
+    /// `check`. otherwise, why it didn't is shown under the input, which keeps what was typed.
+    /// `params` are the expression's parameter names, for the block builder shown next to it.
+    fn view_expr_input<C, F>(
+        &self,
+        link: &Scope<Self>,
+        check: C,
+        f: F,
+        class: String,
+        label: String,
+        value: String,
+        params: Vec<String>,
+    ) -> Html
+    where
+        C: Fn(&str) -> Result<(), ExprError> + 'static,
+        F: Fn(String) -> Msg + 'static,
+    {
+        let label_ = label.clone();
+        let submit = Rc::new(move |expr_str: String| match check(&expr_str) {
+            Ok(()) => Ok(vec![Msg::ExprValid(label_.clone()), f(expr_str)]),
+            Err(err) => Err(vec![Msg::ExprInvalid(label_.clone(), expr_str, err)]),
+        });
+        let submit_ = submit.clone();
+        let onkeypress = link.batch_callback(move |e: KeyboardEvent| {
+            if e.key() == "Enter" {
+                let input: InputElement = e.target_unchecked_into();
+                match submit_(input.value()) {
+                    Ok(msgs) => {
+                        input.set_value("");
+                        msgs
+                    }
+                    Err(msgs) => msgs,
+                }
+            } else {
+                vec![]
+            }
+        });
+        let on_builder_submit =
+            link.batch_callback(move |expr_str: String| match submit(expr_str) {
+                Ok(msgs) | Err(msgs) => msgs,
+            });
+        let expr_error_html = match self.expr_errors.get(&label) {
+            None => html! {},
+            Some((expr_str, err)) => view_expr_error(expr_str, err),
+        };
+        html! {
+            <div>
+                <label>{format!("{}: ", label)}</label>
+                <input
+                    {class}
+                    value={value.clone()}
+                    {onkeypress}
+                />
+                <ExprBuilder {params} expr={value} on_submit={on_builder_submit} />
+                {expr_error_html}
+            </div>
+        }
+    }
+
+    /// sends `on_set(path, expr_str)` once the zome call succeeds, `Msg::SmProposed` if the change
+    /// awaits approval, or `Msg::SmSetFailed` if the call fails.
+    fn set_sm(
+        &self,
+        link: &Scope<Self>,
+        path: String,
+        expr_str: String,
+        zome_fn: String,
+        on_set: fn(String, String) -> Msg,
+    ) {
+        let app_ws_ = self.app_ws.clone();
+        let cell_id_ = self.memez_cell_id.clone();
+        link.send_future(async move {
+            let cmd = AppWsCmd::CallZome {
+                cell_id: cell_id_.clone(),
+                zome_name: MEMEZ_ZOME_NAME.into(),
+                fn_name: zome_fn.clone(),
+                payload: (path.clone(), expr_str.clone()).serialize_to_js_obj(),
+                provenance: cell_id_.1.clone(),
+                cap: "".into(),
+            };
+            let resp = app_ws_.call(cmd).await;
+            match resp {
+                Ok(AppWsCmdResponse::CallZome(val)) => {
+                    if val.as_bool() == Some(false) {
+                        Msg::SmProposed(path)
+                    } else {
+                        on_set(path, expr_str)
+                    }
+                }
+                Ok(resp) => Msg::SmSetFailed(format!("impossible: invalid response: {:?}", resp)),
+                Err(err) => Msg::SmSetFailed(format!("{}: {:?}", zome_fn, err)),
+            }
+        });
+    }
+}
+
+async fn get_sm_exprs(app_ws: AppWebsocket, cell_id: CellId, path: String) -> Msg {
+    let cmd = AppWsCmd::CallZome {
+        cell_id: cell_id.clone(),
+        zome_name: MEMEZ_ZOME_NAME.into(),
+        fn_name: "get_sm_exprs".into(),
+        payload: JsValue::from_str(&path),
+        provenance: cell_id.1.clone(),
+        cap: "".into(),
+    };
+    let resp = app_ws.call(cmd).await;
+    match resp {
+        Ok(AppWsCmdResponse::CallZome(val)) => {
+            Msg::SmExprs(path, SmExprsRaw::deserialize_from_js_obj(val))
+        }
+        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => Msg::Error(format!("err: {:?}", err)),
+    }
+}
+
+async fn get_all_memez(app_ws: AppWebsocket, cell_id: CellId, feed_score_comp: String) -> Msg {
+    let cmd = AppWsCmd::CallZome {
+        cell_id: cell_id.clone(),
+        zome_name: MEMEZ_ZOME_NAME.into(),
+        fn_name: "get_all_memez".into(),
+        payload: (feed_score_comp, cell_id.1.clone()).serialize_to_js_obj(),
+        provenance: cell_id.1.clone(),
+        cap: "".into(),
+    };
+    let resp = app_ws.call(cmd).await;
+    match resp {
+        Ok(AppWsCmdResponse::CallZome(val)) => Msg::ZomeCallResponse(ZomeCallResponse::Memes(
+            ListResultRaw::<FeedItemRaw>::deserialize_from_js_obj_(val),
+        )),
+        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => Msg::Error(format!("err: {:?}", err)),
+    }
+}
+
+async fn get_all_score_presets(app_ws: AppWebsocket, cell_id: CellId) -> Msg {
+    let cmd = AppWsCmd::CallZome {
+        cell_id: cell_id.clone(),
+        zome_name: MEMEZ_ZOME_NAME.into(),
+        fn_name: "get_all_score_presets".into(),
+        payload: JsValue::NULL,
+        provenance: cell_id.1.clone(),
+        cap: "".into(),
+    };
+    let resp = app_ws.call(cmd).await;
+    match resp {
+        Ok(AppWsCmdResponse::CallZome(val)) => {
+            let list_result =
+                ListResultRaw::<Pair<EntryHashRaw, PublishedScorePreset>>::deserialize_from_js_obj_(
+                    val,
+                );
+            log_list_errors("get_all_score_presets", &list_result.errors);
+            Msg::ZomeCallResponse(ZomeCallResponse::ScorePresets(
+                list_result
+                    .items
+                    .into_iter()
+                    .map(|pair| pair.into())
+                    .collect(),
+            ))
+        }
+        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => Msg::Error(format!("err: {:?}", err)),
+    }
+}
+
+async fn get_all_boards(app_ws: AppWebsocket, cell_id: CellId) -> Msg {
+    let cmd = AppWsCmd::CallZome {
+        cell_id: cell_id.clone(),
+        zome_name: MEMEZ_ZOME_NAME.into(),
+        fn_name: "get_all_boards".into(),
+        payload: JsValue::NULL,
+        provenance: cell_id.1.clone(),
+        cap: "".into(),
+    };
+    let resp = app_ws.call(cmd).await;
+    match resp {
+        Ok(AppWsCmdResponse::CallZome(val)) => {
+            let list_result =
+                ListResultRaw::<Pair<EntryHashRaw, Board>>::deserialize_from_js_obj_(val);
+            log_list_errors("get_all_boards", &list_result.errors);
+            Msg::ZomeCallResponse(ZomeCallResponse::Boards(
+                list_result
+                    .items
+                    .into_iter()
+                    .map(|pair| pair.into())
+                    .collect(),
+            ))
+        }
+        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => Msg::Error(format!("err: {:?}", err)),
+    }
+}
+
+async fn get_board_memez(app_ws: AppWebsocket, cell_id: CellId, board_eh: EntryHashRaw) -> Msg {
+    let cmd = AppWsCmd::CallZome {
+        cell_id: cell_id.clone(),
+        zome_name: MEMEZ_ZOME_NAME.into(),
+        fn_name: "get_board_memez".into(),
+        payload: board_eh.clone().serialize_to_js_obj(),
+        provenance: cell_id.1.clone(),
+        cap: "".into(),
+    };
+    let resp = app_ws.call(cmd).await;
+    match resp {
+        Ok(AppWsCmdResponse::CallZome(val)) => {
+            let list_result =
+                ListResultRaw::<Pair<EntryHashRaw, MemeThumbnail>>::deserialize_from_js_obj_(val);
+            log_list_errors("get_board_memez", &list_result.errors);
+            Msg::ZomeCallResponse(ZomeCallResponse::BoardMemez(
+                board_eh,
+                list_result
+                    .items
+                    .into_iter()
+                    .map(|pair| pair.into())
+                    .collect(),
+            ))
+        }
+        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => Msg::Error(format!("err: {:?}", err)),
+    }
+}
+
+fn log_list_errors(zome_fn: &str, errors: &[(EntryHashRaw, String)]) {
+    for (_eh, reason) in errors {
+        console_error!(format!("{}: could not load an item: {}", zome_fn, reason));
+    }
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+fn mk_img_src(blob_str: &str) -> String {
+    "data:img;base64,".to_string() + blob_str
 }
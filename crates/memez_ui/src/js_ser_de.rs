@@ -1,8 +1,16 @@
-use js_sys::{Array, Object, Reflect};
+use hdk::prelude::AgentPubKey;
+use js_sys::{Array, Object, Reflect, Uint8Array};
 use wasm_bindgen::{prelude::*, JsCast};
 
-use holochain_client_wrapper::{DeserializeFromJsObj, EntryHashRaw, SerializeToJsObj};
-use memez_core::types::Meme;
+use holochain_client_wrapper::{
+    DeserializeFromJsObj, EntryHashRaw, EntryHeaderHashPairRaw, SerializeToJsObj,
+};
+use memez_core::types::{
+    Board, CreateScorePresetInput, Meme, MemeThumbnail, MemezConfig, PublishedScorePreset,
+    ScorePreset, SimulateFeedInput, UploadMemeInput,
+};
+use widget_core::config::SensemakerProperties;
+pub use widget_helpers::list_result::ListResultRaw;
 
 pub struct Pair<A, B>(pub A, pub B);
 
@@ -13,16 +21,31 @@ impl<A, B> Into<(A, B)> for Pair<A, B> {
     }
 }
 
-pub struct Triple<A, B, C>(pub A, pub B, pub C);
+/// the UI-side mirror of `memez_core::types::FeedItem`.
+#[derive(Clone)]
+pub struct FeedItemRaw {
+    pub meme_eh: EntryHashRaw,
+    pub thumbnail: MemeThumbnail,
+    pub score: i64,
+    pub hidden: bool,
+    pub battle_rating: Option<i64>,
+}
 
-impl<A, B, C> Into<(A, B, C)> for Triple<A, B, C> {
-    fn into(self) -> (A, B, C) {
-        let Triple(a, b, c) = self;
-        (a, b, c)
-    }
+/// the UI-side mirror of `memez_core::types::SimulatedScore`.
+pub struct SimulatedScoreRaw {
+    pub meme_eh: EntryHashRaw,
+    pub current: Option<i64>,
+    pub simulated: Option<i64>,
 }
 
-pub type MemeEhScoreVec = Vec<Triple<EntryHashRaw, Meme, i64>>;
+/// the UI-side mirror of `memez_core::types::UploadMemeOutcome`.
+pub enum UploadMemeOutcomeRaw {
+    Uploaded(EntryHashRaw),
+    Duplicate(EntryHashRaw),
+    NearDuplicates(Vec<(EntryHashRaw, MemeThumbnail)>),
+}
+
+pub type MemeThumbnailEhVec = Vec<Pair<EntryHashRaw, MemeThumbnail>>;
 
 pub trait SerializeToJsObj_ {
     fn serialize_to_js_obj_(self) -> JsValue;
@@ -52,6 +75,114 @@ impl SerializeToJsObj_ for Meme {
     }
 }
 
+impl SerializeToJsObj_ for UploadMemeInput {
+    fn serialize_to_js_obj_(self) -> JsValue {
+        let ret = move || -> Result<JsValue, JsValue> {
+            let val: JsValue = Object::new().dyn_into()?;
+            assert!(Reflect::set(
+                &val,
+                &JsValue::from_str("meme"),
+                &self.meme.serialize_to_js_obj_(),
+            )?);
+            let thumbnail_blob_str = match self.thumbnail_blob_str {
+                Some(blob_str) => blob_str.serialize_to_js_obj(),
+                None => JsValue::NULL,
+            };
+            assert!(Reflect::set(
+                &val,
+                &JsValue::from_str("thumbnail_blob_str"),
+                &thumbnail_blob_str,
+            )?);
+            let perceptual_hash = match self.perceptual_hash {
+                Some(perceptual_hash) => perceptual_hash.serialize_to_js_obj(),
+                None => JsValue::NULL,
+            };
+            assert!(Reflect::set(
+                &val,
+                &JsValue::from_str("perceptual_hash"),
+                &perceptual_hash,
+            )?);
+            assert!(Reflect::set(
+                &val,
+                &JsValue::from_str("allow_near_duplicate"),
+                &JsValue::from_bool(self.allow_near_duplicate),
+            )?);
+            Ok(val)
+        };
+        ret().expect("operations to succeed")
+    }
+}
+
+impl SerializeToJsObj_ for CreateScorePresetInput {
+    fn serialize_to_js_obj_(self) -> JsValue {
+        let ret = move || -> Result<JsValue, JsValue> {
+            let val: JsValue = Object::new().dyn_into()?;
+            assert!(Reflect::set(
+                &val,
+                &JsValue::from_str("name"),
+                &self.name.serialize_to_js_obj(),
+            )?);
+            assert!(Reflect::set(
+                &val,
+                &JsValue::from_str("description"),
+                &self.description.serialize_to_js_obj(),
+            )?);
+            assert!(Reflect::set(
+                &val,
+                &JsValue::from_str("expr"),
+                &self.expr.serialize_to_js_obj(),
+            )?);
+            Ok(val)
+        };
+        ret().expect("operations to succeed")
+    }
+}
+
+impl SerializeToJsObj_ for SimulateFeedInput {
+    fn serialize_to_js_obj_(self) -> JsValue {
+        let ret = move || -> Result<JsValue, JsValue> {
+            let val: JsValue = Object::new().dyn_into()?;
+            let sm_comp = match self.sm_comp {
+                Some(sm_comp) => sm_comp.serialize_to_js_obj(),
+                None => JsValue::NULL,
+            };
+            assert!(Reflect::set(&val, &JsValue::from_str("sm_comp"), &sm_comp)?);
+            assert!(Reflect::set(
+                &val,
+                &JsValue::from_str("current_feed_score_comp"),
+                &self.current_feed_score_comp.serialize_to_js_obj(),
+            )?);
+            assert!(Reflect::set(
+                &val,
+                &JsValue::from_str("feed_score_comp"),
+                &self.feed_score_comp.serialize_to_js_obj(),
+            )?);
+            Ok(val)
+        };
+        ret().expect("operations to succeed")
+    }
+}
+
+impl SerializeToJsObj_ for Vec<String> {
+    fn serialize_to_js_obj_(self) -> JsValue {
+        let val = Array::new();
+        for s in self {
+            let _ = val.push(&s.serialize_to_js_obj());
+        }
+        val.dyn_into().expect("Array conversion to succeed")
+    }
+}
+
+impl SerializeToJsObj_ for (EntryHashRaw, EntryHashRaw) {
+    fn serialize_to_js_obj_(self) -> JsValue {
+        let (a, b) = self;
+        let val = Array::new();
+        let _ = val.push(&a.serialize_to_js_obj());
+        let _ = val.push(&b.serialize_to_js_obj());
+        val.dyn_into().expect("Array conversion to succeed")
+    }
+}
+
 impl<A: SerializeToJsObj_, B: SerializeToJsObj> SerializeToJsObj_ for Pair<A, B> {
     fn serialize_to_js_obj_(self) -> JsValue {
         let Pair(a, b) = self;
@@ -87,19 +218,9 @@ impl<A: DeserializeFromJsObj, B: DeserializeFromJsObj_> DeserializeFromJsObj_ fo
     }
 }
 
-impl<A: DeserializeFromJsObj, B: DeserializeFromJsObj_, C: DeserializeFromJsObj>
-    DeserializeFromJsObj_ for Triple<A, B, C>
-{
+impl<T: DeserializeFromJsObj_> DeserializeFromJsObj_ for ListResultRaw<T> {
     fn deserialize_from_js_obj_(v: JsValue) -> Self {
-        let arr: Array = v.dyn_into().expect("Array conversion to succeed");
-        let a = arr.at(0);
-        let b = arr.at(1);
-        let c = arr.at(2);
-        Triple(
-            A::deserialize_from_js_obj(a),
-            B::deserialize_from_js_obj_(b),
-            C::deserialize_from_js_obj(c),
-        )
+        ListResultRaw::deserialize_from_js_obj_with(v, T::deserialize_from_js_obj_)
     }
 }
 
@@ -112,6 +233,42 @@ impl<A: DeserializeFromJsObj, B: DeserializeFromJsObj> DeserializeFromJsObj_ for
     }
 }
 
+impl DeserializeFromJsObj_ for String {
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        String::deserialize_from_js_obj(v)
+    }
+}
+
+impl DeserializeFromJsObj_ for MemezConfig {
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        let field = |name: &str| {
+            Reflect::get(&v, &JsValue::from_str(name)).expect("object field get to succeed")
+        };
+        Self {
+            // flattened into the config by serde
+            sensemaker: SensemakerProperties {
+                sensemaker_dna_hash: field("sensemaker_dna_hash").as_string(),
+                progenitor: field("progenitor").as_string(),
+                sm_admins: Vec::<String>::deserialize_from_js_obj_(field("sm_admins")),
+                sm_approvals_required: field("sm_approvals_required")
+                    .as_f64()
+                    .expect("number conversion to succeed")
+                    as u32,
+            },
+            sm_init_expr: String::deserialize_from_js_obj(field("sm_init_expr")),
+            sm_comp_expr: String::deserialize_from_js_obj(field("sm_comp_expr")),
+            feed_score_comp: String::deserialize_from_js_obj(field("feed_score_comp")),
+            hide_comp: String::deserialize_from_js_obj(field("hide_comp")),
+            max_meme_bytes: field("max_meme_bytes")
+                .as_f64()
+                .expect("number conversion to succeed") as u64,
+            allowed_extensions: Vec::<String>::deserialize_from_js_obj_(field(
+                "allowed_extensions",
+            )),
+        }
+    }
+}
+
 impl DeserializeFromJsObj_ for Meme {
     fn deserialize_from_js_obj_(v: JsValue) -> Self {
         let filename = String::deserialize_from_js_obj(
@@ -123,3 +280,139 @@ impl DeserializeFromJsObj_ for Meme {
         Self { filename, blob_str }
     }
 }
+
+impl DeserializeFromJsObj_ for MemeThumbnail {
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        let filename = String::deserialize_from_js_obj(
+            Reflect::get(&v, &JsValue::from_str("filename")).expect("object field get to succeed"),
+        );
+        let blob_str = String::deserialize_from_js_obj(
+            Reflect::get(&v, &JsValue::from_str("blob_str")).expect("object field get to succeed"),
+        );
+        Self { filename, blob_str }
+    }
+}
+
+impl DeserializeFromJsObj_ for ScorePreset {
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        let name = String::deserialize_from_js_obj(
+            Reflect::get(&v, &JsValue::from_str("name")).expect("object field get to succeed"),
+        );
+        let description = String::deserialize_from_js_obj(
+            Reflect::get(&v, &JsValue::from_str("description"))
+                .expect("object field get to succeed"),
+        );
+        let expr = String::deserialize_from_js_obj(
+            Reflect::get(&v, &JsValue::from_str("expr")).expect("object field get to succeed"),
+        );
+        Self {
+            name,
+            description,
+            expr,
+        }
+    }
+}
+
+impl DeserializeFromJsObj_ for PublishedScorePreset {
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        let preset = ScorePreset::deserialize_from_js_obj_(
+            Reflect::get(&v, &JsValue::from_str("preset")).expect("object field get to succeed"),
+        );
+        let author = AgentPubKey::from_raw_39_panicky(
+            Uint8Array::new(
+                &Reflect::get(&v, &JsValue::from_str("author"))
+                    .expect("object field get to succeed"),
+            )
+            .to_vec(),
+        );
+        Self { preset, author }
+    }
+}
+
+impl DeserializeFromJsObj_ for Board {
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        let name = String::deserialize_from_js_obj(
+            Reflect::get(&v, &JsValue::from_str("name")).expect("object field get to succeed"),
+        );
+        let author = AgentPubKey::from_raw_39_panicky(
+            Uint8Array::new(
+                &Reflect::get(&v, &JsValue::from_str("author"))
+                    .expect("object field get to succeed"),
+            )
+            .to_vec(),
+        );
+        Self { name, author }
+    }
+}
+
+impl DeserializeFromJsObj_ for UploadMemeOutcomeRaw {
+    /// serde encodes enums as an object with a single key, the variant name.
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        let get_variant = |name: &str| {
+            let field =
+                Reflect::get(&v, &JsValue::from_str(name)).expect("object field get to succeed");
+            if field.is_undefined() {
+                None
+            } else {
+                Some(field)
+            }
+        };
+        if let Some(field) = get_variant("Uploaded") {
+            let (meme_eh, _meme_hh) = EntryHeaderHashPairRaw::deserialize_from_js_obj_(field);
+            UploadMemeOutcomeRaw::Uploaded(meme_eh)
+        } else if let Some(field) = get_variant("Duplicate") {
+            UploadMemeOutcomeRaw::Duplicate(EntryHashRaw::deserialize_from_js_obj(field))
+        } else if let Some(field) = get_variant("NearDuplicates") {
+            UploadMemeOutcomeRaw::NearDuplicates(
+                MemeThumbnailEhVec::deserialize_from_js_obj_(field)
+                    .into_iter()
+                    .map(|pair| pair.into())
+                    .collect(),
+            )
+        } else {
+            panic!("impossible: unknown UploadMemeOutcome variant")
+        }
+    }
+}
+
+impl DeserializeFromJsObj_ for FeedItemRaw {
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        let meme_eh = EntryHashRaw::deserialize_from_js_obj(
+            Reflect::get(&v, &JsValue::from_str("meme_eh")).expect("object field get to succeed"),
+        );
+        let thumbnail = MemeThumbnail::deserialize_from_js_obj_(
+            Reflect::get(&v, &JsValue::from_str("thumbnail")).expect("object field get to succeed"),
+        );
+        let score = i64::deserialize_from_js_obj(
+            Reflect::get(&v, &JsValue::from_str("score")).expect("object field get to succeed"),
+        );
+        let hidden = Reflect::get(&v, &JsValue::from_str("hidden"))
+            .expect("object field get to succeed")
+            .as_bool()
+            .expect("bool conversion to succeed");
+        let battle_rating = Reflect::get(&v, &JsValue::from_str("battle_rating"))
+            .expect("object field get to succeed")
+            .as_f64()
+            .map(|x| x as i64);
+        Self {
+            meme_eh,
+            thumbnail,
+            score,
+            hidden,
+            battle_rating,
+        }
+    }
+}
+
+impl DeserializeFromJsObj_ for SimulatedScoreRaw {
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        let field = |name: &str| {
+            Reflect::get(&v, &JsValue::from_str(name)).expect("object field get to succeed")
+        };
+        Self {
+            meme_eh: EntryHashRaw::deserialize_from_js_obj(field("meme_eh")),
+            current: field("current").as_f64().map(|x| x as i64),
+            simulated: field("simulated").as_f64().map(|x| x as i64),
+        }
+    }
+}
@@ -1,33 +1,128 @@
 use hdk::prelude::{holo_hash::DnaHash, *};
+use std::collections::BTreeMap;
 
 use common::{
-    compose_entry_hash_path, get_latest_linked_entry, remote_get_sensemaker_entry_by_path,
-    remote_initialize_sm_data, remote_initialize_sm_data_path,
-    remote_set_sensemaker_entry_parse_rl_expr, remote_step_sm, remote_step_sm_path,
-    sensemaker_cell_id_anchor, sensemaker_cell_id_fns, util, SensemakerCellId, SensemakerEntry,
+    compose_entry_hash_path, compose_paths, get_latest_linked_entry, remote_initialize_sm_data,
+    remote_initialize_sm_data_path, sensemaker_cell_id_anchor, sensemaker_cell_id_fns, util,
+    SensemakerCellId, SensemakerEntry,
 };
 use social_sensemaker_core::{OWNER_TAG, SM_COMP_TAG, SM_DATA_TAG, SM_INIT_TAG};
+use widget_core::{
+    buckets::{DayBuckets, Page},
+    config::dna_properties,
+    error::WidgetError,
+};
+use widget_sensemaker::{
+    eval_value, int_value, replay,
+    sm_ops::{ArmedSmOp, CompletedSmOp, FailedSmOp, PendingSmOp, SmOp},
+    validate_sm_link, widget_sensemaker_fns, SmExprProposal, SmExprSource,
+};
 
 use paperz_core::{
-    types::{Annotation, Paper},
-    AGENT_PATH, ANNOTATIONZ_PATH, ANN_TAG, PAPER_TAG,
+    types::{Annotation, ListResult, Paper, PaperzConfig, SimulatedAgentScore, UploadPaperOutcome},
+    AGENT_PATH, ANNOTATIONZ_PATH, ANN_TAG, CONTENT_HASH_TAG, CREDITED_AGENT_TAG, PAPER_TAG,
 };
 
 entry_defs![
     Paper::entry_def(),
     Annotation::entry_def(),
+    SmExprSource::entry_def(),
+    SmExprProposal::entry_def(),
+    PendingSmOp::entry_def(),
+    ArmedSmOp::entry_def(),
+    CompletedSmOp::entry_def(),
+    FailedSmOp::entry_def(),
     SensemakerCellId::entry_def(),
     PathEntry::entry_def()
 ];
 
 sensemaker_cell_id_fns! {}
+widget_sensemaker_fns! {
+    paths: [ANNOTATIONZ_PATH, AGENT_PATH],
+    tags: [SM_INIT_TAG, SM_COMP_TAG],
+}
+
+#[hdk_extern]
+/// `ensure_sm_admin` only guards the externs, so its rule is also enforced here, for every agent:
+/// only sm admins may propose, approve & set sensemaker expressions.
+fn validate(op: Op) -> ExternResult<ValidateCallbackResult> {
+    match op {
+        Op::RegisterCreateLink { create_link } => {
+            validate_sm_link(&create_link.hashed.content, &[])
+        }
+        _ => Ok(ValidateCallbackResult::Valid),
+    }
+}
+
+fn entry_not_found(err: WasmError) -> WasmError {
+    WidgetError::EntryNotFound(err.to_string()).into()
+}
+
+/// see `DayBuckets`. the legacy anchor is the single one all papers used to hang off.
+const PAPER_BUCKETS: DayBuckets = DayBuckets {
+    anchor_type: "paperz_by_day",
+    legacy_anchor_type: "paperz",
+    tag: PAPER_TAG,
+};
+
+/// like `PAPER_BUCKETS`, but for annotations.
+const ANNOTATION_BUCKETS: DayBuckets = DayBuckets {
+    anchor_type: "annotationz_by_day",
+    legacy_anchor_type: ANN_TAG,
+    tag: ANN_TAG,
+};
+
+#[hdk_extern]
+/// every paper bucket, newest first.
+fn list_paper_buckets(_: ()) -> ExternResult<Vec<String>> {
+    PAPER_BUCKETS.list_buckets()
+}
+
+fn content_hash_anchor(content_hash: String) -> ExternResult<EntryHash> {
+    anchor("paper_content_hash".into(), content_hash)
+}
+
+#[hdk_extern]
+/// the DNA properties, with `PaperzConfig::default` standing in for any they leave out. see
+/// `widget_core::config::dna_properties`.
+fn get_config(_: ()) -> ExternResult<PaperzConfig> {
+    dna_properties()
+}
+
+/// reject papers which the config doesn't allow.
+fn check_paper_allowed(config: &PaperzConfig, paper: &Paper, size: u64) -> ExternResult<()> {
+    if size > config.max_paper_bytes {
+        return Err(WidgetError::ValidationFailed(format!(
+            "paper is {} bytes, but at most {} are allowed",
+            size, config.max_paper_bytes
+        ))
+        .into());
+    }
+    let extension = paper
+        .filename
+        .rsplit_once('.')
+        .map(|(_name, extension)| extension.to_lowercase())
+        .unwrap_or_default();
+    if !config.allowed_extensions.is_empty() && !config.allowed_extensions.contains(&extension) {
+        return Err(WidgetError::ValidationFailed(format!(
+            "papers must be one of: {}",
+            config.allowed_extensions.join(", ")
+        ))
+        .into());
+    }
+    Ok(())
+}
 
-fn paper_anchor() -> ExternResult<EntryHash> {
-    anchor("paperz".into(), "".into())
+/// the hex SHA-256 of the paper's decoded bytes. the filename doesn't take part.
+fn paper_content_hash(bytes: Vec<u8>) -> ExternResult<String> {
+    Ok(hash_sha256(bytes)?
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
 }
 
 #[hdk_extern]
-fn upload_paper((paper, agent_pk): (Paper, AgentPubKey)) -> ExternResult<(EntryHash, HeaderHash)> {
+fn upload_paper((paper, agent_pk): (Paper, AgentPubKey)) -> ExternResult<UploadPaperOutcome> {
     debug!(
         "upload_paper: received input of length {}",
         paper.blob_str.len()
@@ -36,81 +131,212 @@ fn upload_paper((paper, agent_pk): (Paper, AgentPubKey)) -> ExternResult<(EntryH
     let agent_b64: String = base64::encode(agent_pk.clone().into_inner());
     debug!("upload_paper: agent_b64: {}", agent_b64);
 
+    let bytes = base64::decode(&paper.blob_str).map_err(|err| {
+        WasmError::from(WidgetError::ValidationFailed(format!(
+            "paper blob_str is not base64: {}",
+            err
+        )))
+    })?;
+    check_paper_allowed(&get_config(())?, &paper, bytes.len() as u64)?;
+
+    let content_anchor = content_hash_anchor(paper_content_hash(bytes)?)?;
+    let content_links = get_links(content_anchor.clone(), Some(LinkTag::new(CONTENT_HASH_TAG)))?;
+    if let Some(lnk) = content_links.into_iter().next() {
+        let paper_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+        debug!("upload_paper: already exists as {}", paper_eh);
+        return Ok(UploadPaperOutcome::AlreadyExists(paper_eh));
+    }
+
     let paper_hh = create_entry(&paper)?;
     let paper_eh = hash_entry(&paper)?;
+    PAPER_BUCKETS.link(paper_eh.clone())?;
     create_link(
-        paper_anchor()?,
+        content_anchor,
         paper_eh.clone(),
         LinkType(0),
-        LinkTag::new(PAPER_TAG),
+        LinkTag::new(CONTENT_HASH_TAG),
+    )?;
+
+    // `agent_pk` is the caller's to choose, so the simulation needs to know whom it was
+    create_link(
+        paper_eh.clone(),
+        paper_eh.clone(),
+        LinkType(0),
+        LinkTag::new(format!("{}{}", CREDITED_AGENT_TAG, agent_b64)),
     )?;
 
     // increment agent SM
-    step_sm_path_remote((AGENT_PATH.into(), agent_b64, "1".into()))?;
+    run_sm_op(SmOp::StepSmPath(AGENT_PATH.into(), agent_b64, "1".into()))?;
 
-    Ok((paper_eh, paper_hh))
+    Ok(UploadPaperOutcome::Uploaded(paper_eh, paper_hh))
 }
 
-#[hdk_extern]
-fn get_all_paperz(_: ()) -> ExternResult<Vec<(EntryHash, Paper)>> {
-    let paper_entry_links = get_links(paper_anchor()?, Some(LinkTag::new(PAPER_TAG)))?;
+/// the papers `paper_links` point at, and why any couldn't be loaded.
+fn get_linked_paperz(
+    paper_links: Vec<Link>,
+) -> (Vec<(EntryHash, Paper)>, Vec<(EntryHash, String)>) {
     let mut paperz: Vec<(EntryHash, Paper)> = Vec::new();
-    let mut opt_err = None;
-    for lnk in paper_entry_links {
-        let res: ExternResult<(EntryHash, Paper)> = {
-            let paper_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
-            let (paper, _hh) =
-                util::try_get_and_convert_with_hh(paper_eh.clone(), GetOptions::content())?;
-            Ok((paper_eh, paper))
-        };
-
-        match res {
-            Ok(tup) => paperz.push(tup),
+    let mut errors: Vec<(EntryHash, String)> = Vec::new();
+    for lnk in paper_links {
+        let paper_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+        match util::try_get_and_convert_with_hh(paper_eh.clone(), GetOptions::content())
+            .map_err(entry_not_found)
+        {
+            Ok((paper, _hh)) => paperz.push((paper_eh, paper)),
             Err(err) => {
                 debug!("err in fetching Paper: {}", err);
-                opt_err = Some(err);
+                errors.push((paper_eh, err.to_string()));
             }
         }
     }
-    match opt_err {
-        None => Ok(paperz),
-        Some(err) => Err(WasmError::Guest(format!("get_all_paperz: {:?}", err))),
+    (paperz, errors)
+}
+
+#[hdk_extern]
+fn get_all_paperz(_: ()) -> ExternResult<ListResult<(EntryHash, Paper)>> {
+    let (paperz, errors) = get_linked_paperz(PAPER_BUCKETS.get_links()?);
+    Ok(ListResult {
+        items: paperz,
+        errors,
+    })
+}
+
+#[hdk_extern]
+/// at least `min_items` papers (fewer only on the last page), newest first, starting with the
+/// bucket `from_bucket`, or the newest one if that is `None`. see `DayBuckets::get_page`.
+fn get_paperz_page(
+    (from_bucket, min_items): (Option<String>, u32),
+) -> ExternResult<Page<(EntryHash, Paper)>> {
+    let (paper_links, next_bucket) = PAPER_BUCKETS.get_page(from_bucket, min_items as usize)?;
+    let (items, errors) = get_linked_paperz(paper_links);
+    Ok(Page {
+        items,
+        errors,
+        next_bucket,
+    })
+}
+
+/// the `agent_b64` whose agent sensemaker state the upload of `paper_eh` stepped. papers uploaded
+/// before that was recorded are put down to their author, who is whom the paperz UI credits.
+fn credited_agent_b64(paper_eh: EntryHash) -> ExternResult<String> {
+    let credited_links = get_links(paper_eh.clone(), Some(LinkTag::new(CREDITED_AGENT_TAG)))?;
+    let credited = credited_links.into_iter().find_map(|lnk| {
+        String::from_utf8(lnk.tag.0)
+            .ok()?
+            .strip_prefix(CREDITED_AGENT_TAG)
+            .map(str::to_string)
+    });
+    if let Some(agent_b64) = credited {
+        return Ok(agent_b64);
+    }
+    match get(paper_eh, GetOptions::content())? {
+        Some(element) => Ok(base64::encode(
+            element.header().author().clone().into_inner(),
+        )),
+        None => Err(WidgetError::EntryNotFound("paper not found".into()).into()),
     }
 }
 
-fn annotation_anchor() -> ExternResult<EntryHash> {
-    anchor(ANN_TAG.into(), "".into())
+#[hdk_extern]
+/// replay every paper upload through `sm_comp`, from the sm_init in use, next to each credited
+/// agent's current agent sensemaker state, without writing anything. see `credited_agent_b64`.
+/// papers & agents which can't be simulated are left out, and their errors keyed by the paper.
+fn simulate_agent_scores(sm_comp: String) -> ExternResult<ListResult<SimulatedAgentScore>> {
+    let sm_init = match get_sm_init(AGENT_PATH.into())? {
+        Some((_eh, se)) => se.output_flat_value,
+        None => eval_value(get_config(())?.sm_init_expr)?,
+    };
+    // agent_b64 -> the papers whose uploads stepped it, ordered so that results are stable
+    let mut uploads: BTreeMap<String, Vec<EntryHash>> = BTreeMap::new();
+    let mut errors: Vec<(EntryHash, String)> = Vec::new();
+    for lnk in PAPER_BUCKETS.get_links()? {
+        let paper_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+        match credited_agent_b64(paper_eh.clone()) {
+            Ok(agent_b64) => uploads.entry(agent_b64).or_default().push(paper_eh),
+            Err(err) => errors.push((paper_eh, err.to_string())),
+        }
+    }
+
+    // a closure, rather than a block, so that `?` only abandons the one agent
+    let simulate_agent =
+        |agent_b64: String, upload_count: usize| -> ExternResult<SimulatedAgentScore> {
+            let path_string = compose_paths(&AGENT_PATH.into(), &agent_b64);
+            let current = get_sm_generic(path_string, SM_DATA_TAG.into())?
+                .and_then(|(_eh, se)| int_value(&se.output_flat_value));
+            let simulated = int_value(&replay(
+                sm_init.clone(),
+                &sm_comp,
+                &vec!["1".to_string(); upload_count],
+            )?);
+            Ok(SimulatedAgentScore {
+                agent_b64,
+                current,
+                simulated,
+            })
+        };
+
+    let mut items: Vec<SimulatedAgentScore> = Vec::new();
+    for (agent_b64, paper_ehs) in uploads {
+        match simulate_agent(agent_b64.clone(), paper_ehs.len()) {
+            Ok(item) => items.push(item),
+            Err(err) => {
+                debug!("simulate_agent_scores: {}: {}", agent_b64, err);
+                // `ListResult` errors are keyed by entry, so the agent's are put on its papers
+                errors.extend(
+                    paper_ehs
+                        .into_iter()
+                        .map(|paper_eh| (paper_eh, format!("{}: {}", agent_b64, err))),
+                );
+            }
+        }
+    }
+    Ok(ListResult { items, errors })
+}
+
+#[hdk_extern]
+/// every annotation bucket, newest first.
+fn list_annotation_buckets(_: ()) -> ExternResult<Vec<String>> {
+    ANNOTATION_BUCKETS.list_buckets()
+}
+
+#[hdk_extern]
+/// move every paper & annotation on the legacy single anchors into their day buckets. see
+/// `DayBuckets::migrate`. returns the number of (papers, annotations) moved.
+fn migrate_anchors(_: ()) -> ExternResult<(u32, u32)> {
+    Ok((PAPER_BUCKETS.migrate()?, ANNOTATION_BUCKETS.migrate()?))
 }
 
 #[hdk_extern]
 fn get_annotations_for_paper(
     paper_entry_hash: EntryHash,
-) -> ExternResult<Vec<(EntryHash, Annotation)>> {
+) -> ExternResult<ListResult<(EntryHash, Annotation)>> {
     let mut annotations: Vec<(EntryHash, Annotation)> = Vec::new();
+    let mut errors: Vec<(EntryHash, String)> = Vec::new();
     for link in get_links(paper_entry_hash, Some(LinkTag::new(ANN_TAG)))? {
         let annotation_entry_hash = link.target.into_entry_hash().expect("should be an Entry.");
-        match util::try_get_and_convert(annotation_entry_hash.clone(), GetOptions::content()) {
+        match util::try_get_and_convert(annotation_entry_hash.clone(), GetOptions::content())
+            .map_err(entry_not_found)
+        {
             Ok(annotation) => {
                 annotations.push((annotation_entry_hash, annotation));
             }
             Err(err) => {
                 error!("get_annotations_for_paper: err: {}", err);
+                errors.push((annotation_entry_hash, err.to_string()));
             }
         }
     }
-    Ok(annotations)
+    Ok(ListResult {
+        items: annotations,
+        errors,
+    })
 }
 
 #[hdk_extern]
 fn create_annotation(annotation: Annotation) -> ExternResult<(EntryHash, HeaderHash)> {
     let annotation_headerhash = create_entry(&annotation)?;
     let annotation_entryhash = hash_entry(&annotation)?;
-    create_link(
-        annotation_anchor()?,
-        annotation_entryhash.clone(),
-        LinkType(0),
-        LinkTag::new(ANN_TAG),
-    )?;
+    ANNOTATION_BUCKETS.link(annotation_entryhash.clone())?;
     create_link(
         annotation.paper_ref,
         annotation_entryhash.clone(),
@@ -118,78 +344,73 @@ fn create_annotation(annotation: Annotation) -> ExternResult<(EntryHash, HeaderH
         LinkTag::new(ANN_TAG),
     )?;
 
-    let cell_id = get_sensemaker_cell_id(())?;
-    let payload = (ANNOTATIONZ_PATH.to_string(), annotation_entryhash.clone());
-    remote_initialize_sm_data(cell_id, None, payload)?;
+    run_sm_op(SmOp::InitSmData(
+        ANNOTATIONZ_PATH.into(),
+        annotation_entryhash.clone(),
+    ))?;
 
     Ok((annotation_entryhash, annotation_headerhash))
 }
 
 #[hdk_extern]
-fn init_agent_sm_data(payload: (String, String)) -> ExternResult<()> {
-    let cell_id = get_sensemaker_cell_id(())?;
-    remote_initialize_sm_data_path(cell_id, None, payload)
-}
-
-#[hdk_extern]
-fn get_sm_data(target_eh: EntryHash) -> ExternResult<Option<(EntryHash, SensemakerEntry)>> {
-    let path_string = compose_entry_hash_path(&ANNOTATIONZ_PATH.into(), target_eh);
-    get_sm_generic(path_string, SM_DATA_TAG.to_string())
-}
-
-#[hdk_extern]
-fn get_sm_init(path_string: String) -> ExternResult<Option<(EntryHash, SensemakerEntry)>> {
-    get_sm_generic(path_string, SM_INIT_TAG.into())
-}
-
-#[hdk_extern]
-fn get_sm_comp(path_string: String) -> ExternResult<Option<(EntryHash, SensemakerEntry)>> {
-    get_sm_generic(path_string, SM_COMP_TAG.into())
-}
-
-fn get_sm_generic(
-    path_string: String,
-    link_tag_string: String,
-) -> ExternResult<Option<(EntryHash, SensemakerEntry)>> {
-    let cell_id = get_sensemaker_cell_id(())?;
-    remote_get_sensemaker_entry_by_path(cell_id, None, (path_string, link_tag_string))
+/// whether any annotation is missing its sensemaker data, i.e. whether `reconcile_sm_data` has
+/// anything to do. unlike it, this writes nothing.
+fn has_missing_sm_data(_: ()) -> ExternResult<bool> {
+    for lnk in ANNOTATION_BUCKETS.get_links()? {
+        let annotation_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+        if get_sm_data(annotation_eh)?.is_none() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
 }
 
 #[hdk_extern]
-/// set the sm_init state for the path_string to the `rep_lang` interpretation of `expr_str`
-pub fn set_sm_init((path_string, expr_str): (String, String)) -> ExternResult<bool> {
-    set_sensemaker_entry(path_string, SM_INIT_TAG.into(), expr_str)
-}
+/// initialize the missing sensemaker data of every annotation. `create_annotation` commits the
+/// annotation before calling into the sensemaker, so a failed call there leaves it without any.
+/// annotations which needed no repair are left out of the result.
+fn reconcile_sm_data(_: ()) -> ExternResult<ListResult<EntryHash>> {
+    let cell_id = sensemaker_cell_id()?;
+    // a closure, rather than a block, so that `?` only abandons the one annotation
+    let reconcile_annotation = |annotation_eh: EntryHash| -> ExternResult<bool> {
+        if get_sm_data(annotation_eh.clone())?.is_some() {
+            return Ok(false);
+        }
+        let payload = (ANNOTATIONZ_PATH.to_string(), annotation_eh);
+        remote_initialize_sm_data(cell_id.clone(), None, payload).map_err(sensemaker_error)?;
+        Ok(true)
+    };
 
-#[hdk_extern]
-/// set the sm_comp state for the path_string to the `rep_lang` interpretation of `expr_str`
-pub fn set_sm_comp((path_string, expr_str): (String, String)) -> ExternResult<bool> {
-    set_sensemaker_entry(path_string, SM_COMP_TAG.into(), expr_str)
-}
-
-fn set_sensemaker_entry(
-    path_string: String,
-    link_tag_string: String,
-    expr_str: String,
-) -> ExternResult<bool> {
-    let cell_id = get_sensemaker_cell_id(())?;
-    remote_set_sensemaker_entry_parse_rl_expr(
-        cell_id,
-        None,
-        (path_string, link_tag_string, expr_str),
-    )?;
-    Ok(true)
+    let mut repaired: Vec<EntryHash> = Vec::new();
+    let mut errors: Vec<(EntryHash, String)> = Vec::new();
+    for lnk in ANNOTATION_BUCKETS.get_links()? {
+        let annotation_eh = lnk.target.into_entry_hash().expect("should be an Entry.");
+        if repaired.contains(&annotation_eh) {
+            continue;
+        }
+        match reconcile_annotation(annotation_eh.clone()) {
+            Ok(true) => repaired.push(annotation_eh),
+            Ok(false) => {}
+            Err(err) => {
+                debug!("err in reconciling annotation: {}", err);
+                errors.push((annotation_eh, err.to_string()));
+            }
+        }
+    }
+    Ok(ListResult {
+        items: repaired,
+        errors,
+    })
 }
 
 #[hdk_extern]
-fn step_sm_remote((path_string, entry_hash, act): (String, EntryHash, String)) -> ExternResult<()> {
-    let cell_id = get_sensemaker_cell_id(())?;
-    remote_step_sm(cell_id, None, (path_string, entry_hash, act))
+fn init_agent_sm_data(payload: (String, String)) -> ExternResult<()> {
+    let cell_id = sensemaker_cell_id()?;
+    remote_initialize_sm_data_path(cell_id, None, payload).map_err(sensemaker_error)
 }
 
-// TODO figure out how to automate / streamline all these high-indirection methods
 #[hdk_extern]
-fn step_sm_path_remote(payload: (String, String, String)) -> ExternResult<()> {
-    let cell_id = get_sensemaker_cell_id(())?;
-    remote_step_sm_path(cell_id, None, payload)
+fn get_sm_data(target_eh: EntryHash) -> ExternResult<Option<(EntryHash, SensemakerEntry)>> {
+    let path_string = compose_entry_hash_path(&ANNOTATIONZ_PATH.into(), target_eh);
+    get_sm_generic(path_string, SM_DATA_TAG.to_string())
 }
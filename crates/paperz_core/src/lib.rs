@@ -2,7 +2,14 @@ pub mod types;
 
 pub const PAPER_TAG: &str = "paperz_paper";
 pub const ANN_TAG: &str = "annotationz";
+pub const CONTENT_HASH_TAG: &str = "paperz_content_hash";
+/// on a link from a paper to itself, followed by the `agent_b64` whose agent sensemaker state its
+/// upload stepped. see `AGENT_PATH`.
+pub const CREDITED_AGENT_TAG: &str = "paperz_credited_agent:";
 pub const ANNOTATIONZ_PATH: &str = "widget.paperz.annotationz";
 pub const AGENT_PATH: &str = "widget.agent.paperz";
 
 pub const PAPERZ_ZOME_NAME: &str = "paperz_main_zome";
+
+/// 16 MiB. entries much larger than this are slow to gossip.
+pub const DEFAULT_MAX_PAPER_BYTES: u64 = 16 * 1024 * 1024;
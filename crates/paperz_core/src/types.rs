@@ -1,5 +1,8 @@
 use hdk::prelude::*;
 
+use widget_core::config::SensemakerProperties;
+pub use widget_core::types::ListResult;
+
 #[hdk_entry]
 #[derive(Clone)]
 pub struct Paper {
@@ -9,6 +12,13 @@ pub struct Paper {
     pub blob_str: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum UploadPaperOutcome {
+    Uploaded(EntryHash, HeaderHash),
+    /// a paper with the same bytes was already uploaded. nothing was created.
+    AlreadyExists(EntryHash),
+}
+
 #[hdk_entry]
 pub struct Annotation {
     pub paper_ref: EntryHash, // this should probably be a HeaderHash
@@ -17,3 +27,40 @@ pub struct Annotation {
     pub what_it_says: String,
     pub what_it_should_say: String,
 }
+
+/// an agent's paperz sensemaker state as things are, next to the one a candidate sm_comp would give
+/// it. `None` if there is no state, or it isn't an integer.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SimulatedAgentScore {
+    /// as in the agent's sensemaker path. see `AGENT_PATH`.
+    pub agent_b64: String,
+    pub current: Option<i64>,
+    pub simulated: Option<i64>,
+}
+
+/// the paperz DNA properties. see `widget_core::config`.
+#[derive(Serialize, Deserialize, SerializedBytes, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PaperzConfig {
+    #[serde(flatten)]
+    pub sensemaker: SensemakerProperties,
+    /// the `rep_lang` expressions the UI starts out with for the agent path.
+    pub sm_init_expr: String,
+    pub sm_comp_expr: String,
+    /// the largest paper accepted, in (decoded) bytes.
+    pub max_paper_bytes: u64,
+    /// lowercase file extensions accepted for papers. empty means any.
+    pub allowed_extensions: Vec<String>,
+}
+
+impl Default for PaperzConfig {
+    fn default() -> Self {
+        Self {
+            sensemaker: SensemakerProperties::default(),
+            sm_init_expr: widget_core::config::STARTER_SM_INIT_EXPR.into(),
+            sm_comp_expr: widget_core::config::STARTER_SM_COMP_EXPR.into(),
+            max_paper_bytes: crate::DEFAULT_MAX_PAPER_BYTES,
+            allowed_extensions: vec!["pdf".into()],
+        }
+    }
+}
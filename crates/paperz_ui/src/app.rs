@@ -1,4 +1,7 @@
 use base64::encode;
+use js_sys::Uint8Array;
+use std::collections::BTreeMap;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlInputElement as InputElement;
 use weblog::{console_error, console_log};
@@ -13,28 +16,93 @@ use yew::{html::Scope, prelude::*};
 
 use holochain_client_wrapper::{
     agent_pk_to_vec_u8, AdminWebsocket, AdminWsCmd, AdminWsCmdResponse, AppWebsocket, AppWsCmd,
-    AppWsCmdResponse, CellId, DeserializeFromJsObj, EntryHashRaw, EntryHeaderHashPairRaw,
-    SerializeToJsObj,
+    AppWsCmdResponse, CellId, DeserializeFromJsObj, EntryHashRaw, SerializeToJsObj,
+};
+use paperz_core::{
+    types::{Paper, PaperzConfig, SimulatedAgentScore},
+    AGENT_PATH, PAPERZ_ZOME_NAME,
+};
+use widget_core::error::WidgetError;
+use widget_helpers::{
+    expr_builder::{ExprBuilder, SM_COMP_PARAMS},
+    file_upload::{FileBytes, FileUploadApp},
+    rep_lang_check::{check_sm_comp, check_sm_init, view_expr_error, ExprError},
+    score_preview::{view_score_preview, ScoreDiffRow, ScorePreview, SimulatedScores},
+    sensemaker_discovery::{
+        find_sensemaker_cells, get_sensemaker_cell_id, set_sensemaker_cell_id, SensemakerCandidate,
+    },
+    sm_config_history::{
+        get_sm_config_history, rollback_sm_expr, view_sm_config_history, SmConfigHistoryRaw,
+    },
+    sm_exprs::{view_sm_expr_note, SmExprRaw, SmExprsRaw},
+    sm_op_retry::SmOpRetryRaw,
+    sm_proposals::{approve_sm_proposal, get_sm_proposals, view_sm_proposals, SmProposalRaw},
 };
-use paperz_core::{types::Paper, AGENT_PATH, PAPERZ_ZOME_NAME};
-use widget_helpers::file_upload::{FileBytes, FileUploadApp};
 
 use crate::js_ser_de::*;
 
-// TODO get rid of this once we're using proper sensemaker app name
-const TEST_APP_NAME: &str = "test-app";
-
 pub enum Msg {
     AdminWs(WsMsg<AdminWsCmd, AdminWsCmdResponse>),
     AppWs(WsMsg<AppWsCmd, AppWsCmdResponse>),
     Log(String),
     Error(String),
     ZomeCallResponse(ZomeCallResponse),
+    Config(PaperzConfig),
     BrowserUploadedPaper(Paper),
     SensemakerPresent(bool),
+    /// the sensemaker cells found, and the one the paperz cell currently uses.
+    SensemakerDiscovered(Vec<SensemakerCandidate>, Option<CellId>),
+    /// point the paperz cell at `sensemaker_candidates[i]`.
+    SelectSensemaker(usize),
+    SensemakerCellIdSet(CellId),
+    ToggleSensemakerSettings,
     SmInitSubmit(String),
     SmCompSubmit(String),
+    /// simulate a candidate sm_comp, before it can be submitted.
+    SimulateSmComp(String),
+    SmCompSimulated(Result<ListResultRaw<SimulatedAgentScore>, String>),
+    ConfirmSmComp,
+    CancelSmCompPreview,
+    /// the expressions currently set on the DHT for `AGENT_PATH`.
+    SmExprs(SmExprsRaw),
+    SmInitSet(String),
+    SmCompSet(String),
+    /// a `set_sm_init` / `set_sm_comp` was proposed, rather than set, as it needs more approvals.
+    SmProposed,
+    SmSetFailed(String),
+    DismissSmSetError,
+    /// the changes proposed to `AGENT_PATH`'s expressions which haven't been set yet.
+    SmProposals(Result<Vec<SmProposalRaw>, String>),
+    /// approve `sm_proposals[i]`.
+    ApproveSmProposal(usize),
+    /// whether the approval got the proposal set.
+    SmProposalApproved(bool),
+    ToggleSmHistory,
+    /// the first page of the changes made to `AGENT_PATH`'s expressions, newest first.
+    SmHistory(Result<SmConfigHistoryRaw, String>),
+    /// fetch the next (older) page of the history.
+    LoadMoreSmHistory,
+    SmHistoryPage(Result<SmConfigHistoryRaw, String>),
+    /// roll back to `sm_history[i]`.
+    RollbackSm(usize),
+    /// whether the rollback was set, rather than proposed.
+    RolledBack(bool),
+    /// (input label, submitted expr, why it was rejected)
+    ExprInvalid(String, String, ExprError),
+    /// the expression submitted via the labelled input passed the checks.
+    ExprValid(String),
     SmDataInit,
+    RefreshPaperz,
+    /// one-time move of papers & annotations off the legacy single anchors.
+    MigrateAnchors,
+    DismissAlreadyExists,
+    DismissError,
+    /// reconcile sensemaker data if any annotations are missing it.
+    CheckSmData,
+    /// initialize the sensemaker data of any annotations which are missing it.
+    ReconcileSmData,
+    /// apply the sensemaker operations queued while the sensemaker was unreachable.
+    RetryPendingSmOps,
 }
 
 pub enum WsMsg<WSCMD, WSCMDRESP> {
@@ -43,8 +111,9 @@ pub enum WsMsg<WSCMD, WSCMDRESP> {
 }
 
 pub enum ZomeCallResponse {
-    Papers(Vec<(EntryHashRaw, Paper)>),
+    Papers(ListResultRaw<Pair<EntryHashRaw, Paper>>),
     UploadPaper(EntryHashRaw, Paper),
+    PaperAlreadyExists(EntryHashRaw),
 }
 
 pub struct Model {
@@ -52,15 +121,36 @@ pub struct Model {
     app_ws: AppWebsocket,
     paperz_cell_id: CellId,
     paperz: Vec<(EntryHashRaw, Paper)>,
+    /// papers which `get_all_paperz` couldn't load, with the reason.
+    paperz_errors: Vec<(EntryHashRaw, String)>,
     /// None means we don't know yet (no response). for `Some(b)`, `b == True` indicates presence.
     sensemaker_present: Option<bool>,
-    /// (sm_init_expr_string, sm_comp_expr_string)
-    paper_sm: (String, String),
+    /// the (sm_init, sm_comp) expressions, as in use on the DHT. the config's expressions are
+    /// shown for those which aren't set (or not known yet), or whose source isn't known.
+    paper_sm: SmExprsRaw,
+    /// why the last `set_sm_init` / `set_sm_comp` / `rollback_sm_expr` failed, until dismissed.
+    sm_set_error: Option<String>,
+    /// `None` while hidden. otherwise, `None` until loaded.
+    sm_history: Option<Option<SmConfigHistoryRaw>>,
+    sm_proposals: Vec<SmProposalRaw>,
+    /// input label -> (submitted expr, why it was rejected), for expressions which failed to check.
+    expr_errors: BTreeMap<String, (String, ExprError)>,
+    /// a candidate sm_comp, with what it would do to agents' scores.
+    sm_comp_preview: Option<ScorePreview<String>>,
+    /// the paperz DNA's properties. defaults until `get_config` responds.
+    config: PaperzConfig,
+    /// set when the last upload turned out to be a paper we already have.
+    already_exists: Option<EntryHashRaw>,
+    /// the most recent zome error which the user hasn't dismissed yet.
+    last_error: Option<WidgetError>,
+    /// set when the paperz cell doesn't know the sensemaker cell id yet.
+    sensemaker_cell_not_set: bool,
+    sensemaker_candidates: Vec<SensemakerCandidate>,
+    /// the sensemaker cell the paperz cell uses, if it has been set.
+    sensemaker_cell_id: Option<CellId>,
+    show_sensemaker_settings: bool,
 }
 
-const STARTER_SM_INIT_EXPR_STRING: &str = "0";
-const STARTER_SM_COMP_EXPR_STRING: &str = "+";
-
 #[derive(Properties, PartialEq)]
 pub struct ModelProps {
     pub admin_ws_js: JsValue,
@@ -77,14 +167,16 @@ impl Component for Model {
         let cell_id = CellId::deserialize_from_js_obj(props.cell_id_js.clone());
         let app_ws: AppWebsocket = props.app_ws_js.clone().into();
 
-        // get_all_paperz
+        ctx.link()
+            .send_future(get_all_paperz(app_ws.clone(), cell_id.clone()));
+
         let app_ws_ = app_ws.clone();
         let cell_id_ = cell_id.clone();
         ctx.link().send_future(async move {
             let cmd = AppWsCmd::CallZome {
                 cell_id: cell_id_.clone(),
                 zome_name: PAPERZ_ZOME_NAME.into(),
-                fn_name: "get_all_paperz".into(),
+                fn_name: "get_config".into(),
                 payload: JsValue::NULL,
                 provenance: cell_id_.1.clone(),
                 cap: "".into(),
@@ -92,12 +184,7 @@ impl Component for Model {
             let resp = app_ws_.call(cmd).await;
             match resp {
                 Ok(AppWsCmdResponse::CallZome(val)) => {
-                    Msg::ZomeCallResponse(ZomeCallResponse::Papers(
-                        PaperEhVec::deserialize_from_js_obj_(val)
-                            .into_iter()
-                            .map(|pair| pair.into())
-                            .collect(),
-                    ))
+                    Msg::Config(PaperzConfig::deserialize_from_js_obj_(val))
                 }
                 Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
                 Err(err) => Msg::Error(format!("err: {:?}", err)),
@@ -105,22 +192,29 @@ impl Component for Model {
         });
 
         // state machine setup
-        let paper_sm: (String, String) = (
-            STARTER_SM_INIT_EXPR_STRING.into(),
-            STARTER_SM_COMP_EXPR_STRING.into(),
-        );
+        ctx.link()
+            .send_future(get_sm_exprs(app_ws.clone(), cell_id.clone()));
+        let app_ws_ = app_ws.clone();
+        let cell_id_ = cell_id.clone();
+        ctx.link().send_future(async move {
+            Msg::SmProposals(
+                get_sm_proposals(&app_ws_, &cell_id_, PAPERZ_ZOME_NAME, AGENT_PATH.into()).await,
+            )
+        });
 
         let admin_ws: AdminWebsocket = props.admin_ws_js.clone().into();
         let admin_ws_ = admin_ws.clone();
+        let app_ws_ = app_ws.clone();
+        let cell_id_ = cell_id.clone();
         ctx.link().send_future(async move {
-            let resp = admin_ws_.call(AdminWsCmd::ListActiveApps).await;
-            match resp {
-                Ok(AdminWsCmdResponse::ListActiveApps(active_apps)) => {
-                    let sensemaker_present = active_apps.contains(&TEST_APP_NAME.into());
-                    Msg::SensemakerPresent(sensemaker_present)
-                }
-                Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
-                Err(err) => Msg::Error(format!("err: {:?}", err)),
+            let current = match get_sensemaker_cell_id(&app_ws_, &cell_id_, PAPERZ_ZOME_NAME).await
+            {
+                Ok(current) => current,
+                Err(err) => return Msg::Error(err),
+            };
+            match find_sensemaker_cells(&admin_ws_, &app_ws_, &cell_id_, current.as_ref()).await {
+                Ok(candidates) => Msg::SensemakerDiscovered(candidates, current),
+                Err(err) => Msg::Error(err),
             }
         });
         Self {
@@ -128,8 +222,21 @@ impl Component for Model {
             app_ws,
             paperz_cell_id: cell_id.clone(),
             paperz: Vec::new(),
+            paperz_errors: Vec::new(),
             sensemaker_present: None,
-            paper_sm,
+            paper_sm: SmExprsRaw::default(),
+            sm_set_error: None,
+            sm_history: None,
+            sm_proposals: Vec::new(),
+            expr_errors: BTreeMap::new(),
+            sm_comp_preview: None,
+            config: PaperzConfig::default(),
+            already_exists: None,
+            last_error: None,
+            sensemaker_cell_not_set: false,
+            sensemaker_candidates: Vec::new(),
+            sensemaker_cell_id: None,
+            show_sensemaker_settings: false,
         }
     }
 
@@ -181,7 +288,19 @@ impl Component for Model {
 
             Msg::Error(err) => {
                 console_error!("Error: {}", err);
-                false
+                match WidgetError::decode(&err) {
+                    Some(WidgetError::SensemakerMissing(_)) => {
+                        self.sensemaker_present = Some(false);
+                    }
+                    Some(WidgetError::SensemakerCellNotSet) => {
+                        self.sensemaker_cell_not_set = true;
+                    }
+                    Some(widget_err) => {
+                        self.last_error = Some(widget_err);
+                    }
+                    None => {}
+                }
+                true
             }
 
             Msg::Log(err) => {
@@ -189,12 +308,36 @@ impl Component for Model {
                 false
             }
 
-            Msg::ZomeCallResponse(ZomeCallResponse::Papers(paper_vec)) => {
-                self.paperz = paper_vec;
+            Msg::ZomeCallResponse(ZomeCallResponse::Papers(list_result)) => {
+                for (_eh, reason) in &list_result.errors {
+                    console_error!(format!(
+                        "get_all_paperz: could not load a paper: {}",
+                        reason
+                    ));
+                }
+                self.paperz = list_result
+                    .items
+                    .into_iter()
+                    .map(|pair| pair.into())
+                    .collect();
+                self.paperz_errors = list_result.errors;
                 console_log!("got paper_vec");
                 true
             }
 
+            Msg::Config(config) => {
+                self.config = config;
+                true
+            }
+
+            Msg::RefreshPaperz => {
+                ctx.link().send_future(get_all_paperz(
+                    self.app_ws.clone(),
+                    self.paperz_cell_id.clone(),
+                ));
+                false
+            }
+
             Msg::ZomeCallResponse(ZomeCallResponse::UploadPaper(paper_eh, paper)) => {
                 self.paperz.push((paper_eh, paper));
                 true
@@ -215,9 +358,16 @@ impl Component for Model {
                     let resp = ws.call(cmd).await;
                     match resp {
                         Ok(AppWsCmdResponse::CallZome(val)) => {
-                            let (paper_eh, _paper_hh) =
-                                EntryHeaderHashPairRaw::deserialize_from_js_obj_(val);
-                            Msg::ZomeCallResponse(ZomeCallResponse::UploadPaper(paper_eh, paper))
+                            match UploadPaperOutcomeRaw::deserialize_from_js_obj_(val) {
+                                UploadPaperOutcomeRaw::Uploaded(paper_eh) => Msg::ZomeCallResponse(
+                                    ZomeCallResponse::UploadPaper(paper_eh, paper),
+                                ),
+                                UploadPaperOutcomeRaw::AlreadyExists(paper_eh) => {
+                                    Msg::ZomeCallResponse(ZomeCallResponse::PaperAlreadyExists(
+                                        paper_eh,
+                                    ))
+                                }
+                            }
                         }
                         Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
                         Err(err) => Msg::Error(format!("err: {:?}", err)),
@@ -226,25 +376,484 @@ impl Component for Model {
                 true
             }
 
+            Msg::MigrateAnchors => {
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.paperz_cell_id.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: PAPERZ_ZOME_NAME.into(),
+                        fn_name: "migrate_anchors".into(),
+                        payload: JsValue::NULL,
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        Ok(AppWsCmdResponse::CallZome(val)) => {
+                            Msg::Log(format!("migrate_anchors: {:?}", val))
+                        }
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Msg::Error(format!("err: {:?}", err)),
+                    }
+                });
+                false
+            }
+
+            Msg::CheckSmData => {
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.paperz_cell_id.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: PAPERZ_ZOME_NAME.into(),
+                        fn_name: "has_missing_sm_data".into(),
+                        payload: JsValue::NULL,
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        Ok(AppWsCmdResponse::CallZome(val)) => match val.as_bool() {
+                            Some(true) => Msg::ReconcileSmData,
+                            _ => Msg::Log(format!("has_missing_sm_data: {:?}", val)),
+                        },
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Msg::Error(format!("err: {:?}", err)),
+                    }
+                });
+                false
+            }
+
+            Msg::ReconcileSmData => {
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.paperz_cell_id.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: PAPERZ_ZOME_NAME.into(),
+                        fn_name: "reconcile_sm_data".into(),
+                        payload: JsValue::NULL,
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        Ok(AppWsCmdResponse::CallZome(val)) => {
+                            Msg::Log(format!("reconcile_sm_data: {:?}", val))
+                        }
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Msg::Error(format!("err: {:?}", err)),
+                    }
+                });
+                false
+            }
+
+            Msg::RetryPendingSmOps => {
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.paperz_cell_id.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: PAPERZ_ZOME_NAME.into(),
+                        fn_name: "retry_pending_sm_ops".into(),
+                        payload: JsValue::NULL,
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    match resp {
+                        Ok(AppWsCmdResponse::CallZome(val)) => {
+                            let retry = SmOpRetryRaw::deserialize_from_js_obj(val);
+                            if retry.needs_another_retry() {
+                                Msg::RetryPendingSmOps
+                            } else {
+                                Msg::Log(format!(
+                                    "retry_pending_sm_ops: applied {}, errors: {:?}",
+                                    retry.applied.len(),
+                                    retry.errors
+                                ))
+                            }
+                        }
+                        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Msg::Error(format!("err: {:?}", err)),
+                    }
+                });
+                false
+            }
+
+            Msg::ZomeCallResponse(ZomeCallResponse::PaperAlreadyExists(paper_eh)) => {
+                self.already_exists = Some(paper_eh);
+                true
+            }
+
+            Msg::DismissAlreadyExists => {
+                self.already_exists = None;
+                true
+            }
+
+            Msg::DismissError => {
+                self.last_error = None;
+                true
+            }
+
             Msg::SensemakerPresent(sensemaker_present) => {
                 self.sensemaker_present = Some(sensemaker_present);
+                // this UI doesn't list annotations, so it can't spot the ones missing sensemaker
+                // data. instead, ask the zome whether an earlier absence of the sensemaker left
+                // any behind.
+                if sensemaker_present {
+                    ctx.link().send_message(Msg::RetryPendingSmOps);
+                    ctx.link().send_message(Msg::CheckSmData);
+                }
                 true
             }
 
-            Msg::SmInitSubmit(expr_str) => {
-                self.set_sm(ctx.link(), expr_str.clone(), "set_sm_init".into());
-                // TODO ideally we would wait for confirmation before setting this
-                self.paper_sm.0 = expr_str;
+            Msg::SensemakerDiscovered(candidates, current) => {
+                let current_found = current.as_ref().map_or(false, |cell_id| {
+                    candidates.iter().any(|c| c.cell_id == *cell_id)
+                });
+                let current_set = current.is_some();
+                self.sensemaker_candidates = candidates;
+                self.sensemaker_cell_id = current;
+                if current_found {
+                    ctx.link().send_message(Msg::SensemakerPresent(true));
+                } else if current_set {
+                    // the chosen sensemaker isn't running. that's for the user to sort out, not to
+                    // be papered over by picking another.
+                    ctx.link().send_message(Msg::SensemakerPresent(false));
+                    self.show_sensemaker_settings = true;
+                } else if let Some(i) = self
+                    .sensemaker_candidates
+                    .iter()
+                    .position(|candidate| candidate.bundled)
+                {
+                    // the happ carries its own sensemaker, but was packed without the
+                    // `sensemaker_dna_hash` property, so the zome can't find it by itself and has
+                    // to store it. when it can, the bundled cell is `current`, and nothing is
+                    // stored.
+                    ctx.link().send_message(Msg::SelectSensemaker(i));
+                } else if self.sensemaker_candidates.len() == 1 {
+                    // there's nothing to choose between
+                    ctx.link().send_message(Msg::SelectSensemaker(0));
+                } else if self.sensemaker_candidates.is_empty() {
+                    ctx.link().send_message(Msg::SensemakerPresent(false));
+                } else {
+                    self.show_sensemaker_settings = true;
+                }
                 true
             }
 
+            Msg::SelectSensemaker(i) => {
+                if let Some(candidate) = self.sensemaker_candidates.get(i).cloned() {
+                    let app_ws_ = self.app_ws.clone();
+                    let cell_id_ = self.paperz_cell_id.clone();
+                    ctx.link().send_future(async move {
+                        let sensemaker_cell_id = candidate.cell_id;
+                        match set_sensemaker_cell_id(
+                            &app_ws_,
+                            &cell_id_,
+                            PAPERZ_ZOME_NAME,
+                            sensemaker_cell_id.clone(),
+                        )
+                        .await
+                        {
+                            Ok(()) => Msg::SensemakerCellIdSet(sensemaker_cell_id),
+                            Err(err) => Msg::Error(err),
+                        }
+                    });
+                }
+                false
+            }
+
+            Msg::SensemakerCellIdSet(sensemaker_cell_id) => {
+                self.sensemaker_cell_id = Some(sensemaker_cell_id);
+                self.sensemaker_cell_not_set = false;
+                self.show_sensemaker_settings = false;
+                ctx.link().send_message(Msg::SensemakerPresent(true));
+                true
+            }
+
+            Msg::ToggleSensemakerSettings => {
+                self.show_sensemaker_settings = !self.show_sensemaker_settings;
+                true
+            }
+
+            Msg::SmInitSubmit(expr_str) => {
+                self.set_sm(ctx.link(), expr_str, "set_sm_init".into(), Msg::SmInitSet);
+                false
+            }
+
             Msg::SmCompSubmit(expr_str) => {
-                self.set_sm(ctx.link(), expr_str.clone(), "set_sm_comp".into());
-                // TODO ideally we would wait for confirmation before setting this
-                self.paper_sm.1 = expr_str;
+                self.set_sm(ctx.link(), expr_str, "set_sm_comp".into(), Msg::SmCompSet);
+                false
+            }
+
+            Msg::SimulateSmComp(expr_str) => {
+                self.sm_comp_preview = Some(ScorePreview::new(expr_str.clone()));
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.paperz_cell_id.clone();
+                ctx.link().send_future(async move {
+                    let cmd = AppWsCmd::CallZome {
+                        cell_id: cell_id_.clone(),
+                        zome_name: PAPERZ_ZOME_NAME.into(),
+                        fn_name: "simulate_agent_scores".into(),
+                        payload: expr_str.serialize_to_js_obj(),
+                        provenance: cell_id_.1.clone(),
+                        cap: "".into(),
+                    };
+                    let resp = app_ws_.call(cmd).await;
+                    Msg::SmCompSimulated(match resp {
+                        Ok(AppWsCmdResponse::CallZome(val)) => {
+                            Ok(ListResultRaw::<SimulatedAgentScore>::deserialize_from_js_obj_(val))
+                        }
+                        Ok(resp) => Err(format!("impossible: invalid response: {:?}", resp)),
+                        Err(err) => Err(format!("{:?}", err)),
+                    })
+                });
+                true
+            }
+
+            Msg::SmCompSimulated(result) => {
+                let own_agent_b64 =
+                    base64::encode(agent_pk_to_vec_u8(self.paperz_cell_id.1.clone()));
+                let preview = match self.sm_comp_preview.as_mut() {
+                    // cancelled in the meantime
+                    None => return false,
+                    Some(preview) => preview,
+                };
+                preview.result = Some(result.map(|list_result| {
+                    SimulatedScores {
+                        failed: list_result.errors.len(),
+                        rows: list_result
+                            .items
+                            .into_iter()
+                            .map(|score| ScoreDiffRow {
+                                label: agent_label(&score.agent_b64, &own_agent_b64),
+                                current: score.current,
+                                simulated: score.simulated,
+                            })
+                            .collect(),
+                    }
+                }));
+                true
+            }
+
+            Msg::ConfirmSmComp => {
+                if let Some(preview) = self.sm_comp_preview.take() {
+                    if let Some(Ok(_)) = preview.result {
+                        ctx.link().send_message(Msg::SmCompSubmit(preview.change));
+                    }
+                }
+                true
+            }
+
+            Msg::CancelSmCompPreview => {
+                self.sm_comp_preview = None;
+                true
+            }
+
+            Msg::SmExprs(sm_exprs) => {
+                self.paper_sm = sm_exprs;
+                true
+            }
+
+            Msg::SmInitSet(expr_str) => {
+                self.paper_sm.0 = SmExprRaw::Source(expr_str);
+                self.sm_set_error = None;
+                self.refresh_sm_history(ctx.link());
+                true
+            }
+
+            Msg::SmCompSet(expr_str) => {
+                self.paper_sm.1 = SmExprRaw::Source(expr_str);
+                self.sm_set_error = None;
+                self.refresh_sm_history(ctx.link());
+                true
+            }
+
+            Msg::SmProposed => {
+                self.sm_set_error = None;
+                self.refresh_sm_proposals(ctx.link());
+                true
+            }
+
+            Msg::SmProposals(result) => match result {
+                Ok(proposals) => {
+                    self.sm_proposals = proposals;
+                    true
+                }
+                Err(err) => {
+                    console_error!("get_sm_proposals failed: {}", err);
+                    false
+                }
+            },
+
+            Msg::ApproveSmProposal(i) => {
+                let proposal = match self.sm_proposals.get(i) {
+                    None => return false,
+                    Some(proposal) => proposal.clone(),
+                };
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.paperz_cell_id.clone();
+                ctx.link().send_future(async move {
+                    match approve_sm_proposal(
+                        &app_ws_,
+                        &cell_id_,
+                        PAPERZ_ZOME_NAME,
+                        AGENT_PATH.into(),
+                        proposal,
+                    )
+                    .await
+                    {
+                        Ok(set) => Msg::SmProposalApproved(set),
+                        Err(err) => Msg::SmSetFailed(format!("approve_sm_proposal: {}", err)),
+                    }
+                });
+                false
+            }
+
+            Msg::SmProposalApproved(set) => {
+                self.sm_set_error = None;
+                self.refresh_sm_proposals(ctx.link());
+                if set {
+                    ctx.link().send_future(get_sm_exprs(
+                        self.app_ws.clone(),
+                        self.paperz_cell_id.clone(),
+                    ));
+                    self.refresh_sm_history(ctx.link());
+                }
+                true
+            }
+
+            Msg::SmSetFailed(err) => {
+                console_error!("set_sm failed: {}", err.clone());
+                self.sm_set_error = Some(match WidgetError::decode(&err) {
+                    Some(widget_err) => widget_err.to_string(),
+                    None => err,
+                });
+                true
+            }
+
+            Msg::DismissSmSetError => {
+                self.sm_set_error = None;
                 true
             }
 
+            Msg::ToggleSmHistory => {
+                if self.sm_history.is_some() {
+                    self.sm_history = None;
+                } else {
+                    self.sm_history = Some(None);
+                    self.refresh_sm_history(ctx.link());
+                }
+                true
+            }
+
+            Msg::SmHistory(result) => match result {
+                Ok(history) => {
+                    // hidden in the meantime
+                    if self.sm_history.is_none() {
+                        return false;
+                    }
+                    self.sm_history = Some(Some(history));
+                    true
+                }
+                Err(err) => {
+                    console_error!("get_sm_config_history failed: {}", err);
+                    self.sm_history = None;
+                    true
+                }
+            },
+
+            Msg::LoadMoreSmHistory => {
+                let after = match self
+                    .sm_history
+                    .as_ref()
+                    .and_then(|h| h.as_ref()?.next_after.clone())
+                {
+                    None => return false,
+                    Some(after) => after,
+                };
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.paperz_cell_id.clone();
+                ctx.link().send_future(async move {
+                    Msg::SmHistoryPage(
+                        get_sm_config_history(
+                            &app_ws_,
+                            &cell_id_,
+                            PAPERZ_ZOME_NAME,
+                            AGENT_PATH.into(),
+                            Some(after),
+                        )
+                        .await,
+                    )
+                });
+                false
+            }
+
+            Msg::SmHistoryPage(result) => match (&mut self.sm_history, result) {
+                (Some(Some(history)), Ok(older)) => {
+                    history.append(older);
+                    true
+                }
+                // hidden in the meantime
+                (_, Ok(_)) => false,
+                (_, Err(err)) => {
+                    console_error!("get_sm_config_history failed: {}", err);
+                    false
+                }
+            },
+
+            Msg::RollbackSm(i) => {
+                let change = match self
+                    .sm_history
+                    .as_ref()
+                    .and_then(|h| h.as_ref()?.changes.get(i))
+                {
+                    None => return false,
+                    Some(change) => change.clone(),
+                };
+                let app_ws_ = self.app_ws.clone();
+                let cell_id_ = self.paperz_cell_id.clone();
+                ctx.link().send_future(async move {
+                    match rollback_sm_expr(
+                        &app_ws_,
+                        &cell_id_,
+                        PAPERZ_ZOME_NAME,
+                        AGENT_PATH.into(),
+                        change,
+                    )
+                    .await
+                    {
+                        Ok(set) => Msg::RolledBack(set),
+                        Err(err) => Msg::SmSetFailed(format!("rollback_sm_expr: {}", err)),
+                    }
+                });
+                false
+            }
+
+            Msg::RolledBack(set) => {
+                if set {
+                    self.sm_set_error = None;
+                    ctx.link().send_future(get_sm_exprs(
+                        self.app_ws.clone(),
+                        self.paperz_cell_id.clone(),
+                    ));
+                    self.refresh_sm_history(ctx.link());
+                } else {
+                    ctx.link().send_message(Msg::SmProposed);
+                }
+                true
+            }
+
+            Msg::ExprInvalid(label, expr_str, err) => {
+                self.expr_errors.insert(label, (expr_str, err));
+                true
+            }
+
+            Msg::ExprValid(label) => self.expr_errors.remove(&label).is_some(),
+
             Msg::SmDataInit => {
                 let app_ws_ = self.app_ws.clone();
                 let cell_id_ = self.paperz_cell_id.clone();
@@ -288,9 +897,17 @@ impl Component for Model {
                 </div>
             },
         };
+        let sensemaker_cell_not_set_html = if self.sensemaker_cell_not_set {
+            html! {
+                <div class="alert">
+                  <h3>{"sensemaker cell id is not set!"}</h3>
+                  <p>{"`paperz` doesn't know which cell is the sensemaker yet. pick one in the sensemaker settings."}</p>
+                </div>
+            }
+        } else {
+            html! {}
+        };
         //
-        let sm_init_handler = |input: String| Ok(Msg::SmInitSubmit(input));
-        let sm_comp_handler = |input: String| Ok(Msg::SmCompSubmit(input));
         //
         let content_name = "paper";
         let on_file_upload: Callback<FileBytes> = {
@@ -306,70 +923,267 @@ impl Component for Model {
         let mk_paper_src = |paper: Paper| -> String {
             "data:application/pdf;base64,".to_string() + &paper.blob_str
         };
+        let paperz_errors_html = if self.paperz_errors.is_empty() {
+            html! {}
+        } else {
+            html! {
+                <div class="alert">
+                    <p>{ format!("{} paperz could not be loaded", self.paperz_errors.len()) }</p>
+                    <button onclick={ctx.link().callback(|_| Msg::RefreshPaperz)}>{ "retry" }</button>
+                </div>
+            }
+        };
+        let already_exists_html = match &self.already_exists {
+            None => html! {},
+            Some(paper_eh) => html! {
+                <div class="alert">
+                    <p>
+                        {"this paper has already been uploaded: "}
+                        <a href={format!("#{}", paper_anchor_id(paper_eh))}>{"go to it"}</a>
+                    </p>
+                    <button onclick={ctx.link().callback(|_| Msg::DismissAlreadyExists)}>{ "ok" }</button>
+                </div>
+            },
+        };
+        let sm_set_error_html = match &self.sm_set_error {
+            None => html! {},
+            Some(err) => html! {
+                <div class="alert">
+                    <h3>{"the sensemaker expression was not set"}</h3>
+                    <p>{err}</p>
+                    <button onclick={ctx.link().callback(|_| Msg::DismissSmSetError)}>{ "dismiss" }</button>
+                </div>
+            },
+        };
+        let sm_init_expr = self
+            .paper_sm
+            .0
+            .source()
+            .map_or_else(|| self.config.sm_init_expr.clone(), str::to_string);
+        let sm_comp_expr = self
+            .paper_sm
+            .1
+            .source()
+            .map_or_else(|| self.config.sm_comp_expr.clone(), str::to_string);
+        // a new sm_comp has to fit the sm_init which is currently set. if that one's source isn't
+        // known, the sm_comp can only be checked by itself.
+        let sm_init_expr_ = match self.paper_sm.0 {
+            SmExprRaw::SetOutsideWidget => None,
+            _ => Some(sm_init_expr.clone()),
+        };
+        let sm_comp_check = move |input: &str| match &sm_init_expr_ {
+            Some(sm_init_expr) => check_sm_comp(sm_init_expr, input),
+            None => check_sm_init(input),
+        };
+        let sm_comp_params: Vec<String> = SM_COMP_PARAMS.iter().map(|p| p.to_string()).collect();
+        let sm_comp_preview_html = match &self.sm_comp_preview {
+            None => html! {},
+            Some(preview) => view_score_preview(
+                preview,
+                format!("agent scores with sm_comp `{}`", preview.change),
+                ctx.link().callback(|_| Msg::ConfirmSmComp),
+                ctx.link().callback(|_| Msg::CancelSmCompPreview),
+            ),
+        };
+        let sm_history_html = match &self.sm_history {
+            None => html! {},
+            Some(None) => html! { <p>{"loading the sensemaker history…"}</p> },
+            Some(Some(history)) => view_sm_config_history(
+                history,
+                &base64::encode(agent_pk_to_vec_u8(self.paperz_cell_id.1.clone())),
+                ctx.link().callback(Msg::RollbackSm),
+                ctx.link().callback(|_| Msg::LoadMoreSmHistory),
+            ),
+        };
+        let sm_proposals_html = view_sm_proposals(
+            &self.sm_proposals,
+            &base64::encode(agent_pk_to_vec_u8(self.paperz_cell_id.1.clone())),
+            ctx.link().callback(Msg::ApproveSmProposal),
+        );
+        let last_error_html = match &self.last_error {
+            None => html! {},
+            Some(err) => html! {
+                <div class="alert">
+                    <p>{err.to_string()}</p>
+                    <button onclick={ctx.link().callback(|_| Msg::DismissError)}>{ "dismiss" }</button>
+                </div>
+            },
+        };
 
         html! {
             <div>
                 <p>{"hello, paperz 👋"}</p>
                 <br/>
                 {sensemaker_present_html}
+                {sensemaker_cell_not_set_html}
+                {last_error_html}
+                <button onclick={ctx.link().callback(|_| Msg::ToggleSensemakerSettings)}>{ "sensemaker settings" }</button>
+                { self.view_sensemaker_settings(ctx.link()) }
                 <br/>
-                { self.view_string_input(ctx.link(), sm_init_handler, "sm_init".into(), "paperz/agent sm_init".into(), self.paper_sm.0.clone()) }
+                {sm_set_error_html}
+                { self.view_expr_input(ctx.link(), check_sm_init, Msg::SmInitSubmit, "sm_init".into(), "paperz/agent sm_init".into(), sm_init_expr, Vec::new()) }
+                { view_sm_expr_note(&self.paper_sm.0, "paperz/agent sm_init") }
                 <br/>
-                { self.view_string_input(ctx.link(), sm_comp_handler, "sm_comp".into(), "paperz/agent sm_comp".into(), self.paper_sm.1.clone()) }
+                { self.view_expr_input(ctx.link(), sm_comp_check, Msg::SimulateSmComp, "sm_comp".into(), "paperz/agent sm_comp".into(), sm_comp_expr, sm_comp_params) }
+                { view_sm_expr_note(&self.paper_sm.1, "paperz/agent sm_comp") }
+                {sm_comp_preview_html}
+                {sm_proposals_html}
+                <button onclick={ctx.link().callback(|_| Msg::ToggleSmHistory)}>{ "sensemaker history" }</button>
+                {sm_history_html}
                 <br/>
                 <button onclick={ctx.link().callback(move |_| Msg::SmDataInit)}>{ "initialize_sm_data" }</button>
+                <button onclick={ctx.link().callback(move |_| Msg::MigrateAnchors)}>{ "migrate_anchors" }</button>
+                <button onclick={ctx.link().callback(move |_| Msg::ReconcileSmData)}>{ "reconcile_sm_data" }</button>
                 <br/>
-                <FileUploadApp {content_name} {on_file_upload} />
+                <FileUploadApp
+                    {content_name}
+                    {on_file_upload}
+                    allowed_extensions={self.config.allowed_extensions.clone()}
+                    max_bytes={Some(self.config.max_paper_bytes)}
+                />
+                {already_exists_html}
                 <br/>
                 <h3 class="subtitle">{"paperz"}</h3>
-                { for self.paperz.iter().map(|pair| html!{ <iframe src={mk_paper_src(pair.1.clone())} width="100%" height="500px" /> }) }
+                {paperz_errors_html}
+                { for self.paperz.iter().map(|pair| html!{
+                    <div id={paper_anchor_id(&pair.0)}>
+                        <iframe src={mk_paper_src(pair.1.clone())} width="100%" height="500px" />
+                    </div>
+                }) }
             </div>
         }
     }
 }
 
 impl Model {
-    fn view_string_input<F>(
+    fn view_sensemaker_settings(&self, link: &Scope<Self>) -> Html {
+        if !self.show_sensemaker_settings {
+            return html! {};
+        }
+        if self.sensemaker_candidates.is_empty() {
+            return html! {
+                <div class="sensemaker-settings">
+                    <p>{"no sensemaker cells were found."}</p>
+                </div>
+            };
+        }
+        html! {
+            <div class="sensemaker-settings">
+                <p>{"which sensemaker should `paperz` use?"}</p>
+                { for self.sensemaker_candidates.iter().enumerate().map(|(i, candidate)| {
+                    let in_use = self.sensemaker_cell_id.as_ref() == Some(&candidate.cell_id);
+                    html! {
+                        <button
+                            disabled={in_use}
+                            onclick={link.callback(move |_| Msg::SelectSensemaker(i))}
+                        >
+                            { if in_use { format!("{} (in use)", candidate.label()) } else { candidate.label() } }
+                        </button>
+                    }
+                }) }
+            </div>
+        }
+    }
+
+    /// the `rep_lang` expression is only submitted if it passes `check`. otherwise, why it didn't
+    /// is shown under the input, which keeps what was typed. `params` are the expression's
+    /// parameter names, for the block builder shown next to the input.
+    fn view_expr_input<C, F>(
         &self,
         link: &Scope<Self>,
+        check: C,
         f: F,
         class: String,
         label: String,
         value: String,
+        params: Vec<String>,
     ) -> Html
     where
-        F: Fn(String) -> Result<Msg, String> + 'static,
+        C: Fn(&str) -> Result<(), ExprError> + 'static,
+        F: Fn(String) -> Msg + 'static,
     {
+        let label_ = label.clone();
+        let submit = Rc::new(move |expr_str: String| match check(&expr_str) {
+            Ok(()) => Ok(vec![Msg::ExprValid(label_.clone()), f(expr_str)]),
+            Err(err) => Err(vec![Msg::ExprInvalid(label_.clone(), expr_str, err)]),
+        });
+        let submit_ = submit.clone();
         let onkeypress = link.batch_callback(move |e: KeyboardEvent| {
             if e.key() == "Enter" {
                 let input: InputElement = e.target_unchecked_into();
-                match f(input.value()) {
-                    Ok(msg) => {
+                match submit_(input.value()) {
+                    Ok(msgs) => {
                         input.set_value("");
-                        Some(msg)
-                    }
-                    Err(err) => {
-                        console_error!(format!("view_string_input: {}", err));
-                        None
+                        msgs
                     }
+                    Err(msgs) => msgs,
                 }
             } else {
-                None
+                vec![]
             }
         });
+        let on_builder_submit =
+            link.batch_callback(move |expr_str: String| match submit(expr_str) {
+                Ok(msgs) | Err(msgs) => msgs,
+            });
+        let expr_error_html = match self.expr_errors.get(&label) {
+            None => html! {},
+            Some((expr_str, err)) => view_expr_error(expr_str, err),
+        };
         html! {
             <div>
                 <label>{format!("{}: ", label)}</label>
                 <input
                     {class}
-                    {value}
+                    value={value.clone()}
                     {onkeypress}
                 />
+                <ExprBuilder {params} expr={value} on_submit={on_builder_submit} />
+                {expr_error_html}
             </div>
         }
     }
 
-    fn set_sm(&self, link: &Scope<Self>, expr_str: String, zome_fn: String) {
+    fn refresh_sm_proposals(&self, link: &Scope<Self>) {
+        let app_ws_ = self.app_ws.clone();
+        let cell_id_ = self.paperz_cell_id.clone();
+        link.send_future(async move {
+            Msg::SmProposals(
+                get_sm_proposals(&app_ws_, &cell_id_, PAPERZ_ZOME_NAME, AGENT_PATH.into()).await,
+            )
+        });
+    }
+
+    /// reload the history, if it is shown.
+    fn refresh_sm_history(&self, link: &Scope<Self>) {
+        if self.sm_history.is_none() {
+            return;
+        }
+        let app_ws_ = self.app_ws.clone();
+        let cell_id_ = self.paperz_cell_id.clone();
+        link.send_future(async move {
+            Msg::SmHistory(
+                get_sm_config_history(
+                    &app_ws_,
+                    &cell_id_,
+                    PAPERZ_ZOME_NAME,
+                    AGENT_PATH.into(),
+                    None,
+                )
+                .await,
+            )
+        });
+    }
+
+    /// sends `on_set(expr_str)` once the zome call succeeds, `Msg::SmProposed` if the change awaits
+    /// approval, or `Msg::SmSetFailed` if the call fails.
+    fn set_sm(
+        &self,
+        link: &Scope<Self>,
+        expr_str: String,
+        zome_fn: String,
+        on_set: fn(String) -> Msg,
+    ) {
         let app_ws_ = self.app_ws.clone();
         let cell_id_ = self.paperz_cell_id.clone();
         link.send_future(async move {
@@ -377,16 +1191,83 @@ impl Model {
                 cell_id: cell_id_.clone(),
                 zome_name: PAPERZ_ZOME_NAME.into(),
                 fn_name: zome_fn.clone(),
-                payload: (AGENT_PATH.to_string(), expr_str).serialize_to_js_obj(),
+                payload: (AGENT_PATH.to_string(), expr_str.clone()).serialize_to_js_obj(),
                 provenance: cell_id_.1.clone(),
                 cap: "".into(),
             };
             let resp = app_ws_.call(cmd).await;
             match resp {
-                Ok(AppWsCmdResponse::CallZome(val)) => Msg::Log(format!("{}: {:?}", zome_fn, val)),
-                Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
-                Err(err) => Msg::Error(format!("err: {:?}", err)),
+                Ok(AppWsCmdResponse::CallZome(val)) => {
+                    if val.as_bool() == Some(false) {
+                        Msg::SmProposed
+                    } else {
+                        on_set(expr_str)
+                    }
+                }
+                Ok(resp) => Msg::SmSetFailed(format!("impossible: invalid response: {:?}", resp)),
+                Err(err) => Msg::SmSetFailed(format!("{}: {:?}", zome_fn, err)),
             }
         });
     }
 }
+
+async fn get_all_paperz(app_ws: AppWebsocket, cell_id: CellId) -> Msg {
+    let cmd = AppWsCmd::CallZome {
+        cell_id: cell_id.clone(),
+        zome_name: PAPERZ_ZOME_NAME.into(),
+        fn_name: "get_all_paperz".into(),
+        payload: JsValue::NULL,
+        provenance: cell_id.1.clone(),
+        cap: "".into(),
+    };
+    let resp = app_ws.call(cmd).await;
+    match resp {
+        Ok(AppWsCmdResponse::CallZome(val)) => {
+            Msg::ZomeCallResponse(ZomeCallResponse::Papers(ListResultRaw::<
+                Pair<EntryHashRaw, Paper>,
+            >::deserialize_from_js_obj_(
+                val
+            )))
+        }
+        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => Msg::Error(format!("err: {:?}", err)),
+    }
+}
+
+async fn get_sm_exprs(app_ws: AppWebsocket, cell_id: CellId) -> Msg {
+    let cmd = AppWsCmd::CallZome {
+        cell_id: cell_id.clone(),
+        zome_name: PAPERZ_ZOME_NAME.into(),
+        fn_name: "get_sm_exprs".into(),
+        payload: JsValue::from_str(AGENT_PATH),
+        provenance: cell_id.1.clone(),
+        cap: "".into(),
+    };
+    let resp = app_ws.call(cmd).await;
+    match resp {
+        Ok(AppWsCmdResponse::CallZome(val)) => {
+            Msg::SmExprs(SmExprsRaw::deserialize_from_js_obj(val))
+        }
+        Ok(resp) => Msg::Error(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => Msg::Error(format!("err: {:?}", err)),
+    }
+}
+
+/// how an agent is shown in a score preview. the hash prefix is the same for every agent, so the
+/// end of the key is shown.
+fn agent_label(agent_b64: &str, own_agent_b64: &str) -> String {
+    if agent_b64 == own_agent_b64 {
+        return "you".into();
+    }
+    let start = agent_b64.len().saturating_sub(8);
+    format!("…{}", &agent_b64[start..])
+}
+
+/// the DOM id of a paper in the list, for linking to it.
+fn paper_anchor_id(paper_eh: &EntryHashRaw) -> String {
+    let bytes = Uint8Array::new(&paper_eh.clone().serialize_to_js_obj()).to_vec();
+    format!(
+        "paper-{}",
+        base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+    )
+}
@@ -1,8 +1,12 @@
 use js_sys::{Array, Object, Reflect};
 use wasm_bindgen::{prelude::*, JsCast};
 
-use holochain_client_wrapper::{DeserializeFromJsObj, EntryHashRaw, SerializeToJsObj};
-use paperz_core::types::Paper;
+use holochain_client_wrapper::{
+    DeserializeFromJsObj, EntryHashRaw, EntryHeaderHashPairRaw, SerializeToJsObj,
+};
+use paperz_core::types::{Paper, PaperzConfig, SimulatedAgentScore};
+use widget_core::config::SensemakerProperties;
+pub use widget_helpers::list_result::ListResultRaw;
 
 pub struct Pair<A, B>(pub A, pub B);
 
@@ -13,7 +17,11 @@ impl<A, B> Into<(A, B)> for Pair<A, B> {
     }
 }
 
-pub type PaperEhVec = Vec<Pair<EntryHashRaw, Paper>>;
+/// the UI-side mirror of `paperz_core::types::UploadPaperOutcome`.
+pub enum UploadPaperOutcomeRaw {
+    Uploaded(EntryHashRaw),
+    AlreadyExists(EntryHashRaw),
+}
 
 pub trait SerializeToJsObj_ {
     fn serialize_to_js_obj_(self) -> JsValue;
@@ -78,6 +86,12 @@ impl<A: DeserializeFromJsObj, B: DeserializeFromJsObj_> DeserializeFromJsObj_ fo
     }
 }
 
+impl<T: DeserializeFromJsObj_> DeserializeFromJsObj_ for ListResultRaw<T> {
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        ListResultRaw::deserialize_from_js_obj_with(v, T::deserialize_from_js_obj_)
+    }
+}
+
 impl<A: DeserializeFromJsObj, B: DeserializeFromJsObj> DeserializeFromJsObj_ for (A, B) {
     fn deserialize_from_js_obj_(v: JsValue) -> Self {
         let arr: Array = v.dyn_into().expect("Array conversion to succeed");
@@ -87,6 +101,53 @@ impl<A: DeserializeFromJsObj, B: DeserializeFromJsObj> DeserializeFromJsObj_ for
     }
 }
 
+impl DeserializeFromJsObj_ for String {
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        String::deserialize_from_js_obj(v)
+    }
+}
+
+impl DeserializeFromJsObj_ for PaperzConfig {
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        let field = |name: &str| {
+            Reflect::get(&v, &JsValue::from_str(name)).expect("object field get to succeed")
+        };
+        Self {
+            // flattened into the config by serde
+            sensemaker: SensemakerProperties {
+                sensemaker_dna_hash: field("sensemaker_dna_hash").as_string(),
+                progenitor: field("progenitor").as_string(),
+                sm_admins: Vec::<String>::deserialize_from_js_obj_(field("sm_admins")),
+                sm_approvals_required: field("sm_approvals_required")
+                    .as_f64()
+                    .expect("number conversion to succeed")
+                    as u32,
+            },
+            sm_init_expr: String::deserialize_from_js_obj(field("sm_init_expr")),
+            sm_comp_expr: String::deserialize_from_js_obj(field("sm_comp_expr")),
+            max_paper_bytes: field("max_paper_bytes")
+                .as_f64()
+                .expect("number conversion to succeed") as u64,
+            allowed_extensions: Vec::<String>::deserialize_from_js_obj_(field(
+                "allowed_extensions",
+            )),
+        }
+    }
+}
+
+impl DeserializeFromJsObj_ for SimulatedAgentScore {
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        let field = |name: &str| {
+            Reflect::get(&v, &JsValue::from_str(name)).expect("object field get to succeed")
+        };
+        Self {
+            agent_b64: String::deserialize_from_js_obj(field("agent_b64")),
+            current: field("current").as_f64().map(|x| x as i64),
+            simulated: field("simulated").as_f64().map(|x| x as i64),
+        }
+    }
+}
+
 impl DeserializeFromJsObj_ for Paper {
     fn deserialize_from_js_obj_(v: JsValue) -> Self {
         let filename = String::deserialize_from_js_obj(
@@ -98,3 +159,23 @@ impl DeserializeFromJsObj_ for Paper {
         Self { filename, blob_str }
     }
 }
+
+impl DeserializeFromJsObj_ for UploadPaperOutcomeRaw {
+    /// serde encodes enums as an object with a single key, the variant name.
+    fn deserialize_from_js_obj_(v: JsValue) -> Self {
+        let uploaded =
+            Reflect::get(&v, &JsValue::from_str("Uploaded")).expect("object field get to succeed");
+        if !uploaded.is_undefined() {
+            let (paper_eh, _paper_hh) = EntryHeaderHashPairRaw::deserialize_from_js_obj_(uploaded);
+            return UploadPaperOutcomeRaw::Uploaded(paper_eh);
+        }
+        let already_exists = Reflect::get(&v, &JsValue::from_str("AlreadyExists"))
+            .expect("object field get to succeed");
+        if !already_exists.is_undefined() {
+            return UploadPaperOutcomeRaw::AlreadyExists(EntryHashRaw::deserialize_from_js_obj(
+                already_exists,
+            ));
+        }
+        panic!("impossible: unknown UploadPaperOutcome variant")
+    }
+}
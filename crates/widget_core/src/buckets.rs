@@ -0,0 +1,186 @@
+use chrono::NaiveDateTime;
+use hdk::prelude::*;
+
+/// `YYYY-MM-DD`, so that buckets sort chronologically.
+pub fn day_bucket(timestamp: Timestamp) -> String {
+    let secs = timestamp.as_micros().div_euclid(1_000_000);
+    NaiveDateTime::from_timestamp(secs, 0)
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// items which hang off one anchor per UTC day (see `day_bucket`) rather than a single anchor, so
+/// that no anchor collects every item.
+///
+/// items linked before buckets existed hang off a single legacy anchor instead. those are read
+/// as part of the bucket of the day they were linked on, until `migrate` has emptied the legacy
+/// anchor.
+pub struct DayBuckets {
+    /// the anchor type of the buckets, whose anchor texts are the `day_bucket`s.
+    pub anchor_type: &'static str,
+    /// the anchor type of the legacy anchor, whose anchor text is empty.
+    pub legacy_anchor_type: &'static str,
+    /// the tag of the links to the items, from both the buckets & the legacy anchor.
+    pub tag: &'static str,
+}
+
+/// one page of items, newest first. see `DayBuckets::get_page`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// (entry hash of the item which failed to load, reason)
+    pub errors: Vec<(EntryHash, String)>,
+    /// where the next (older) page starts, `None` if this is the last one.
+    pub next_bucket: Option<String>,
+}
+
+/// the state of a `DayBuckets` at the time of one read, so that the legacy anchor is only
+/// fetched once.
+struct Snapshot {
+    /// the buckets which have an anchor.
+    anchored: Vec<String>,
+    /// every bucket which holds an item, newest first.
+    buckets: Vec<String>,
+    legacy_links: Vec<Link>,
+}
+
+impl DayBuckets {
+    pub fn bucket_anchor(&self, bucket: String) -> ExternResult<EntryHash> {
+        anchor(self.anchor_type.into(), bucket)
+    }
+
+    fn legacy_anchor(&self) -> ExternResult<EntryHash> {
+        anchor(self.legacy_anchor_type.into(), "".into())
+    }
+
+    fn link_tag(&self) -> LinkTag {
+        LinkTag::new(self.tag)
+    }
+
+    /// link `target` into the bucket for today.
+    pub fn link(&self, target: EntryHash) -> ExternResult<HeaderHash> {
+        create_link(
+            self.bucket_anchor(day_bucket(sys_time()?))?,
+            target,
+            LinkType(0),
+            self.link_tag(),
+        )
+    }
+
+    fn snapshot(&self) -> ExternResult<Snapshot> {
+        let anchored = list_anchor_tags(self.anchor_type.into())?;
+        let legacy_links = get_links(self.legacy_anchor()?, Some(self.link_tag()))?;
+        let mut buckets: Vec<String> = anchored
+            .iter()
+            .cloned()
+            .chain(legacy_links.iter().map(|lnk| day_bucket(lnk.timestamp)))
+            .collect();
+        buckets.sort_by(|a, b| b.cmp(a));
+        buckets.dedup();
+        Ok(Snapshot {
+            anchored,
+            buckets,
+            legacy_links,
+        })
+    }
+
+    /// every bucket which holds an item, newest first.
+    pub fn list_buckets(&self) -> ExternResult<Vec<String>> {
+        Ok(self.snapshot()?.buckets)
+    }
+
+    /// the links in `bucket`, including legacy links from that day, newest first.
+    fn bucket_links(&self, snapshot: &Snapshot, bucket: &str) -> ExternResult<Vec<Link>> {
+        let mut links = if snapshot.anchored.iter().any(|anchored| anchored == bucket) {
+            get_links(self.bucket_anchor(bucket.into())?, Some(self.link_tag()))?
+        } else {
+            Vec::new()
+        };
+        for lnk in &snapshot.legacy_links {
+            if day_bucket(lnk.timestamp) == bucket
+                && !links
+                    .iter()
+                    .any(|bucket_lnk| bucket_lnk.target == lnk.target)
+            {
+                links.push(lnk.clone());
+            }
+        }
+        links.sort_by_key(|lnk| std::cmp::Reverse(self.linked_micros(lnk)));
+        Ok(links)
+    }
+
+    /// the links to every item, newest first.
+    pub fn get_links(&self) -> ExternResult<Vec<Link>> {
+        let snapshot = self.snapshot()?;
+        let mut links = Vec::new();
+        for bucket in &snapshot.buckets {
+            links.extend(self.bucket_links(&snapshot, bucket)?);
+        }
+        Ok(links)
+    }
+
+    /// the links to at least `min_items` items (fewer only on the last page), newest first,
+    /// starting with the bucket `from_bucket`, or the newest one if that is `None`. whole buckets
+    /// are returned, so a page can hold more. also returns the bucket the next page starts with.
+    pub fn get_page(
+        &self,
+        from_bucket: Option<String>,
+        min_items: usize,
+    ) -> ExternResult<(Vec<Link>, Option<String>)> {
+        let snapshot = self.snapshot()?;
+        let mut buckets = snapshot
+            .buckets
+            .iter()
+            .filter(|bucket| match &from_bucket {
+                None => true,
+                Some(from_bucket) => *bucket <= from_bucket,
+            })
+            .peekable();
+        let mut links = Vec::new();
+        while let Some(bucket) = buckets.next() {
+            links.extend(self.bucket_links(&snapshot, bucket)?);
+            if links.len() >= min_items {
+                break;
+            }
+        }
+        Ok((links, buckets.peek().map(|bucket| bucket.to_string())))
+    }
+
+    /// when the item `lnk` points at was linked. links created by `migrate` carry the time of the
+    /// legacy link in their tag, as they are created later.
+    pub fn linked_micros(&self, lnk: &Link) -> i64 {
+        lnk.tag
+            .0
+            .get(self.tag.len() + 1..)
+            .and_then(|suffix| std::str::from_utf8(suffix).ok())
+            .and_then(|micros| micros.parse().ok())
+            .unwrap_or_else(|| lnk.timestamp.as_micros())
+    }
+
+    /// move every item on the legacy anchor into its day bucket, deleting the legacy link. items
+    /// which are already in a bucket are only unlinked from the legacy anchor, so this is safe to
+    /// run more than once. returns the number of items moved.
+    pub fn migrate(&self) -> ExternResult<u32> {
+        let legacy_links = get_links(self.legacy_anchor()?, Some(self.link_tag()))?;
+        let mut migrated = 0;
+        for lnk in legacy_links {
+            let bucket_anchor = self.bucket_anchor(day_bucket(lnk.timestamp))?;
+            let bucket_links = get_links(bucket_anchor.clone(), Some(self.link_tag()))?;
+            if !bucket_links
+                .iter()
+                .any(|bucket_lnk| bucket_lnk.target == lnk.target)
+            {
+                let tag = format!("{}:{}", self.tag, lnk.timestamp.as_micros());
+                create_link(
+                    bucket_anchor,
+                    lnk.target.clone(),
+                    LinkType(0),
+                    LinkTag::new(tag),
+                )?;
+                migrated += 1;
+            }
+            delete_link(lnk.create_link_hash)?;
+        }
+        Ok(migrated)
+    }
+}
@@ -0,0 +1,85 @@
+//! widget configs are read from the DNA properties. every field of one is optional in `dna.yaml`,
+//! falling back to its `Default`, so that a neighbourhood can fork its config without recompiling.
+
+use hdk::prelude::*;
+
+use crate::error::WidgetError;
+
+/// the sm_init expression widgets start out with.
+pub const STARTER_SM_INIT_EXPR: &str = "0";
+/// the sm_comp expression widgets start out with.
+pub const STARTER_SM_COMP_EXPR: &str = "+";
+
+/// the DNA properties every widget reads for its sensemaker. widgets embed these in their own
+/// config with `#[serde(flatten)]`, so that they sit alongside the widget's own properties.
+#[derive(Serialize, Deserialize, SerializedBytes, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SensemakerProperties {
+    /// the hash of the sensemaker DNA which the widget's happ bundles as its `sensemaker` role, if
+    /// it does.
+    pub sensemaker_dna_hash: Option<String>,
+    /// the agent (as an `AgentPubKeyB64` string) who installed the DNA for the neighbourhood, if
+    /// it is set. see `sm_admins`.
+    pub progenitor: Option<String>,
+    /// the agents (as `AgentPubKeyB64` strings) who may propose & approve changes to sm_init &
+    /// sm_comp expressions. empty means only the `progenitor` may, or anyone, if it isn't set
+    /// either, as in a standalone install.
+    pub sm_admins: Vec<String>,
+    /// how many approvals a proposed change needs before it is set. the proposer's counts.
+    pub sm_approvals_required: u32,
+}
+
+impl Default for SensemakerProperties {
+    fn default() -> Self {
+        Self {
+            sensemaker_dna_hash: None,
+            progenitor: None,
+            sm_admins: Vec::new(),
+            sm_approvals_required: 1,
+        }
+    }
+}
+
+/// msgpack's `nil`, which is what `properties: null` (or no `properties` at all) comes through as.
+const MSGPACK_NIL: u8 = 0xc0;
+
+/// whether `properties` are `null`, as opposed to malformed.
+pub fn is_null_properties(properties: &SerializedBytes) -> bool {
+    matches!(properties.bytes().as_slice(), [] | [MSGPACK_NIL])
+}
+
+/// the DNA properties as a `T`, or its `Default` if they are `null`. properties which don't
+/// deserialize are an `InvalidProperties` error, rather than quietly replaced by the default.
+pub fn dna_properties<T>() -> ExternResult<T>
+where
+    T: Default + TryFrom<SerializedBytes, Error = SerializedBytesError>,
+{
+    let properties = dna_info()?.properties;
+    if is_null_properties(&properties) {
+        return Ok(T::default());
+    }
+    T::try_from(properties).map_err(|err| WidgetError::InvalidProperties(err.to_string()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(bytes: Vec<u8>) -> SerializedBytes {
+        SerializedBytes::from(UnsafeBytes::from(bytes))
+    }
+
+    #[test]
+    fn null_properties_are_null() {
+        assert!(is_null_properties(&bytes(vec![])));
+        assert!(is_null_properties(&bytes(vec![MSGPACK_NIL])));
+    }
+
+    #[test]
+    fn malformed_properties_are_not_null() {
+        // a msgpack string, where a map is expected
+        assert!(!is_null_properties(&bytes(vec![0xa1, b'x'])));
+        // an empty msgpack map
+        assert!(!is_null_properties(&bytes(vec![0x80])));
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+
+use hdk::prelude::*;
+
+/// the `WasmError::Guest` strings produced by widget zome calls carry one of these, so that UIs
+/// can tell failures apart. see `encode` & `decode`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum WidgetError {
+    /// the sensemaker cell couldn't be called, most likely because it isn't installed or running.
+    SensemakerMissing(String),
+    /// `set_sensemaker_cell_id` hasn't been called yet.
+    SensemakerCellNotSet,
+    /// the sensemaker was reached, but the call failed.
+    SensemakerFailed(String),
+    EntryNotFound(String),
+    /// an item's sensemaker data was never initialized, e.g. as the sensemaker was missing when it
+    /// was created. the widget's `reconcile_sm_data` repairs this.
+    SmDataMissing(String),
+    ValidationFailed(String),
+    /// a `rep_lang` expression didn't parse, or doesn't have the type it is used at.
+    InvalidExpression(String),
+    NotAuthorized(String),
+    /// the DNA properties don't deserialize into the widget's config.
+    InvalidProperties(String),
+}
+
+const ENCODING_PREFIX: &str = "widget_error[";
+const ENCODING_SUFFIX: &str = "]";
+
+/// how errors of the host or of `ZomeCallResponse` read when the sensemaker couldn't be called at
+/// all, as opposed to it failing the call.
+const SENSEMAKER_MISSING_MARKERS: [&str; 6] = [
+    "CallError",
+    "CellMissing",
+    "ZomeNotExists",
+    "ZomeFnNotExists",
+    "NetworkError",
+    "Unauthorized",
+];
+
+impl WidgetError {
+    /// `widget_error[..]`, around the JSON of `self` in URL-safe base64.
+    ///
+    /// by the time the error reaches a UI, it has been wrapped (and `Debug`-escaped) a few times
+    /// over. base64 has nothing which escaping would change, so `decode` gets the error back as is.
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("WidgetError serializes to JSON");
+        format!(
+            "{}{}{}",
+            ENCODING_PREFIX,
+            base64::encode_config(json, base64::URL_SAFE_NO_PAD),
+            ENCODING_SUFFIX
+        )
+    }
+
+    /// find & decode an `encode`d error anywhere within `s`.
+    pub fn decode(s: &str) -> Option<Self> {
+        let start = s.find(ENCODING_PREFIX)? + ENCODING_PREFIX.len();
+        let rest = &s[start..];
+        let encoded = &rest[..rest.find(ENCODING_SUFFIX)?];
+        let json = base64::decode_config(encoded, base64::URL_SAFE_NO_PAD).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+
+    /// a failed call into the sensemaker cell, described by `detail`. errors which are already
+    /// widget errors are passed on.
+    pub fn from_sensemaker_call(detail: String) -> Self {
+        if let Some(err) = Self::decode(&detail) {
+            return err;
+        }
+        if SENSEMAKER_MISSING_MARKERS
+            .iter()
+            .any(|marker| detail.contains(marker))
+        {
+            WidgetError::SensemakerMissing(detail)
+        } else {
+            WidgetError::SensemakerFailed(detail)
+        }
+    }
+}
+
+impl fmt::Display for WidgetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WidgetError::SensemakerMissing(detail) => {
+                write!(f, "the sensemaker could not be reached: {}", detail)
+            }
+            WidgetError::SensemakerCellNotSet => write!(f, "the sensemaker cell has not been set"),
+            WidgetError::SensemakerFailed(detail) => write!(f, "the sensemaker failed: {}", detail),
+            WidgetError::EntryNotFound(detail) => write!(f, "entry not found: {}", detail),
+            WidgetError::SmDataMissing(detail) => write!(f, "no sensemaker data: {}", detail),
+            WidgetError::ValidationFailed(detail) => write!(f, "invalid input: {}", detail),
+            WidgetError::InvalidExpression(detail) => write!(f, "invalid expression: {}", detail),
+            WidgetError::NotAuthorized(detail) => write!(f, "not authorized: {}", detail),
+            WidgetError::InvalidProperties(detail) => {
+                write!(f, "invalid DNA properties: {}", detail)
+            }
+        }
+    }
+}
+
+impl From<WidgetError> for WasmError {
+    fn from(err: WidgetError) -> Self {
+        WasmError::Guest(err.encode())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_variants() -> Vec<WidgetError> {
+        let detail = "a \"quoted\" [bracketed] | piped \\ detail\nover λ lines ]".to_string();
+        vec![
+            WidgetError::SensemakerMissing(detail.clone()),
+            WidgetError::SensemakerCellNotSet,
+            WidgetError::SensemakerFailed(detail.clone()),
+            WidgetError::EntryNotFound(detail.clone()),
+            WidgetError::SmDataMissing(detail.clone()),
+            WidgetError::ValidationFailed(detail.clone()),
+            WidgetError::InvalidExpression(detail.clone()),
+            WidgetError::NotAuthorized(detail.clone()),
+            WidgetError::InvalidProperties(detail),
+        ]
+    }
+
+    #[test]
+    fn encode_decode_round_trips() {
+        for err in all_variants() {
+            assert_eq!(WidgetError::decode(&err.encode()), Some(err));
+        }
+    }
+
+    #[test]
+    fn decode_finds_errors_wrapped_and_escaped() {
+        for err in all_variants() {
+            let wrapped = format!("{:?}", WasmError::from(err.clone()));
+            let rewrapped = format!(
+                "{:?}",
+                WasmError::Guest(format!("call failed: {}", wrapped))
+            );
+            assert_eq!(WidgetError::decode(&wrapped), Some(err.clone()));
+            assert_eq!(WidgetError::decode(&rewrapped), Some(err));
+        }
+    }
+
+    #[test]
+    fn decode_rejects_other_strings() {
+        assert_eq!(WidgetError::decode("Guest(\"no widget error here\")"), None);
+        assert_eq!(WidgetError::decode("widget_error[not base64!]"), None);
+        assert_eq!(WidgetError::decode("widget_error[e30"), None);
+    }
+
+    #[test]
+    fn from_sensemaker_call_classifies() {
+        assert_eq!(
+            WidgetError::from_sensemaker_call("Host(\"CellMissing(..)\")".into()),
+            WidgetError::SensemakerMissing("Host(\"CellMissing(..)\")".into())
+        );
+        assert_eq!(
+            WidgetError::from_sensemaker_call("NetworkError(\"timed out\")".into()),
+            WidgetError::SensemakerMissing("NetworkError(\"timed out\")".into())
+        );
+        assert_eq!(
+            WidgetError::from_sensemaker_call("Guest(\"parse error\")".into()),
+            WidgetError::SensemakerFailed("Guest(\"parse error\")".into())
+        );
+        let err = WidgetError::NotAuthorized("no".into());
+        assert_eq!(
+            WidgetError::from_sensemaker_call(format!("{:?}", WasmError::from(err.clone()))),
+            err
+        );
+    }
+}
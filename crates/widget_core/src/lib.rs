@@ -0,0 +1,4 @@
+pub mod buckets;
+pub mod config;
+pub mod error;
+pub mod types;
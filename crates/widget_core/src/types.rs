@@ -0,0 +1,9 @@
+use hdk::prelude::*;
+
+/// the result of a list extern: every item which could be loaded, plus why the others couldn't.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListResult<T> {
+    pub items: Vec<T>,
+    /// (entry hash of the item which failed to load, reason)
+    pub errors: Vec<(EntryHash, String)>,
+}
@@ -0,0 +1,587 @@
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement};
+use yew::html::{Scope, TargetCast};
+use yew::{html, Callback, Component, Context, Html, Properties};
+
+/// the parameters of an sm_comp: the current state and the action.
+pub const SM_COMP_PARAMS: [&str; 2] = ["st", "act"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl ArithOp {
+    pub const ALL: [ArithOp; 4] = [ArithOp::Add, ArithOp::Sub, ArithOp::Mul, ArithOp::Div];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ArithOp::Add => "+",
+            ArithOp::Sub => "-",
+            ArithOp::Mul => "*",
+            ArithOp::Div => "/",
+        }
+    }
+
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        ArithOp::ALL.into_iter().find(|op| op.symbol() == symbol)
+    }
+}
+
+/// the body of an expression, as the builder shows it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Block {
+    Number(i64),
+    /// one of the expression's parameters, e.g. the state or the action of an sm_comp.
+    Input(String),
+    /// `weight * block`
+    Weighted(i64, Box<Block>),
+    Arith(ArithOp, Box<Block>, Box<Block>),
+    /// `if lhs == rhs then .. else ..`
+    IfEq(Box<Block>, Box<Block>, Box<Block>, Box<Block>),
+}
+
+impl Block {
+    /// the names shown in the kind picker, in order.
+    const KINDS: [&str; 8] = ["number", "input", "weighted", "+", "-", "*", "/", "if ="];
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Block::Number(_) => "number",
+            Block::Input(_) => "input",
+            Block::Weighted(_, _) => "weighted",
+            Block::Arith(op, _, _) => op.symbol(),
+            Block::IfEq(_, _, _, _) => "if =",
+        }
+    }
+
+    /// a block of kind `kind`, keeping `self` as its first operand where it has one, so changing
+    /// the kind of a block doesn't throw away what was built below it.
+    fn with_kind(&self, kind: &str, params: &[String]) -> Block {
+        let keep = || Box::new(self.clone());
+        let zero = || Box::new(Block::Number(0));
+        match kind {
+            "number" => Block::Number(0),
+            "input" => match params.first() {
+                Some(param) => Block::Input(param.clone()),
+                None => Block::Number(0),
+            },
+            "weighted" => Block::Weighted(1, keep()),
+            "if =" => Block::IfEq(keep(), zero(), Box::new(Block::Number(1)), zero()),
+            symbol => match ArithOp::from_symbol(symbol) {
+                Some(op) => Block::Arith(op, keep(), zero()),
+                None => self.clone(),
+            },
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Block> {
+        match self {
+            Block::Number(_) | Block::Input(_) => vec![],
+            Block::Weighted(_, block) => vec![block.as_mut()],
+            Block::Arith(_, lhs, rhs) => vec![lhs.as_mut(), rhs.as_mut()],
+            Block::IfEq(lhs, rhs, then, els) => {
+                vec![lhs.as_mut(), rhs.as_mut(), then.as_mut(), els.as_mut()]
+            }
+        }
+    }
+
+    /// the block reached by following `path`, as child indices, from `self`.
+    fn at_mut(&mut self, path: &[usize]) -> Option<&mut Block> {
+        match path.split_first() {
+            None => Some(self),
+            Some((idx, rest)) => self.children_mut().into_iter().nth(*idx)?.at_mut(rest),
+        }
+    }
+
+    pub fn to_expr_string(&self) -> String {
+        match self {
+            // `rep_lang` has no negative literals
+            Block::Number(x) if *x < 0 => format!("(- 0 {})", x.unsigned_abs()),
+            Block::Number(x) => x.to_string(),
+            Block::Input(name) => name.clone(),
+            Block::Weighted(weight, block) => format!(
+                "(* {} {})",
+                Block::Number(*weight).to_expr_string(),
+                block.to_expr_string()
+            ),
+            Block::Arith(op, lhs, rhs) => format!(
+                "({} {} {})",
+                op.symbol(),
+                lhs.to_expr_string(),
+                rhs.to_expr_string()
+            ),
+            Block::IfEq(lhs, rhs, then, els) => format!(
+                "(if (== {} {}) {} {})",
+                lhs.to_expr_string(),
+                rhs.to_expr_string(),
+                then.to_expr_string(),
+                els.to_expr_string()
+            ),
+        }
+    }
+}
+
+/// the `rep_lang` source for `body`: a function of `params`, or just `body` if there are none.
+pub fn blocks_to_expr(params: &[String], body: &Block) -> String {
+    if params.is_empty() {
+        body.to_expr_string()
+    } else {
+        format!("(lam [{}] {})", params.join(" "), body.to_expr_string())
+    }
+}
+
+#[derive(Debug)]
+enum Sexp {
+    Atom(String),
+    List(Vec<Sexp>),
+    Vector(Vec<Sexp>),
+}
+
+fn tokenize(src: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut atom = String::new();
+    for c in src.chars() {
+        if c.is_whitespace() || "()[]".contains(c) {
+            if !atom.is_empty() {
+                tokens.push(std::mem::take(&mut atom));
+            }
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        } else {
+            atom.push(c);
+        }
+    }
+    if !atom.is_empty() {
+        tokens.push(atom);
+    }
+    tokens
+}
+
+fn read_sexp(tokens: &[String], pos: &mut usize) -> Option<Sexp> {
+    let token = tokens.get(*pos)?;
+    *pos += 1;
+    let close = match token.as_str() {
+        "(" => ")",
+        "[" => "]",
+        ")" | "]" => return None,
+        _ => return Some(Sexp::Atom(token.clone())),
+    };
+    let mut items = Vec::new();
+    while tokens.get(*pos)? != close {
+        items.push(read_sexp(tokens, pos)?);
+    }
+    *pos += 1;
+    Some(if close == ")" {
+        Sexp::List(items)
+    } else {
+        Sexp::Vector(items)
+    })
+}
+
+/// `names` maps the expression's own parameter names to the builder's.
+fn sexp_to_block(sexp: &Sexp, names: &[(String, String)]) -> Option<Block> {
+    match sexp {
+        Sexp::Atom(atom) => match atom.parse::<i64>() {
+            Ok(x) => Some(Block::Number(x)),
+            Err(_) => names
+                .iter()
+                .find(|(name, _param)| name == atom)
+                .map(|(_name, param)| Block::Input(param.clone())),
+        },
+        Sexp::List(items) => match items.as_slice() {
+            [Sexp::Atom(head), lhs, rhs] => {
+                let op = ArithOp::from_symbol(head)?;
+                let rhs = sexp_to_block(rhs, names)?;
+                match (op, sexp_to_block(lhs, names)?) {
+                    (ArithOp::Sub, Block::Number(0)) => match rhs {
+                        Block::Number(x) => Some(Block::Number(-x)),
+                        rhs => Some(Block::Arith(op, Box::new(Block::Number(0)), Box::new(rhs))),
+                    },
+                    (ArithOp::Mul, Block::Number(weight)) => {
+                        Some(Block::Weighted(weight, Box::new(rhs)))
+                    }
+                    (op, lhs) => Some(Block::Arith(op, Box::new(lhs), Box::new(rhs))),
+                }
+            }
+            [Sexp::Atom(head), Sexp::List(cond), then, els] if head == "if" => {
+                match cond.as_slice() {
+                    [Sexp::Atom(eq), lhs, rhs] if eq == "==" => Some(Block::IfEq(
+                        Box::new(sexp_to_block(lhs, names)?),
+                        Box::new(sexp_to_block(rhs, names)?),
+                        Box::new(sexp_to_block(then, names)?),
+                        Box::new(sexp_to_block(els, names)?),
+                    )),
+                    _ => None,
+                }
+            }
+            _ => None,
+        },
+        Sexp::Vector(_) => None,
+    }
+}
+
+/// the blocks for `expr_str`, if it only uses what the builder can show. it has to be a `lam` over
+/// as many parameters as `params` (or not a `lam`, if there are none), but they may be named
+/// differently.
+pub fn expr_to_blocks(params: &[String], expr_str: &str) -> Option<Block> {
+    let tokens = tokenize(expr_str);
+    let mut pos = 0;
+    let sexp = read_sexp(&tokens, &mut pos)?;
+    if pos != tokens.len() {
+        return None;
+    }
+    if params.is_empty() {
+        return sexp_to_block(&sexp, &[]);
+    }
+    match sexp {
+        Sexp::List(items) => match items.as_slice() {
+            [Sexp::Atom(lam), Sexp::Vector(names), body]
+                if lam == "lam" && names.len() == params.len() =>
+            {
+                let names = names
+                    .iter()
+                    .zip(params)
+                    .map(|(name, param)| match name {
+                        Sexp::Atom(name) => Some((name.clone(), param.clone())),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                sexp_to_block(body, &names)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+pub enum Msg {
+    ToggleShown,
+    /// replace the block at the path (child indices from the root).
+    Edit(Vec<usize>, Block),
+    /// start from scratch, for expressions which can't be shown as blocks.
+    Reset,
+    Submit,
+}
+
+/// a block-based editor for `rep_lang` expressions, for people who'd rather not write them.
+pub struct ExprBuilder {
+    shown: bool,
+    /// `None` if `loaded_expr` can't be shown as blocks.
+    body: Option<Block>,
+    /// the `expr` prop the blocks were last loaded from.
+    loaded_expr: String,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// the parameter names of the expression, e.g. `st` & `act` for an sm_comp. empty for a
+    /// constant, like an sm_init.
+    #[prop_or_default]
+    pub params: Vec<String>,
+    /// the expression currently in use, which the blocks start out as.
+    pub expr: String,
+    pub on_submit: Callback<String>,
+}
+
+impl Component for ExprBuilder {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let props = ctx.props();
+        Self {
+            shown: false,
+            body: expr_to_blocks(&props.params, &props.expr),
+            loaded_expr: props.expr.clone(),
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let props = ctx.props();
+        // only reload when the expression in use changed, so edits survive re-renders
+        if props.expr != self.loaded_expr {
+            self.body = expr_to_blocks(&props.params, &props.expr);
+            self.loaded_expr = props.expr.clone();
+        }
+        true
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::ToggleShown => {
+                self.shown = !self.shown;
+                true
+            }
+            Msg::Edit(path, block) => {
+                match self.body.as_mut().and_then(|body| body.at_mut(&path)) {
+                    Some(target) => {
+                        *target = block;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Msg::Reset => {
+                self.body = Some(Block::Number(0));
+                true
+            }
+            Msg::Submit => {
+                if let Some(body) = &self.body {
+                    ctx.props()
+                        .on_submit
+                        .emit(blocks_to_expr(&ctx.props().params, body));
+                }
+                false
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let link = ctx.link();
+        let toggle_html = html! {
+            <button onclick={link.callback(|_| Msg::ToggleShown)}>
+                { if self.shown { "hide blocks" } else { "blocks" } }
+            </button>
+        };
+        if !self.shown {
+            return toggle_html;
+        }
+        let params = &ctx.props().params;
+        let body_html = match &self.body {
+            None => html! {
+                <div>
+                    <p>{"this expression can't be shown as blocks."}</p>
+                    <button onclick={link.callback(|_| Msg::Reset)}>{ "start over" }</button>
+                </div>
+            },
+            Some(body) => html! {
+                <div>
+                    { self.view_block(link, params, Vec::new(), body) }
+                    <code>{ blocks_to_expr(params, body) }</code>
+                    <button onclick={link.callback(|_| Msg::Submit)}>{ "use these blocks" }</button>
+                </div>
+            },
+        };
+        html! {
+            <div class="expr-builder">
+                {toggle_html}
+                {body_html}
+            </div>
+        }
+    }
+}
+
+impl ExprBuilder {
+    fn view_block(
+        &self,
+        link: &Scope<Self>,
+        params: &[String],
+        path: Vec<usize>,
+        block: &Block,
+    ) -> Html {
+        let kind_html = {
+            let block_ = block.clone();
+            let params_ = params.to_vec();
+            let path_ = path.clone();
+            let onchange = link.callback(move |e: Event| {
+                let select: HtmlSelectElement = e.target_unchecked_into();
+                Msg::Edit(path_.clone(), block_.with_kind(&select.value(), &params_))
+            });
+            html! {
+                <select {onchange}>
+                    { for Block::KINDS.iter()
+                        // there is nothing to pick for a constant
+                        .filter(|kind| !(params.is_empty() && **kind == "input"))
+                        .map(|kind| html! {
+                            <option value={*kind} selected={*kind == block.kind()}>{ *kind }</option>
+                        })
+                    }
+                </select>
+            }
+        };
+        let child = |idx: usize, child: &Block| {
+            let mut child_path = path.clone();
+            child_path.push(idx);
+            self.view_block(link, params, child_path, child)
+        };
+        let fields_html = match block {
+            Block::Number(x) => self.view_number(link, path.clone(), *x, Block::Number),
+            Block::Input(name) => {
+                let path_ = path.clone();
+                let onchange = link.callback(move |e: Event| {
+                    let select: HtmlSelectElement = e.target_unchecked_into();
+                    Msg::Edit(path_.clone(), Block::Input(select.value()))
+                });
+                html! {
+                    <select {onchange}>
+                        { for params.iter().map(|param| html! {
+                            <option value={param.clone()} selected={param == name}>{ param }</option>
+                        })}
+                    </select>
+                }
+            }
+            Block::Weighted(weight, weighted) => {
+                let weighted_ = weighted.clone();
+                html! {
+                    <>
+                        { self.view_number(link, path.clone(), *weight, move |w| Block::Weighted(w, weighted_.clone())) }
+                        <span>{" × "}</span>
+                        { child(0, weighted) }
+                    </>
+                }
+            }
+            Block::Arith(_op, lhs, rhs) => html! {
+                <>
+                    { child(0, lhs) }
+                    { child(1, rhs) }
+                </>
+            },
+            Block::IfEq(lhs, rhs, then, els) => html! {
+                <>
+                    <span>{"if"}</span>
+                    { child(0, lhs) }
+                    <span>{"equals"}</span>
+                    { child(1, rhs) }
+                    <span>{"then"}</span>
+                    { child(2, then) }
+                    <span>{"else"}</span>
+                    { child(3, els) }
+                </>
+            },
+        };
+        html! {
+            <div class="expr-block">
+                {kind_html}
+                {fields_html}
+            </div>
+        }
+    }
+
+    /// an integer input, which replaces the block at `path` with `mk_block(value)` on change.
+    fn view_number<F>(&self, link: &Scope<Self>, path: Vec<usize>, value: i64, mk_block: F) -> Html
+    where
+        F: Fn(i64) -> Block + 'static,
+    {
+        let onchange = link.batch_callback(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            input
+                .value()
+                .trim()
+                .parse::<i64>()
+                .ok()
+                .map(|x| Msg::Edit(path.clone(), mk_block(x)))
+        });
+        html! {
+            <input type="number" step="1" value={value.to_string()} {onchange} />
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sm_comp_params() -> Vec<String> {
+        SM_COMP_PARAMS.iter().map(|p| p.to_string()).collect()
+    }
+
+    fn input(name: &str) -> Box<Block> {
+        Box::new(Block::Input(name.into()))
+    }
+
+    fn num(x: i64) -> Box<Block> {
+        Box::new(Block::Number(x))
+    }
+
+    #[test]
+    fn negative_numbers_are_subtractions() {
+        assert_eq!(Block::Number(-3).to_expr_string(), "(- 0 3)");
+        assert_eq!(
+            Block::Weighted(-2, input("act")).to_expr_string(),
+            "(* (- 0 2) act)"
+        );
+    }
+
+    #[test]
+    fn blocks_round_trip() {
+        let params = sm_comp_params();
+        let bodies = vec![
+            Block::Number(7),
+            Block::Number(-7),
+            Block::Input("st".into()),
+            Block::Weighted(3, input("act")),
+            Block::Weighted(-3, input("act")),
+            Block::Weighted(-1, Box::new(Block::Weighted(-2, input("st")))),
+            Block::Arith(ArithOp::Add, input("st"), input("act")),
+            Block::Arith(ArithOp::Sub, input("st"), num(-1)),
+            Block::Arith(
+                ArithOp::Div,
+                Box::new(Block::Weighted(2, input("st"))),
+                num(3),
+            ),
+            Block::IfEq(
+                input("act"),
+                num(-1),
+                input("st"),
+                Box::new(Block::Weighted(-5, input("act"))),
+            ),
+        ];
+        for body in bodies {
+            let expr_str = blocks_to_expr(&params, &body);
+            assert_eq!(
+                expr_to_blocks(&params, &expr_str),
+                Some(body),
+                "{}",
+                expr_str
+            );
+        }
+    }
+
+    #[test]
+    fn blocks_without_params_round_trip() {
+        let body = Block::Arith(ArithOp::Mul, num(-4), num(5));
+        let expr_str = blocks_to_expr(&[], &body);
+        assert_eq!(expr_str, "(* (- 0 4) 5)");
+        // a product with a number first reads back as a weight
+        assert_eq!(
+            expr_to_blocks(&[], &expr_str),
+            Some(Block::Weighted(-4, num(5)))
+        );
+    }
+
+    #[test]
+    fn exprs_round_trip_with_their_own_param_names() {
+        let params = sm_comp_params();
+        let expr_str = "(lam [state action] (+ state (* (- 0 2) action)))";
+        let body = expr_to_blocks(&params, expr_str).unwrap();
+        assert_eq!(
+            body,
+            Block::Arith(
+                ArithOp::Add,
+                input("st"),
+                Box::new(Block::Weighted(-2, input("act")))
+            )
+        );
+        assert_eq!(
+            blocks_to_expr(&params, &body),
+            "(lam [st act] (+ st (* (- 0 2) act)))"
+        );
+    }
+
+    #[test]
+    fn exprs_the_builder_cant_show_are_rejected() {
+        let params = sm_comp_params();
+        // wrong number of parameters
+        assert_eq!(expr_to_blocks(&params, "(lam [st] st)"), None);
+        // unknown names & functions
+        assert_eq!(expr_to_blocks(&params, "(lam [st act] (+ st x))"), None);
+        assert_eq!(expr_to_blocks(&params, "(lam [st act] (f st act))"), None);
+        // trailing input & unbalanced brackets
+        assert_eq!(expr_to_blocks(&params, "(lam [st act] st) 1"), None);
+        assert_eq!(expr_to_blocks(&params, "(lam [st act] (+ st act)"), None);
+    }
+}
@@ -9,24 +9,44 @@ use weblog::console_error;
 use yew::html::TargetCast;
 use yew::{html, Callback, Component, Context, Html, Properties};
 
+use crate::image_preprocess::{preprocess_image, ImagePreprocessConfig};
+
 pub enum Msg {
     Loaded(FileBytes),
     Files(Vec<File>),
+    Error(String),
 }
 
 pub struct FileBytes {
     pub filename: String,
     pub bytes: Vec<u8>,
+    /// only present if the file went through image preprocessing.
+    pub thumbnail: Option<Vec<u8>>,
+    /// only present if the file went through image preprocessing. see
+    /// `image_preprocess::perceptual_hash`.
+    pub perceptual_hash: Option<String>,
 }
 
 pub struct FileUploadApp {
     readers: HashMap<String, FileReader>,
+    /// files which were dropped rather than uploaded, and why. cleared on the next selection.
+    errors: Vec<String>,
 }
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
     pub content_name: String,
     pub on_file_upload: Callback<FileBytes>,
+    /// if set, every file is treated as an image and preprocessed (metadata stripped,
+    /// downscaled, re-encoded, thumbnailed) before `on_file_upload` fires.
+    #[prop_or_default]
+    pub preprocess: Option<ImagePreprocessConfig>,
+    /// lowercase file extensions offered by the file picker. empty means any.
+    #[prop_or_default]
+    pub allowed_extensions: Vec<String>,
+    /// files larger than this (after any preprocessing) are dropped rather than uploaded.
+    #[prop_or_default]
+    pub max_bytes: Option<u64>,
 }
 
 impl Component for FileUploadApp {
@@ -36,6 +56,7 @@ impl Component for FileUploadApp {
     fn create(_ctx: &Context<Self>) -> Self {
         Self {
             readers: HashMap::default(),
+            errors: Vec::new(),
         }
     }
 
@@ -43,10 +64,43 @@ impl Component for FileUploadApp {
         match msg {
             Msg::Loaded(fb) => {
                 self.readers.remove(&fb.filename);
+                let fb = match &ctx.props().preprocess {
+                    None => fb,
+                    Some(config) => match preprocess_image(config, &fb.filename, &fb.bytes) {
+                        Ok(img) => FileBytes {
+                            filename: img.filename,
+                            bytes: img.bytes,
+                            thumbnail: Some(img.thumbnail),
+                            perceptual_hash: Some(img.perceptual_hash),
+                        },
+                        Err(err) => {
+                            ctx.link()
+                                .send_message(Msg::Error(format!("{}: {}", fb.filename, err)));
+                            return false;
+                        }
+                    },
+                };
+                if let Some(max_bytes) = ctx.props().max_bytes {
+                    if fb.bytes.len() as u64 > max_bytes {
+                        ctx.link().send_message(Msg::Error(format!(
+                            "{} is {} bytes, but at most {} are allowed",
+                            fb.filename,
+                            fb.bytes.len(),
+                            max_bytes
+                        )));
+                        return false;
+                    }
+                }
                 ctx.props().on_file_upload.emit(fb);
                 false
             }
+            Msg::Error(err) => {
+                console_error!(format!("FileUploadApp: {}", err));
+                self.errors.push(err);
+                true
+            }
             Msg::Files(files) => {
+                self.errors.clear();
                 for file in files.into_iter() {
                     let filename = file.name();
                     let task = {
@@ -55,26 +109,36 @@ impl Component for FileUploadApp {
 
                         gloo::file::callbacks::read_as_bytes(&file, move |res| match res {
                             Err(err) => {
-                                console_error!(format!("gloo file read_as_bytes error: {}", err));
-                            }
-                            Ok(bytes) => {
-                                link.send_message(Msg::Loaded(FileBytes { filename, bytes }))
+                                link.send_message(Msg::Error(format!("{}: {}", filename, err)));
                             }
+                            Ok(bytes) => link.send_message(Msg::Loaded(FileBytes {
+                                filename,
+                                bytes,
+                                thumbnail: None,
+                                perceptual_hash: None,
+                            })),
                         })
                     };
                     self.readers.insert(filename, task);
                 }
-                false
+                true
             }
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
+        let accept = ctx
+            .props()
+            .allowed_extensions
+            .iter()
+            .map(|extension| format!(".{}", extension))
+            .collect::<Vec<_>>()
+            .join(",");
         html! {
             <div>
                 <div>
                     <h3 class="subtitle">{format!("upload {}", ctx.props().content_name)}</h3>
-                    <input type="file" multiple=true onchange={ctx.link().callback(move |e: Event| {
+                    <input type="file" multiple=true {accept} onchange={ctx.link().callback(move |e: Event| {
                             let mut result = Vec::new();
                             let input: HtmlInputElement = e.target_unchecked_into();
 
@@ -89,6 +153,9 @@ impl Component for FileUploadApp {
                             Msg::Files(result)
                         })}
                     />
+                    { for self.errors.iter().map(|err| html! {
+                        <p class="help is-danger">{format!("not uploaded: {}", err)}</p>
+                    })}
                 </div>
             </div>
         }
@@ -0,0 +1,338 @@
+use std::io::Cursor;
+
+use exif::{In, Reader, Tag};
+use image::{
+    codecs::gif::{GifDecoder, GifEncoder, Repeat},
+    imageops::FilterType,
+    AnimationDecoder, DynamicImage, ImageFormat, ImageOutputFormat,
+};
+
+/// settings for the client-side image preprocessing done by `FileUploadApp`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImagePreprocessConfig {
+    /// the longest edge of the re-encoded image, in pixels.
+    pub max_dimension: u32,
+    /// the longest edge of the generated thumbnail, in pixels.
+    pub thumbnail_dimension: u32,
+    /// quality (1-100) used for JPEG re-encoding.
+    pub jpeg_quality: u8,
+}
+
+impl Default for ImagePreprocessConfig {
+    fn default() -> Self {
+        Self {
+            max_dimension: 1920,
+            thumbnail_dimension: 320,
+            jpeg_quality: 85,
+        }
+    }
+}
+
+pub struct PreprocessedImage {
+    /// the original filename, with its extension swapped to match the re-encoded format.
+    pub filename: String,
+    pub bytes: Vec<u8>,
+    pub thumbnail: Vec<u8>,
+    /// see `perceptual_hash`.
+    pub perceptual_hash: String,
+}
+
+/// decode `bytes`, strip all metadata, downscale to fit `config.max_dimension`, re-encode, and
+/// generate a thumbnail.
+///
+/// metadata (EXIF, GPS, ICC, ...) is dropped because we only ever re-encode raw pixels. the EXIF
+/// orientation is applied to the pixels first, so photos don't end up sideways.
+///
+/// GIFs are re-encoded frame by frame instead, which keeps any animation but drops comments and
+/// application extensions. they aren't downscaled, and get a (still-frame) thumbnail.
+pub fn preprocess_image(
+    config: &ImagePreprocessConfig,
+    filename: &str,
+    bytes: &[u8],
+) -> Result<PreprocessedImage, String> {
+    let format =
+        image::guess_format(bytes).map_err(|err| format!("unknown image format: {}", err))?;
+    let img = image::load_from_memory_with_format(bytes, format)
+        .map_err(|err| format!("image decode failed: {}", err))?;
+    let img = apply_exif_orientation(img, bytes);
+    let perceptual_hash = perceptual_hash(&img);
+
+    let thumbnail = img.thumbnail(config.thumbnail_dimension, config.thumbnail_dimension);
+    let (thumbnail, _ext) = encode(&thumbnail, config.jpeg_quality)?;
+
+    if format == ImageFormat::Gif {
+        return Ok(PreprocessedImage {
+            filename: with_extension(filename, "gif"),
+            bytes: reencode_gif(bytes)?,
+            thumbnail,
+            perceptual_hash,
+        });
+    }
+
+    let img = if img.width() > config.max_dimension || img.height() > config.max_dimension {
+        // `resize` preserves the aspect ratio, fitting the image within the given bounds.
+        img.resize(
+            config.max_dimension,
+            config.max_dimension,
+            FilterType::Lanczos3,
+        )
+    } else {
+        img
+    };
+    let (bytes, ext) = encode(&img, config.jpeg_quality)?;
+
+    Ok(PreprocessedImage {
+        filename: with_extension(filename, ext),
+        bytes,
+        thumbnail,
+        perceptual_hash,
+    })
+}
+
+/// a 64-bit difference hash ("dHash") of `img`, as 16 hex digits.
+///
+/// the image is shrunk to 9x8 grayscale, and each bit records whether a pixel is brighter than its
+/// right-hand neighbour. re-encoding, resizing & small edits only flip a few bits, so visually
+/// similar images have hashes with a small Hamming distance.
+pub fn perceptual_hash(img: &DynamicImage) -> String {
+    let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash: u64 = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            let bit = small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | bit as u64;
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// images with transparency are encoded as PNG, everything else as JPEG.
+fn encode(img: &DynamicImage, jpeg_quality: u8) -> Result<(Vec<u8>, &'static str), String> {
+    let mut buf = Cursor::new(Vec::new());
+    let ext = if img.color().has_alpha() {
+        img.write_to(&mut buf, ImageOutputFormat::Png)
+            .map_err(|err| format!("png encode failed: {}", err))?;
+        "png"
+    } else {
+        DynamicImage::ImageRgb8(img.to_rgb8())
+            .write_to(&mut buf, ImageOutputFormat::Jpeg(jpeg_quality))
+            .map_err(|err| format!("jpeg encode failed: {}", err))?;
+        "jpg"
+    };
+    Ok((buf.into_inner(), ext))
+}
+
+/// decode every frame of `bytes` and write them into a fresh GIF, which carries nothing but the
+/// frames, their delays and an infinite loop.
+fn reencode_gif(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let frames = GifDecoder::new(Cursor::new(bytes))
+        .and_then(|decoder| decoder.into_frames().collect_frames())
+        .map_err(|err| format!("gif decode failed: {}", err))?;
+    let mut buf = Vec::new();
+    {
+        // the trailer is written when the encoder is dropped.
+        let mut encoder = GifEncoder::new(&mut buf);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .and_then(|()| encoder.encode_frames(frames))
+            .map_err(|err| format!("gif encode failed: {}", err))?;
+    }
+    Ok(buf)
+}
+
+fn apply_exif_orientation(img: DynamicImage, bytes: &[u8]) -> DynamicImage {
+    let orientation = Reader::new()
+        .read_from_container(&mut Cursor::new(bytes))
+        .ok()
+        .and_then(|exif| {
+            exif.get_field(Tag::Orientation, In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+        });
+    match orientation {
+        Some(2) => img.fliph(),
+        Some(3) => img.rotate180(),
+        Some(4) => img.flipv(),
+        Some(5) => img.rotate90().fliph(),
+        Some(6) => img.rotate90(),
+        Some(7) => img.rotate270().fliph(),
+        Some(8) => img.rotate270(),
+        _ => img,
+    }
+}
+
+fn with_extension(filename: &str, ext: &str) -> String {
+    match filename.rsplit_once('.') {
+        Some((stem, _)) => format!("{}.{}", stem, ext),
+        None => format!("{}.{}", filename, ext),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Delay, Frame, Rgb, RgbImage, Rgba, RgbaImage};
+
+    /// insert an APP1 segment holding a big-endian EXIF block with a single `Orientation` field
+    /// right after the JPEG's SOI marker.
+    fn with_exif_orientation(jpeg: &[u8], orientation: u8) -> Vec<u8> {
+        let mut app1 = vec![0xFF, 0xE1, 0x00, 0x22];
+        app1.extend_from_slice(b"Exif\0\0");
+        app1.extend_from_slice(b"MM\0\x2a\0\0\0\x08");
+        app1.extend_from_slice(&[0x00, 0x01]);
+        app1.extend_from_slice(&[0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01]);
+        app1.extend_from_slice(&[0x00, orientation, 0x00, 0x00]);
+        app1.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+        let mut bytes = jpeg[..2].to_vec();
+        bytes.extend(app1);
+        bytes.extend_from_slice(&jpeg[2..]);
+        bytes
+    }
+
+    /// a 4x2 image, left half black & right half white.
+    fn half_and_half() -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(4, 2, |x, _| {
+            if x < 2 {
+                Rgb([0, 0, 0])
+            } else {
+                Rgb([255, 255, 255])
+            }
+        }))
+    }
+
+    fn jpeg(img: &DynamicImage) -> Vec<u8> {
+        encode(img, 100).unwrap().0
+    }
+
+    fn gif(frames: usize) -> Vec<u8> {
+        let mut buf = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut buf);
+            let frames = (0..frames).map(|i| {
+                let shade = (i * 100) as u8;
+                Frame::from_parts(
+                    RgbaImage::from_pixel(3, 3, Rgba([shade, shade, shade, 255])),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(100, 1),
+                )
+            });
+            encoder.encode_frames(frames).unwrap();
+        }
+        buf
+    }
+
+    /// insert a comment extension after the GIF's header & global color table.
+    fn with_gif_comment(gif: &[u8], comment: &[u8]) -> Vec<u8> {
+        let packed = gif[10];
+        let color_table_len = if packed & 0x80 != 0 {
+            3 * (1 << ((packed & 0x07) + 1))
+        } else {
+            0
+        };
+        let at = 13 + color_table_len;
+        let mut bytes = gif[..at].to_vec();
+        bytes.extend_from_slice(&[0x21, 0xFE, comment.len() as u8]);
+        bytes.extend_from_slice(comment);
+        bytes.push(0x00);
+        bytes.extend_from_slice(&gif[at..]);
+        bytes
+    }
+
+    fn is_dark(img: &DynamicImage, x: u32, y: u32) -> bool {
+        img.to_luma8().get_pixel(x, y)[0] < 128
+    }
+
+    #[test]
+    fn orientation_without_exif_is_a_no_op() {
+        let img = half_and_half();
+        let oriented = apply_exif_orientation(img.clone(), &jpeg(&img));
+        assert_eq!((oriented.width(), oriented.height()), (4, 2));
+        assert!(is_dark(&oriented, 0, 0));
+        assert!(!is_dark(&oriented, 3, 0));
+    }
+
+    #[test]
+    fn orientation_6_rotates_clockwise() {
+        let img = half_and_half();
+        let bytes = with_exif_orientation(&jpeg(&img), 6);
+        let oriented = apply_exif_orientation(img, &bytes);
+        assert_eq!((oriented.width(), oriented.height()), (2, 4));
+        // the left (dark) half ends up on top.
+        assert!(is_dark(&oriented, 0, 0));
+        assert!(!is_dark(&oriented, 0, 3));
+    }
+
+    #[test]
+    fn orientation_2_mirrors() {
+        let img = half_and_half();
+        let bytes = with_exif_orientation(&jpeg(&img), 2);
+        let oriented = apply_exif_orientation(img, &bytes);
+        assert_eq!((oriented.width(), oriented.height()), (4, 2));
+        assert!(!is_dark(&oriented, 0, 0));
+        assert!(is_dark(&oriented, 3, 0));
+    }
+
+    #[test]
+    fn preprocess_strips_exif_and_applies_it() {
+        let bytes = with_exif_orientation(&jpeg(&half_and_half()), 6);
+        let img =
+            preprocess_image(&ImagePreprocessConfig::default(), "photo.jpeg", &bytes).unwrap();
+        assert_eq!(img.filename, "photo.jpg");
+        assert!(Reader::new()
+            .read_from_container(&mut Cursor::new(&img.bytes))
+            .is_err());
+        let decoded = image::load_from_memory(&img.bytes).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (2, 4));
+    }
+
+    #[test]
+    fn preprocess_downscales_and_thumbnails() {
+        let config = ImagePreprocessConfig {
+            max_dimension: 40,
+            thumbnail_dimension: 10,
+            jpeg_quality: 85,
+        };
+        let big = DynamicImage::ImageRgb8(RgbImage::from_pixel(100, 50, Rgb([10, 20, 30])));
+        let img = preprocess_image(&config, "big.png", &jpeg(&big)).unwrap();
+        let decoded = image::load_from_memory(&img.bytes).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (40, 20));
+        let thumbnail = image::load_from_memory(&img.thumbnail).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (10, 5));
+        assert_eq!(img.perceptual_hash.len(), 16);
+    }
+
+    #[test]
+    fn preprocess_keeps_alpha_as_png() {
+        let mut bytes = Cursor::new(Vec::new());
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, Rgba([1, 2, 3, 4])))
+            .write_to(&mut bytes, ImageOutputFormat::Png)
+            .unwrap();
+        let img =
+            preprocess_image(&ImagePreprocessConfig::default(), "x", &bytes.into_inner()).unwrap();
+        assert_eq!(img.filename, "x.png");
+        assert_eq!(image::guess_format(&img.bytes).unwrap(), ImageFormat::Png);
+    }
+
+    #[test]
+    fn preprocess_reencodes_gifs_keeping_frames() {
+        let bytes = with_gif_comment(&gif(2), b"secret location");
+        let img = preprocess_image(&ImagePreprocessConfig::default(), "anim.gif", &bytes).unwrap();
+        assert_eq!(img.filename, "anim.gif");
+        assert_eq!(image::guess_format(&img.bytes).unwrap(), ImageFormat::Gif);
+        assert!(!img
+            .bytes
+            .windows(b"secret location".len())
+            .any(|window| window == b"secret location"));
+        let frames = GifDecoder::new(Cursor::new(&img.bytes))
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(frames.len(), 2);
+    }
+
+    #[test]
+    fn preprocess_rejects_garbage() {
+        assert!(preprocess_image(&ImagePreprocessConfig::default(), "x", b"not an image").is_err());
+    }
+}
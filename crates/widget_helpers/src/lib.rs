@@ -1,7 +1,17 @@
 use wasm_bindgen::prelude::*;
 use weblog::{console_error, console_log};
 
+pub mod expr_builder;
 pub mod file_upload;
+pub mod image_preprocess;
+pub mod list_result;
+pub mod rep_lang_check;
+pub mod score_preview;
+pub mod sensemaker_discovery;
+pub mod sm_config_history;
+pub mod sm_exprs;
+pub mod sm_op_retry;
+pub mod sm_proposals;
 
 pub enum WsMsg<WS, WSCMD, WSCMDRESP> {
     Connected(WS),
@@ -0,0 +1,43 @@
+use js_sys::{Array, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+
+use holochain_client_wrapper::{DeserializeFromJsObj, EntryHashRaw};
+
+/// the UI-side mirror of `widget_core::types::ListResult`.
+pub struct ListResultRaw<T> {
+    pub items: Vec<T>,
+    pub errors: Vec<(EntryHashRaw, String)>,
+}
+
+impl<T> ListResultRaw<T> {
+    /// `deserialize_item` turns one element of `items` into a `T`. the `DeserializeFromJsObj_`
+    /// traits of the widget UIs are per-crate, so we can't bound `T` on one of them here.
+    pub fn deserialize_from_js_obj_with(
+        v: JsValue,
+        deserialize_item: impl Fn(JsValue) -> T,
+    ) -> Self {
+        let items = js_array(
+            Reflect::get(&v, &JsValue::from_str("items")).expect("object field get to succeed"),
+        )
+        .iter()
+        .map(deserialize_item)
+        .collect();
+        let errors = js_array(
+            Reflect::get(&v, &JsValue::from_str("errors")).expect("object field get to succeed"),
+        )
+        .iter()
+        .map(|pair| {
+            let pair = js_array(pair);
+            (
+                EntryHashRaw::deserialize_from_js_obj(pair.at(0)),
+                String::deserialize_from_js_obj(pair.at(1)),
+            )
+        })
+        .collect();
+        Self { items, errors }
+    }
+}
+
+fn js_array(v: JsValue) -> Array {
+    v.dyn_into().expect("Array conversion to succeed")
+}
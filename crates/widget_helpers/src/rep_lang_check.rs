@@ -0,0 +1,250 @@
+use std::fmt;
+
+use combine::{stream::position, EasyParser};
+use rep_lang_concrete_syntax::parse::expr;
+use rep_lang_core::abstract_syntax::Expr;
+use rep_lang_runtime::{
+    env::Env,
+    infer::{infer_expr, TypeError},
+};
+use yew::prelude::*;
+
+/// why a `rep_lang` expression was rejected before it was sent to a zome.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExprError {
+    pub message: String,
+    /// 1-based (line, column) in the submitted expression. only parse errors have one.
+    pub position: Option<(i32, i32)>,
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => {
+                write!(f, "line {}, column {}: {}", line, column, self.message)
+            }
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// parse `expr_str`, rejecting it if anything but whitespace follows the expression.
+pub fn parse_expr(expr_str: &str) -> Result<Expr, ExprError> {
+    match expr().easy_parse(position::Stream::new(expr_str)) {
+        Ok((parsed, rest)) => {
+            if rest.input.trim().is_empty() {
+                Ok(parsed)
+            } else {
+                Err(ExprError {
+                    message: format!("unexpected `{}` after the expression", rest.input.trim()),
+                    position: Some((rest.positioner.line, rest.positioner.column)),
+                })
+            }
+        }
+        Err(err) => Err(ExprError {
+            message: err
+                .errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join("; "),
+            position: Some((err.position.line, err.position.column)),
+        }),
+    }
+}
+
+/// `items` as an inline list, e.g. "`a`, `b` and `c`".
+fn list_in_words<T: fmt::Display>(items: &[T]) -> String {
+    let quoted: Vec<String> = items.iter().map(|item| format!("`{}`", item)).collect();
+    match quoted.split_last() {
+        None => "nothing".into(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+/// `type_error` in words, with types as `rep_lang` prints them. `rep_lang`'s syntax tree doesn't
+/// keep source positions, so unlike parse errors, type errors can't point at where they are.
+fn describe_type_error(type_error: &TypeError) -> String {
+    match type_error {
+        TypeError::UnificationFail(expected, found) => {
+            format!("`{}` and `{}` don't match", expected, found)
+        }
+        TypeError::InfiniteType(tv, ty) => {
+            format!("`{}` would have to contain itself, as `{}`", tv, ty)
+        }
+        TypeError::UnboundVariable(name) => format!("`{}` isn't defined", name),
+        TypeError::Ambiguous(constraints) => format!(
+            "the type is ambiguous, as nothing settles {}",
+            constraints
+                .iter()
+                .map(|(lhs, rhs)| format!("whether `{}` is `{}`", lhs, rhs))
+                .collect::<Vec<_>>()
+                .join(", or ")
+        ),
+        TypeError::UnificationMismatch(expected, found) => format!(
+            "{} types can't be matched up with {}: {} against {}",
+            expected.len(),
+            found.len(),
+            list_in_words(expected),
+            list_in_words(found)
+        ),
+    }
+}
+
+fn typecheck(parsed: &Expr) -> Result<(), ExprError> {
+    infer_expr(&Env::new(), parsed)
+        .map(|_scheme| ())
+        .map_err(|type_error| ExprError {
+            message: format!("type error: {}", describe_type_error(&type_error)),
+            position: None,
+        })
+}
+
+/// typecheck `expr_str` as it is used by `wrap`, which is described by `usage`. `expr_str` is
+/// parsed & typechecked on its own first, so parse error positions point into it, rather than into
+/// the wrapping expression, and only the errors of the wrapping are put down to `usage`.
+fn check_in_context<F>(expr_str: &str, usage: &str, wrap: F) -> Result<(), ExprError>
+where
+    F: FnOnce(&str) -> String,
+{
+    typecheck(&parse_expr(expr_str)?)?;
+    typecheck(&parse_expr(&wrap(expr_str))?).map_err(|err| ExprError {
+        message: format!("{} {}", usage, err.message),
+        position: None,
+    })
+}
+
+/// an sm_init can be any well-typed expression.
+pub fn check_sm_init(sm_init_str: &str) -> Result<(), ExprError> {
+    typecheck(&parse_expr(sm_init_str)?)
+}
+
+/// an sm_comp takes the state (starting from `sm_init_str`) and an integer action, and returns a
+/// state of the same type. it is checked by stepping twice, as that fails unless the types agree.
+pub fn check_sm_comp(sm_init_str: &str, sm_comp_str: &str) -> Result<(), ExprError> {
+    check_in_context(
+        sm_comp_str,
+        "as an sm_comp of the sm_init in use:",
+        |sm_comp| format!("((lam [f] (f (f {} 0) 0)) {})", sm_init_str, sm_comp),
+    )
+}
+
+/// check that `expr_str` is a function of `arity` integers (sensemaker states) returning an
+/// integer, as feed score & hide computations are.
+pub fn check_int_fn(expr_str: &str, arity: usize) -> Result<(), ExprError> {
+    let usage = format!("as an integer function of arity {}:", arity);
+    check_in_context(expr_str, &usage, |e| {
+        format!("(+ 0 ({}{}))", e, " 0".repeat(arity))
+    })
+}
+
+/// show `err` under the expression it is about, pointing at the position if there is one.
+pub fn view_expr_error(expr_str: &str, err: &ExprError) -> Html {
+    let pointer_html = match err.position {
+        Some((line, column)) => {
+            let src_line = expr_str
+                .lines()
+                .nth((line.max(1) - 1) as usize)
+                .unwrap_or_default();
+            let pointer = format!("{}^", " ".repeat((column.max(1) - 1) as usize));
+            html! {
+                <pre>{ format!("{}\n{}", src_line, pointer) }</pre>
+            }
+        }
+        None => html! {},
+    };
+    html! {
+        <div class="expr-error">
+            {pointer_html}
+            <p>{ err.to_string() }</p>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUM_COMP: &str = "(lam [st act] (+ st act))";
+
+    #[test]
+    fn sm_comps_which_fit_pass() {
+        assert_eq!(check_sm_comp("0", "+"), Ok(()));
+        assert_eq!(check_sm_comp("0", SUM_COMP), Ok(()));
+        assert_eq!(check_sm_comp("(+ 1 2)", SUM_COMP), Ok(()));
+    }
+
+    #[test]
+    fn sm_comps_which_dont_fit_fail() {
+        // not a function at all
+        let err = check_sm_comp("0", "1").unwrap_err();
+        assert!(err.message.starts_with("as an sm_comp"), "{}", err);
+        assert_eq!(err.position, None);
+        // takes one argument too few
+        assert!(check_sm_comp("0", "(lam [st] st)").is_err());
+        // well-typed by itself, but steps to a function rather than an integer
+        assert!(check_sm_comp("0", "(lam [st act] (lam [x] x))").is_err());
+    }
+
+    #[test]
+    fn sm_comp_type_errors_of_their_own_arent_put_down_to_the_sm_init() {
+        let err = check_sm_comp("0", "(lam [st act] (+ st undefined_var))").unwrap_err();
+        assert!(err.message.starts_with("type error"), "{}", err);
+    }
+
+    #[test]
+    fn sm_comp_parse_errors_point_into_the_sm_comp() {
+        let err = check_sm_comp("0", "(lam [st act] (+ st act)) )").unwrap_err();
+        assert_eq!(err.position.map(|(line, _column)| line), Some(1));
+        let err = check_sm_comp("0", "(lam [st act]\n  (+ st act").unwrap_err();
+        assert_eq!(err.position.map(|(line, _column)| line), Some(2));
+    }
+
+    #[test]
+    fn int_fns_of_the_right_arity_pass() {
+        assert_eq!(check_int_fn("(lam [a] a)", 1), Ok(()));
+        assert_eq!(check_int_fn("(lam [a b] (+ a b))", 2), Ok(()));
+        assert_eq!(check_int_fn("+", 2), Ok(()));
+    }
+
+    #[test]
+    fn int_fns_of_the_wrong_arity_or_type_fail() {
+        assert!(check_int_fn("(lam [a] a)", 2).is_err());
+        assert!(check_int_fn("(lam [a b] (+ a b))", 1).is_err());
+        assert!(check_int_fn("(lam [a] (lam [x] x))", 1).is_err());
+        let err = check_int_fn("1", 1).unwrap_err();
+        assert!(
+            err.message.starts_with("as an integer function of arity 1"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn type_errors_are_described_in_words() {
+        assert_eq!(
+            check_sm_init("(+ 1 true)").unwrap_err().message,
+            "type error: `Int` and `Bool` don't match"
+        );
+        assert_eq!(
+            check_sm_init("undefined_var").unwrap_err().message,
+            "type error: `undefined_var` isn't defined"
+        );
+        assert_eq!(
+            check_int_fn("(lam [a] true)", 1).unwrap_err().message,
+            "as an integer function of arity 1: type error: `Int` and `Bool` don't match"
+        );
+    }
+
+    #[test]
+    fn lists_are_put_in_words() {
+        assert_eq!(list_in_words::<&str>(&[]), "nothing");
+        assert_eq!(list_in_words(&["Int"]), "`Int`");
+        assert_eq!(list_in_words(&["Int", "Bool"]), "`Int` and `Bool`");
+        assert_eq!(
+            list_in_words(&["Int", "Bool", "Int"]),
+            "`Int`, `Bool` and `Int`"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use yew::{html, Callback, Html, MouseEvent};
+
+/// something scored, with its score as things are and as a simulation says it would be. `None`
+/// if it has no score.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoreDiffRow {
+    pub label: String,
+    pub current: Option<i64>,
+    pub simulated: Option<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulatedScores {
+    pub rows: Vec<ScoreDiffRow>,
+    /// how many items the simulation couldn't score.
+    pub failed: usize,
+}
+
+/// a change to a score computation, held back until the user has seen what it would do. `C`
+/// describes the change, for applying it once confirmed.
+pub struct ScorePreview<C> {
+    pub change: C,
+    /// `None` while the simulation runs, otherwise its outcome or why it failed.
+    pub result: Option<Result<SimulatedScores, String>>,
+}
+
+impl<C> ScorePreview<C> {
+    pub fn new(change: C) -> Self {
+        Self {
+            change,
+            result: None,
+        }
+    }
+}
+
+/// 1-based ranks by descending score, in the order of `scores`. unscored items have no rank.
+fn ranks(scores: &[Option<i64>]) -> Vec<Option<usize>> {
+    let mut ranked: Vec<(usize, i64)> = scores
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, score)| score.map(|score| (idx, score)))
+        .collect();
+    ranked.sort_by(|(_, a), (_, b)| b.cmp(a));
+    let mut ret = vec![None; scores.len()];
+    for (rank, (idx, _score)) in ranked.into_iter().enumerate() {
+        ret[idx] = Some(rank + 1);
+    }
+    ret
+}
+
+fn view_rank_move(current: Option<usize>, simulated: Option<usize>) -> String {
+    match (current, simulated) {
+        (Some(current), Some(simulated)) if simulated < current => {
+            format!("▲{}", current - simulated)
+        }
+        (Some(current), Some(simulated)) if simulated > current => {
+            format!("▼{}", simulated - current)
+        }
+        // unranked either way
+        (Some(_), Some(_)) | (None, None) => "–".into(),
+        (None, Some(_)) => "new".into(),
+        (Some(_), None) => "dropped".into(),
+    }
+}
+
+fn view_score(score: Option<i64>) -> String {
+    score.map_or_else(|| "–".into(), |score| score.to_string())
+}
+
+/// the ranking the simulated scores give, next to the current one.
+pub fn view_ranking_diff(rows: &[ScoreDiffRow]) -> Html {
+    let current_ranks = ranks(&rows.iter().map(|row| row.current).collect::<Vec<_>>());
+    let simulated_ranks = ranks(&rows.iter().map(|row| row.simulated).collect::<Vec<_>>());
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    // unranked rows last
+    order.sort_by_key(|idx| (simulated_ranks[*idx].is_none(), simulated_ranks[*idx]));
+    html! {
+        <table>
+            <tr>
+                <th>{"rank"}</th>
+                <th>{"move"}</th>
+                <th>{"item"}</th>
+                <th>{"score"}</th>
+                <th>{"was"}</th>
+            </tr>
+            { for order.into_iter().map(|idx| {
+                let row = &rows[idx];
+                html! {
+                    <tr>
+                        <td>{ simulated_ranks[idx].map_or_else(|| "–".into(), |rank| rank.to_string()) }</td>
+                        <td>{ view_rank_move(current_ranks[idx], simulated_ranks[idx]) }</td>
+                        <td>{ row.label.clone() }</td>
+                        <td>{ view_score(row.simulated) }</td>
+                        <td>{ view_score(row.current) }</td>
+                    </tr>
+                }
+            })}
+        </table>
+    }
+}
+
+/// `preview`, with buttons to apply the change or drop it. the change can only be applied once the
+/// simulation has succeeded.
+pub fn view_score_preview<C>(
+    preview: &ScorePreview<C>,
+    title: String,
+    on_confirm: Callback<MouseEvent>,
+    on_cancel: Callback<MouseEvent>,
+) -> Html {
+    let (body_html, confirm_html) = match &preview.result {
+        None => (html! { <p>{"simulating…"}</p> }, html! {}),
+        Some(Err(err)) => (
+            html! { <p>{ format!("the simulation failed: {}", err) }</p> },
+            html! {},
+        ),
+        Some(Ok(scores)) => {
+            let failed_html = if scores.failed == 0 {
+                html! {}
+            } else {
+                html! { <p>{ format!("{} could not be simulated", scores.failed) }</p> }
+            };
+            (
+                html! {
+                    <>
+                        { view_ranking_diff(&scores.rows) }
+                        {failed_html}
+                    </>
+                },
+                html! { <button onclick={on_confirm}>{ "apply" }</button> },
+            )
+        }
+    };
+    html! {
+        <div class="score-preview">
+            <h3>{title}</h3>
+            {body_html}
+            {confirm_html}
+            <button onclick={on_cancel}>{ "cancel" }</button>
+        </div>
+    }
+}
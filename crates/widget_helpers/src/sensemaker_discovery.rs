@@ -0,0 +1,157 @@
+use wasm_bindgen::prelude::*;
+use weblog::console_error;
+
+use holochain_client_wrapper::{
+    AdminWebsocket, AdminWsCmd, AdminWsCmdResponse, AppWebsocket, AppWsCmd, AppWsCmdResponse,
+    CellId, DeserializeFromJsObj, SerializeToJsObj,
+};
+
+/// the role id happs give the sensemaker DNA.
+pub const SENSEMAKER_ROLE_ID: &str = "sensemaker";
+
+/// the app id the sensemaker used to be installed under, when it was always installed by hand.
+pub const LEGACY_SENSEMAKER_APP_ID: &str = "test-app";
+
+/// a sensemaker cell which a widget could be pointed at.
+#[derive(Clone, Debug)]
+pub struct SensemakerCandidate {
+    pub installed_app_id: String,
+    pub role_id: String,
+    pub cell_id: CellId,
+    /// whether this is the `SENSEMAKER_ROLE_ID` role of the widget's own happ.
+    pub bundled: bool,
+}
+
+impl SensemakerCandidate {
+    /// how the candidate is shown when the user has to pick one.
+    pub fn label(&self) -> String {
+        if self.bundled {
+            format!("{} / {} (bundled)", self.installed_app_id, self.role_id)
+        } else {
+            format!("{} / {}", self.installed_app_id, self.role_id)
+        }
+    }
+}
+
+/// whether `cell` of the app `installed_app_id`, which has `cell_count` cells, is a sensemaker:
+/// `known`, i.e. the widget's bundled or current sensemaker, which pins down its DNA hash; in the
+/// `SENSEMAKER_ROLE_ID` role; or the only cell of an app installed as `LEGACY_SENSEMAKER_APP_ID`.
+fn is_sensemaker_cell(
+    installed_app_id: &str,
+    cell_count: usize,
+    role_id: &str,
+    cell_id: &CellId,
+    known: Option<&CellId>,
+) -> bool {
+    known == Some(cell_id)
+        || role_id == SENSEMAKER_ROLE_ID
+        || (installed_app_id == LEGACY_SENSEMAKER_APP_ID && cell_count == 1)
+}
+
+/// every sensemaker cell of every active app. see `is_sensemaker_cell`. apps whose info can't be
+/// fetched are skipped, rather than failing the whole search.
+///
+/// the sensemaker role of the happ which `widget_cell_id` belongs to is marked `bundled`, so the
+/// UI can point the widget at it without the `sensemaker_dna_hash` property.
+pub async fn find_sensemaker_cells(
+    admin_ws: &AdminWebsocket,
+    app_ws: &AppWebsocket,
+    widget_cell_id: &CellId,
+    known: Option<&CellId>,
+) -> Result<Vec<SensemakerCandidate>, String> {
+    let active_apps = match admin_ws.call(AdminWsCmd::ListActiveApps).await {
+        Ok(AdminWsCmdResponse::ListActiveApps(active_apps)) => active_apps,
+        Ok(resp) => return Err(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => return Err(format!("err: {:?}", err)),
+    };
+    let mut candidates = Vec::new();
+    for installed_app_id in active_apps {
+        let cmd = AppWsCmd::AppInfo {
+            installed_app_id: installed_app_id.clone(),
+        };
+        let app_info = match app_ws.call(cmd).await {
+            Ok(AppWsCmdResponse::AppInfo(app_info)) => app_info,
+            Ok(resp) => {
+                console_error!(format!(
+                    "find_sensemaker_cells: skipping {}: invalid response: {:?}",
+                    installed_app_id, resp
+                ));
+                continue;
+            }
+            Err(err) => {
+                console_error!(format!(
+                    "find_sensemaker_cells: skipping {}: {:?}",
+                    installed_app_id, err
+                ));
+                continue;
+            }
+        };
+        let cell_count = app_info.cell_data.len();
+        let is_widget_app = app_info
+            .cell_data
+            .iter()
+            .any(|cell| cell.cell_id == *widget_cell_id);
+        for cell in app_info.cell_data {
+            if is_sensemaker_cell(
+                &installed_app_id,
+                cell_count,
+                &cell.role_id,
+                &cell.cell_id,
+                known,
+            ) {
+                candidates.push(SensemakerCandidate {
+                    installed_app_id: installed_app_id.clone(),
+                    bundled: is_widget_app && cell.role_id == SENSEMAKER_ROLE_ID,
+                    role_id: cell.role_id,
+                    cell_id: cell.cell_id,
+                });
+            }
+        }
+    }
+    Ok(candidates)
+}
+
+/// the sensemaker cell the widget zome `zome_name` uses: the one bundled into its happ if there is
+/// one, otherwise the one stored by `set_sensemaker_cell_id`. `Ok(None)` only if neither is.
+pub async fn get_sensemaker_cell_id(
+    app_ws: &AppWebsocket,
+    widget_cell_id: &CellId,
+    zome_name: &str,
+) -> Result<Option<CellId>, String> {
+    let cmd = AppWsCmd::CallZome {
+        cell_id: widget_cell_id.clone(),
+        zome_name: zome_name.into(),
+        fn_name: "get_current_sensemaker_cell_id".into(),
+        payload: JsValue::NULL,
+        provenance: widget_cell_id.1.clone(),
+        cap: "".into(),
+    };
+    match app_ws.call(cmd).await {
+        Ok(AppWsCmdResponse::CallZome(val)) if val.is_null() => Ok(None),
+        Ok(AppWsCmdResponse::CallZome(val)) => Ok(Some(CellId::deserialize_from_js_obj(val))),
+        Ok(resp) => Err(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => Err(format!("err: {:?}", err)),
+    }
+}
+
+/// point the widget zome `zome_name` at `sensemaker_cell_id`.
+pub async fn set_sensemaker_cell_id(
+    app_ws: &AppWebsocket,
+    widget_cell_id: &CellId,
+    zome_name: &str,
+    sensemaker_cell_id: CellId,
+) -> Result<(), String> {
+    let cmd = AppWsCmd::CallZome {
+        cell_id: widget_cell_id.clone(),
+        zome_name: zome_name.into(),
+        fn_name: "set_sensemaker_cell_id".into(),
+        payload: sensemaker_cell_id.serialize_to_js_obj(),
+        provenance: widget_cell_id.1.clone(),
+        cap: "".into(),
+    };
+    match app_ws.call(cmd).await {
+        Ok(AppWsCmdResponse::CallZome(_val)) => Ok(()),
+        Ok(resp) => Err(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => Err(format!("err: {:?}", err)),
+    }
+}
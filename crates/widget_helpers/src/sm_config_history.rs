@@ -0,0 +1,241 @@
+use js_sys::{Array, Date, Reflect, Uint8Array};
+use wasm_bindgen::{prelude::*, JsCast};
+use yew::{html, Callback, Html, MouseEvent};
+
+use holochain_client_wrapper::{AppWebsocket, AppWsCmd, AppWsCmdResponse, CellId};
+
+/// the UI-side mirror of `widget_sensemaker::SmConfigChange`.
+#[derive(Clone, Debug)]
+pub struct SmConfigChangeRaw {
+    /// `SM_INIT_TAG` or `SM_COMP_TAG`.
+    pub link_tag_string: String,
+    /// `None` for changes made outside the widget.
+    pub expr_str: Option<String>,
+    /// who made the change, or replaced it, if it was made outside the widget.
+    pub author_b64: String,
+    pub timestamp_micros: i64,
+    /// kept as it came, only to be handed back to `rollback_sm_expr`.
+    pub change_hh: JsValue,
+}
+
+impl SmConfigChangeRaw {
+    fn deserialize_from_js_obj(v: JsValue) -> Self {
+        let field = |name: &str| {
+            Reflect::get(&v, &JsValue::from_str(name)).expect("object field get to succeed")
+        };
+        Self {
+            link_tag_string: field("link_tag_string")
+                .as_string()
+                .expect("string conversion to succeed"),
+            expr_str: field("expr_str").as_string(),
+            author_b64: base64::encode(Uint8Array::new(&field("author")).to_vec()),
+            timestamp_micros: field("timestamp")
+                .as_f64()
+                .expect("number conversion to succeed") as i64,
+            change_hh: field("change_hh"),
+        }
+    }
+}
+
+/// how many changes `get_sm_config_history` fetches at a time.
+pub const SM_CONFIG_HISTORY_PAGE_SIZE: u32 = 20;
+
+/// the UI-side mirror of `widget_sensemaker::SmConfigHistoryPage`, or of several of them, once
+/// `append`ed.
+#[derive(Clone, Debug)]
+pub struct SmConfigHistoryRaw {
+    pub changes: Vec<SmConfigChangeRaw>,
+    /// the tags whose expression in use was set outside the widget.
+    pub set_outside_widget: Vec<String>,
+    /// kept as it came, only to be handed back to `get_sm_config_history`. `None` once every
+    /// change is loaded.
+    pub next_after: Option<JsValue>,
+}
+
+impl SmConfigHistoryRaw {
+    fn deserialize_from_js_obj(v: JsValue) -> Self {
+        let field = |name: &str| {
+            Reflect::get(&v, &JsValue::from_str(name)).expect("object field get to succeed")
+        };
+        let changes: Array = field("changes")
+            .dyn_into()
+            .expect("Array conversion to succeed");
+        let set_outside_widget: Array = field("set_outside_widget")
+            .dyn_into()
+            .expect("Array conversion to succeed");
+        let next_after = field("next_after");
+        Self {
+            changes: changes
+                .iter()
+                .map(SmConfigChangeRaw::deserialize_from_js_obj)
+                .collect(),
+            set_outside_widget: set_outside_widget
+                .iter()
+                .map(|tag| tag.as_string().expect("string conversion to succeed"))
+                .collect(),
+            next_after: if next_after.is_null() || next_after.is_undefined() {
+                None
+            } else {
+                Some(next_after)
+            },
+        }
+    }
+
+    /// add the next (older) page.
+    pub fn append(&mut self, older: SmConfigHistoryRaw) {
+        self.changes.extend(older.changes);
+        self.next_after = older.next_after;
+    }
+}
+
+/// a page of the sm_init & sm_comp changes made to `path_string` through the widget zome
+/// `zome_name`, newest first, starting after `after`, a `SmConfigHistoryRaw::next_after`, or with
+/// the newest change if that is `None`.
+pub async fn get_sm_config_history(
+    app_ws: &AppWebsocket,
+    widget_cell_id: &CellId,
+    zome_name: &str,
+    path_string: String,
+    after: Option<JsValue>,
+) -> Result<SmConfigHistoryRaw, String> {
+    let payload = Array::new();
+    let _ = payload.push(&JsValue::from_str(&path_string));
+    let _ = payload.push(&after.unwrap_or(JsValue::NULL));
+    let _ = payload.push(&JsValue::from(SM_CONFIG_HISTORY_PAGE_SIZE));
+    let cmd = AppWsCmd::CallZome {
+        cell_id: widget_cell_id.clone(),
+        zome_name: zome_name.into(),
+        fn_name: "get_sm_config_history".into(),
+        payload: payload.into(),
+        provenance: widget_cell_id.1.clone(),
+        cap: "".into(),
+    };
+    match app_ws.call(cmd).await {
+        Ok(AppWsCmdResponse::CallZome(val)) => Ok(SmConfigHistoryRaw::deserialize_from_js_obj(val)),
+        Ok(resp) => Err(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => Err(format!("err: {:?}", err)),
+    }
+}
+
+/// propose setting `path_string` back to what `change` set it to. `Ok(true)` if it was set,
+/// `Ok(false)` if the proposal awaits approval.
+pub async fn rollback_sm_expr(
+    app_ws: &AppWebsocket,
+    widget_cell_id: &CellId,
+    zome_name: &str,
+    path_string: String,
+    change: SmConfigChangeRaw,
+) -> Result<bool, String> {
+    let payload = Array::new();
+    let _ = payload.push(&JsValue::from_str(&path_string));
+    let _ = payload.push(&change.change_hh);
+    let cmd = AppWsCmd::CallZome {
+        cell_id: widget_cell_id.clone(),
+        zome_name: zome_name.into(),
+        fn_name: "rollback_sm_expr".into(),
+        payload: payload.into(),
+        provenance: widget_cell_id.1.clone(),
+        cap: "".into(),
+    };
+    match app_ws.call(cmd).await {
+        Ok(AppWsCmdResponse::CallZome(val)) => Ok(val.as_bool().unwrap_or(false)),
+        Ok(resp) => Err(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => Err(format!("err: {:?}", err)),
+    }
+}
+
+/// `history` as a table, newest first, with a button to roll back to each change but the one in
+/// use of its kind, and one to load older changes, if there are any. changes made outside the
+/// widget can't be rolled back to. `on_rollback` receives the index of the change.
+pub fn view_sm_config_history(
+    history: &SmConfigHistoryRaw,
+    own_agent_b64: &str,
+    on_rollback: Callback<usize>,
+    on_load_more: Callback<MouseEvent>,
+) -> Html {
+    if history.changes.is_empty() && history.set_outside_widget.is_empty() {
+        return html! { <p>{"no changes have been recorded."}</p> };
+    }
+    // the newest change of each kind is what is in use, unless it was set outside since
+    let mut seen_tags: Vec<&str> = history
+        .set_outside_widget
+        .iter()
+        .map(String::as_str)
+        .collect();
+    let load_more_html = if history.next_after.is_some() {
+        html! { <button onclick={on_load_more}>{ "older changes" }</button> }
+    } else {
+        html! {}
+    };
+    html! {
+        <div>
+            <table>
+                <tr>
+                    <th>{"when"}</th>
+                    <th>{"who"}</th>
+                    <th>{"what"}</th>
+                    <th>{"expression"}</th>
+                    <th></th>
+                </tr>
+                { for history.set_outside_widget.iter().map(|tag| html! {
+                    <tr>
+                        <td>{"–"}</td>
+                        <td>{"–"}</td>
+                        <td>{ tag.clone() }</td>
+                        <td><em>{"set outside this widget"}</em></td>
+                        <td><span>{"in use"}</span></td>
+                    </tr>
+                })}
+                { for history.changes.iter().enumerate().map(|(idx, change)| {
+                    let in_use = !seen_tags.contains(&change.link_tag_string.as_str());
+                    if in_use {
+                        seen_tags.push(&change.link_tag_string);
+                    }
+                    let on_rollback = on_rollback.clone();
+                    let (expr_html, action_html) = match &change.expr_str {
+                        Some(expr_str) => (
+                            html! { <code>{ expr_str.clone() }</code> },
+                            if in_use {
+                                html! { <span>{"in use"}</span> }
+                            } else {
+                                html! {
+                                    <button onclick={Callback::from(move |_: MouseEvent| on_rollback.emit(idx))}>{ "roll back to this" }</button>
+                                }
+                            },
+                        ),
+                        None => (
+                            html! { <em>{"set outside this widget"}</em> },
+                            html! { <span>{"replaced"}</span> },
+                        ),
+                    };
+                    html! {
+                        <tr>
+                            <td>{ view_timestamp(change.timestamp_micros) }</td>
+                            <td>{ agent_label(&change.author_b64, own_agent_b64) }</td>
+                            <td>{ change.link_tag_string.clone() }</td>
+                            <td>{expr_html}</td>
+                            <td>{action_html}</td>
+                        </tr>
+                    }
+                })}
+            </table>
+            {load_more_html}
+        </div>
+    }
+}
+
+/// how an agent is shown in the history. the hash prefix is the same for every agent, so the end of
+/// the key is shown.
+pub(crate) fn agent_label(agent_b64: &str, own_agent_b64: &str) -> String {
+    if agent_b64 == own_agent_b64 {
+        return "you".into();
+    }
+    let start = agent_b64.len().saturating_sub(8);
+    format!("…{}", &agent_b64[start..])
+}
+
+pub(crate) fn view_timestamp(timestamp_micros: i64) -> String {
+    Date::new(&JsValue::from_f64(timestamp_micros as f64 / 1000.0))
+        .to_iso_string()
+        .into()
+}
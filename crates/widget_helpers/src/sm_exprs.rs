@@ -0,0 +1,67 @@
+use js_sys::{Array, Reflect};
+use wasm_bindgen::{prelude::*, JsCast};
+use yew::{html, Html};
+
+/// the UI-side mirror of `widget_sensemaker::SmExpr`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SmExprRaw {
+    Unset,
+    Source(String),
+    SetOutsideWidget,
+}
+
+impl Default for SmExprRaw {
+    fn default() -> Self {
+        SmExprRaw::Unset
+    }
+}
+
+impl SmExprRaw {
+    /// serde encodes unit variants as their name, and the others as an object with a single key,
+    /// the variant name.
+    fn deserialize_from_js_obj(v: JsValue) -> Self {
+        match v.as_string().as_deref() {
+            Some("Unset") => return SmExprRaw::Unset,
+            Some("SetOutsideWidget") => return SmExprRaw::SetOutsideWidget,
+            _ => {}
+        }
+        let source = Reflect::get(&v, &JsValue::from_str("Source"))
+            .expect("object field get to succeed")
+            .as_string()
+            .expect("impossible: unknown SmExpr variant");
+        SmExprRaw::Source(source)
+    }
+
+    /// the source, if it is known.
+    pub fn source(&self) -> Option<&str> {
+        match self {
+            SmExprRaw::Source(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// the (sm_init, sm_comp) expressions of a path, as returned by `get_sm_exprs`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SmExprsRaw(pub SmExprRaw, pub SmExprRaw);
+
+impl SmExprsRaw {
+    pub fn deserialize_from_js_obj(v: JsValue) -> Self {
+        let arr: Array = v.dyn_into().expect("Array conversion to succeed");
+        SmExprsRaw(
+            SmExprRaw::deserialize_from_js_obj(arr.at(0)),
+            SmExprRaw::deserialize_from_js_obj(arr.at(1)),
+        )
+    }
+}
+
+/// a note under an expression input, for when the expression in use didn't come from the widget,
+/// and so isn't what the input shows.
+pub fn view_sm_expr_note(expr: &SmExprRaw, label: &str) -> Html {
+    match expr {
+        SmExprRaw::SetOutsideWidget => html! {
+            <p>{ format!("the {} in use was set outside this widget, so its source isn't known.", label) }</p>
+        },
+        _ => html! {},
+    }
+}
@@ -0,0 +1,55 @@
+use js_sys::{Array, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+
+use holochain_client_wrapper::{DeserializeFromJsObj, EntryHashRaw};
+
+/// the UI-side mirror of `widget_sensemaker::sm_ops::SmOpRetry`.
+pub struct SmOpRetryRaw {
+    pub applied: Vec<String>,
+    pub armed: Vec<String>,
+    pub errors: Vec<(EntryHashRaw, String)>,
+}
+
+impl SmOpRetryRaw {
+    /// operations armed by this retry are only applied by the next one, so it should be called
+    /// again until this is false.
+    pub fn needs_another_retry(&self) -> bool {
+        !self.armed.is_empty()
+    }
+}
+
+impl DeserializeFromJsObj for SmOpRetryRaw {
+    fn deserialize_from_js_obj(v: JsValue) -> Self {
+        let strings = |name: &str| -> Vec<String> {
+            js_array(
+                Reflect::get(&v, &JsValue::from_str(name)).expect("object field get to succeed"),
+            )
+            .iter()
+            .map(String::deserialize_from_js_obj)
+            .collect()
+        };
+        let applied = strings("applied");
+        let armed = strings("armed");
+        let errors = js_array(
+            Reflect::get(&v, &JsValue::from_str("errors")).expect("object field get to succeed"),
+        )
+        .iter()
+        .map(|pair| {
+            let pair = js_array(pair);
+            (
+                EntryHashRaw::deserialize_from_js_obj(pair.at(0)),
+                String::deserialize_from_js_obj(pair.at(1)),
+            )
+        })
+        .collect();
+        Self {
+            applied,
+            armed,
+            errors,
+        }
+    }
+}
+
+fn js_array(v: JsValue) -> Array {
+    v.dyn_into().expect("Array conversion to succeed")
+}
@@ -0,0 +1,155 @@
+use js_sys::{Array, Reflect, Uint8Array};
+use wasm_bindgen::{prelude::*, JsCast};
+use yew::{html, Callback, Html, MouseEvent};
+
+use holochain_client_wrapper::{AppWebsocket, AppWsCmd, AppWsCmdResponse, CellId};
+
+use crate::sm_config_history::{agent_label, view_timestamp};
+
+/// the UI-side mirror of `widget_sensemaker::SmProposal`.
+#[derive(Clone, Debug)]
+pub struct SmProposalRaw {
+    /// `SM_INIT_TAG` or `SM_COMP_TAG`.
+    pub link_tag_string: String,
+    pub expr_str: String,
+    pub proposer_b64: String,
+    pub timestamp_micros: i64,
+    pub approvals_b64: Vec<String>,
+    pub approvals_required: u32,
+    /// kept as it came, only to be handed back to `approve_sm_proposal`.
+    pub proposal_eh: JsValue,
+}
+
+fn agent_b64(v: &JsValue) -> String {
+    base64::encode(Uint8Array::new(v).to_vec())
+}
+
+impl SmProposalRaw {
+    fn deserialize_from_js_obj(v: JsValue) -> Self {
+        let field = |name: &str| {
+            Reflect::get(&v, &JsValue::from_str(name)).expect("object field get to succeed")
+        };
+        let approvals: Array = field("approvals")
+            .dyn_into()
+            .expect("Array conversion to succeed");
+        Self {
+            link_tag_string: field("link_tag_string")
+                .as_string()
+                .expect("string conversion to succeed"),
+            expr_str: field("expr_str")
+                .as_string()
+                .expect("string conversion to succeed"),
+            proposer_b64: agent_b64(&field("proposer")),
+            timestamp_micros: field("timestamp")
+                .as_f64()
+                .expect("number conversion to succeed") as i64,
+            approvals_b64: approvals.iter().map(|v| agent_b64(&v)).collect(),
+            approvals_required: field("approvals_required")
+                .as_f64()
+                .expect("number conversion to succeed") as u32,
+            proposal_eh: field("proposal_eh"),
+        }
+    }
+}
+
+/// the changes proposed to `path_string` through the widget zome `zome_name` which haven't been
+/// set yet, newest first.
+pub async fn get_sm_proposals(
+    app_ws: &AppWebsocket,
+    widget_cell_id: &CellId,
+    zome_name: &str,
+    path_string: String,
+) -> Result<Vec<SmProposalRaw>, String> {
+    let cmd = AppWsCmd::CallZome {
+        cell_id: widget_cell_id.clone(),
+        zome_name: zome_name.into(),
+        fn_name: "get_sm_proposals".into(),
+        payload: JsValue::from_str(&path_string),
+        provenance: widget_cell_id.1.clone(),
+        cap: "".into(),
+    };
+    match app_ws.call(cmd).await {
+        Ok(AppWsCmdResponse::CallZome(val)) => {
+            let arr: Array = val.dyn_into().expect("Array conversion to succeed");
+            Ok(arr
+                .iter()
+                .map(SmProposalRaw::deserialize_from_js_obj)
+                .collect())
+        }
+        Ok(resp) => Err(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => Err(format!("err: {:?}", err)),
+    }
+}
+
+/// `Ok(true)` if the approval was the last one `proposal` needed, and it was set.
+pub async fn approve_sm_proposal(
+    app_ws: &AppWebsocket,
+    widget_cell_id: &CellId,
+    zome_name: &str,
+    path_string: String,
+    proposal: SmProposalRaw,
+) -> Result<bool, String> {
+    let payload = Array::new();
+    let _ = payload.push(&JsValue::from_str(&path_string));
+    let _ = payload.push(&proposal.proposal_eh);
+    let cmd = AppWsCmd::CallZome {
+        cell_id: widget_cell_id.clone(),
+        zome_name: zome_name.into(),
+        fn_name: "approve_sm_proposal".into(),
+        payload: payload.into(),
+        provenance: widget_cell_id.1.clone(),
+        cap: "".into(),
+    };
+    match app_ws.call(cmd).await {
+        Ok(AppWsCmdResponse::CallZome(val)) => Ok(val.as_bool().unwrap_or(false)),
+        Ok(resp) => Err(format!("impossible: invalid response: {:?}", resp)),
+        Err(err) => Err(format!("err: {:?}", err)),
+    }
+}
+
+/// the pending `proposals`, with a button to approve each the agent hasn't approved yet.
+/// `on_approve` receives the index of the proposal.
+pub fn view_sm_proposals(
+    proposals: &[SmProposalRaw],
+    own_agent_b64: &str,
+    on_approve: Callback<usize>,
+) -> Html {
+    if proposals.is_empty() {
+        return html! {};
+    }
+    html! {
+        <div class="sm-proposals">
+            <h4>{"pending changes"}</h4>
+            <table>
+                <tr>
+                    <th>{"when"}</th>
+                    <th>{"who"}</th>
+                    <th>{"what"}</th>
+                    <th>{"expression"}</th>
+                    <th>{"approvals"}</th>
+                    <th></th>
+                </tr>
+                { for proposals.iter().enumerate().map(|(idx, proposal)| {
+                    let on_approve = on_approve.clone();
+                    let action_html = if proposal.approvals_b64.iter().any(|a| a == own_agent_b64) {
+                        html! { <span>{"approved"}</span> }
+                    } else {
+                        html! {
+                            <button onclick={Callback::from(move |_: MouseEvent| on_approve.emit(idx))}>{ "approve" }</button>
+                        }
+                    };
+                    html! {
+                        <tr>
+                            <td>{ view_timestamp(proposal.timestamp_micros) }</td>
+                            <td>{ agent_label(&proposal.proposer_b64, own_agent_b64) }</td>
+                            <td>{ proposal.link_tag_string.clone() }</td>
+                            <td><code>{ proposal.expr_str.clone() }</code></td>
+                            <td>{ format!("{}/{}", proposal.approvals_b64.len(), proposal.approvals_required) }</td>
+                            <td>{action_html}</td>
+                        </tr>
+                    }
+                })}
+            </table>
+        </div>
+    }
+}
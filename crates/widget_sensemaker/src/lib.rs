@@ -0,0 +1,1277 @@
+//! the sensemaker plumbing every widget zome needs. see `widget_sensemaker_fns`.
+
+use hdk::prelude::{
+    holo_hash::{AgentPubKeyB64, DnaHashB64},
+    *,
+};
+use rep_lang_runtime::eval::{FlatValue, Value};
+use widget_core::error::WidgetError;
+
+// re-exported for `widget_sensemaker_fns`, so that widget zomes needn't import them all.
+pub use common;
+pub use social_sensemaker_core;
+pub use widget_core;
+
+pub mod sm_ops;
+
+pub use widget_core::config::SensemakerProperties;
+
+/// see `widget_core::config::dna_properties`.
+pub fn sensemaker_properties() -> ExternResult<SensemakerProperties> {
+    widget_core::config::dna_properties()
+}
+
+/// the sensemaker paths a widget uses, and the link tags (e.g. `SM_COMP_TAG`) of them which its
+/// externs may set. see `widget_sensemaker_fns`.
+pub struct SmTargets {
+    pub paths: &'static [&'static str],
+    pub tags: &'static [&'static str],
+}
+
+impl SmTargets {
+    pub fn check_path(&self, path_string: &str) -> Result<(), WidgetError> {
+        if self.paths.contains(&path_string) {
+            Ok(())
+        } else {
+            Err(WidgetError::ValidationFailed(format!(
+                "{:?} is not one of this widget's sensemaker paths: {}",
+                path_string,
+                self.paths.join(", ")
+            )))
+        }
+    }
+
+    pub fn check_tag(&self, link_tag_string: &str) -> Result<(), WidgetError> {
+        if self.tags.contains(&link_tag_string) {
+            Ok(())
+        } else {
+            Err(WidgetError::ValidationFailed(format!(
+                "{:?} is not one of this widget's sensemaker tags: {}",
+                link_tag_string,
+                self.tags.join(", ")
+            )))
+        }
+    }
+}
+
+/// the agent's sensemaker cell, if the widget's happ bundles the sensemaker. cells of the same app
+/// share their agent, so the DNA hash is all that needs configuring.
+pub fn bundled_sensemaker_cell_id() -> ExternResult<Option<CellId>> {
+    let properties = sensemaker_properties()?;
+    let dna_hash_b64 = match properties.sensemaker_dna_hash {
+        None => return Ok(None),
+        Some(dna_hash_b64) => dna_hash_b64,
+    };
+    let dna_hash = DnaHashB64::from_b64_str(&dna_hash_b64).map_err(|err| {
+        WidgetError::InvalidProperties(format!("invalid sensemaker_dna_hash property: {}", err))
+    })?;
+    let agent_pk = agent_info()?.agent_initial_pubkey;
+    Ok(Some(CellId::new(dna_hash.into(), agent_pk)))
+}
+
+fn parse_agent(agent_b64: &str, property: &str) -> ExternResult<AgentPubKey> {
+    AgentPubKeyB64::from_b64_str(agent_b64)
+        .map(AgentPubKey::from)
+        .map_err(|err| {
+            WidgetError::InvalidProperties(format!("invalid {} property: {}", property, err)).into()
+        })
+}
+
+/// the agents who may configure the sensemaker: `SensemakerProperties::sm_admins`, or the
+/// `progenitor` if there are none. empty if neither is set, in which case anyone may. see
+/// `may_configure`.
+fn sm_admins(properties: &SensemakerProperties) -> ExternResult<Vec<AgentPubKey>> {
+    if properties.sm_admins.is_empty() {
+        return properties
+            .progenitor
+            .iter()
+            .map(|progenitor_b64| parse_agent(progenitor_b64, "progenitor"))
+            .collect();
+    }
+    properties
+        .sm_admins
+        .iter()
+        .map(|admin_b64| parse_agent(admin_b64, "sm_admins"))
+        .collect()
+}
+
+fn may_configure(admins: &[AgentPubKey], agent: &AgentPubKey) -> bool {
+    admins.is_empty() || admins.contains(agent)
+}
+
+/// whether `agent` may propose & approve changes to sm_init & sm_comp expressions.
+pub fn is_sm_admin(agent: &AgentPubKey) -> ExternResult<bool> {
+    Ok(may_configure(&sm_admins(&sensemaker_properties()?)?, agent))
+}
+
+/// the links from `base` tagged exactly `tag`. `get_links` matches tags by prefix, so links with a
+/// longer tag, which `validate_sm_link` needn't have vouched for, would otherwise be read too.
+pub fn get_exact_links(base: EntryHash, tag: &str) -> ExternResult<Vec<Link>> {
+    let tag = LinkTag::new(tag);
+    let mut links = get_links(base, Some(tag.clone()))?;
+    links.retain(|lnk| lnk.tag == tag);
+    Ok(links)
+}
+
+/// the author of the header which created `lnk`, `None` if it can't be fetched.
+fn link_author(lnk: &Link) -> Option<AgentPubKey> {
+    get(lnk.create_link_hash.clone(), GetOptions::content())
+        .ok()
+        .flatten()
+        .map(|element| element.header().author().clone())
+}
+
+/// the sensemaker only keeps the *evaluated* `rep_lang` expressions, so the source of each one set
+/// through a widget is kept alongside, in a `SmExprSource` linked from here.
+fn sm_expr_anchor(path_string: String) -> ExternResult<EntryHash> {
+    anchor("sm_exprs".into(), path_string)
+}
+
+/// the source of an expression set through a widget. it is linked from `sm_expr_anchor`, tagged
+/// with its `link_tag_string`.
+#[hdk_entry(id = "sm_expr_source")]
+#[derive(Clone)]
+pub struct SmExprSource {
+    /// `SM_INIT_TAG` or `SM_COMP_TAG`.
+    pub link_tag_string: String,
+    /// `None` if the expression was set outside the widget. see `record_outside_sm_expr`.
+    pub expr_str: Option<String>,
+    /// the sensemaker entry `expr_str` was set as. the source only describes the expression in use
+    /// while that entry is.
+    pub sm_entry_eh: EntryHash,
+}
+
+/// record that `expr_str` was set as the `link_tag_string` (e.g. `SM_INIT_TAG`) state of
+/// `path_string`, as the sensemaker entry `sm_entry_eh`. see `get_sm_expr`.
+pub fn record_sm_expr(
+    path_string: String,
+    link_tag_string: String,
+    expr_str: Option<String>,
+    sm_entry_eh: EntryHash,
+) -> ExternResult<()> {
+    let source = SmExprSource {
+        link_tag_string: link_tag_string.clone(),
+        expr_str,
+        sm_entry_eh,
+    };
+    create_entry(&source)?;
+    create_link(
+        sm_expr_anchor(path_string)?,
+        hash_entry(&source)?,
+        LinkType(0),
+        LinkTag::new(link_tag_string),
+    )?;
+    Ok(())
+}
+
+/// record the sensemaker entry `live_eh`, which is about to be replaced, as a change made outside
+/// the widget, unless it was set through one. otherwise that change would be lost from
+/// `get_sm_config_history`, as the sensemaker only has the entry in use.
+pub fn record_outside_sm_expr(
+    path_string: String,
+    link_tag_string: String,
+    live_eh: Option<EntryHash>,
+) -> ExternResult<()> {
+    let live_eh = match live_eh {
+        None => return Ok(()),
+        Some(live_eh) => live_eh,
+    };
+    if find_sm_expr_source(path_string.clone(), &link_tag_string, &live_eh)?.is_some() {
+        return Ok(());
+    }
+    record_sm_expr(path_string, link_tag_string, None, live_eh)
+}
+
+/// the links to the sources recorded for `path_string`, newest first.
+fn get_sm_expr_links(path_string: String) -> ExternResult<Vec<Link>> {
+    let tags = [
+        LinkTag::new(social_sensemaker_core::SM_INIT_TAG),
+        LinkTag::new(social_sensemaker_core::SM_COMP_TAG),
+    ];
+    let mut links = get_links(sm_expr_anchor(path_string)?, None)?;
+    links.retain(|lnk| tags.contains(&lnk.tag));
+    links.sort_by_key(|lnk| std::cmp::Reverse(lnk.timestamp));
+    Ok(links)
+}
+
+/// the source `lnk` points at, `None` if it can't be fetched.
+fn get_sm_expr_source(lnk: &Link) -> Option<SmExprSource> {
+    let source_eh = lnk.target.clone().into_entry_hash()?;
+    get(source_eh, GetOptions::content())
+        .ok()
+        .flatten()?
+        .entry()
+        .to_app_option()
+        .ok()
+        .flatten()
+}
+
+/// the newest source recorded for the `link_tag_string` of `path_string` as the sensemaker entry
+/// `sm_entry_eh`.
+fn find_sm_expr_source(
+    path_string: String,
+    link_tag_string: &str,
+    sm_entry_eh: &EntryHash,
+) -> ExternResult<Option<SmExprSource>> {
+    // the link tags are enough to skip the other kind's sources without fetching them
+    let tag = LinkTag::new(link_tag_string);
+    Ok(get_sm_expr_links(path_string)?
+        .iter()
+        .filter(|lnk| lnk.tag == tag)
+        .filter_map(get_sm_expr_source)
+        .find(|source| source.sm_entry_eh == *sm_entry_eh))
+}
+
+/// a path's sm_init or sm_comp, as far as the widget knows it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SmExpr {
+    /// nothing has been set.
+    Unset,
+    /// the source of the expression in use.
+    Source(String),
+    /// the expression in use was set on the sensemaker directly, so its source isn't known.
+    SetOutsideWidget,
+}
+
+/// the `link_tag_string` expression of `path_string`, given the sensemaker entry which is in use,
+/// as `get_sm_init` & `get_sm_comp` return it.
+pub fn get_sm_expr(
+    path_string: String,
+    link_tag_string: &str,
+    live_eh: Option<EntryHash>,
+) -> ExternResult<SmExpr> {
+    let live_eh = match live_eh {
+        None => return Ok(SmExpr::Unset),
+        Some(live_eh) => live_eh,
+    };
+    Ok(
+        match find_sm_expr_source(path_string, link_tag_string, &live_eh)? {
+            Some(SmExprSource {
+                expr_str: Some(expr_str),
+                ..
+            }) => SmExpr::Source(expr_str),
+            _ => SmExpr::SetOutsideWidget,
+        },
+    )
+}
+
+/// a change to a path's sm_init or sm_comp, as recorded by `record_sm_expr`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SmConfigChange {
+    /// `SM_INIT_TAG` or `SM_COMP_TAG`.
+    pub link_tag_string: String,
+    /// `None` if the change was made outside the widget, in which case it can't be rolled back to.
+    pub expr_str: Option<String>,
+    /// who made the change through the widget, or who replaced it, if it was made outside.
+    pub author: AgentPubKey,
+    pub timestamp: Timestamp,
+    /// the header of the recording link, which identifies the change.
+    pub change_hh: HeaderHash,
+}
+
+/// a page of `get_sm_config_history`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SmConfigHistoryPage {
+    pub changes: Vec<SmConfigChange>,
+    /// the tags (`SM_INIT_TAG` or `SM_COMP_TAG`) whose expression in use was set outside the
+    /// widget, after all of their `changes`. only on the first page.
+    pub set_outside_widget: Vec<String>,
+    /// the `change_hh` the next (older) page starts after, `None` if this is the last one.
+    pub next_after: Option<HeaderHash>,
+}
+
+/// the sensemaker entries in use for a path, by tag, as `get_sm_init` & `get_sm_comp` return them.
+pub type LiveSmEntries = Vec<(String, Option<EntryHash>)>;
+
+/// up to `limit` of the changes recorded for `path_string`, newest first, starting after the change
+/// `after`, or with the newest one if that is `None`. changes which can't be fetched, or whose
+/// author can't be, are skipped. `live` is what is in use, to tell which tags were last set outside
+/// the widget.
+pub fn get_sm_config_history(
+    path_string: String,
+    live: LiveSmEntries,
+    after: Option<HeaderHash>,
+    limit: u32,
+) -> ExternResult<SmConfigHistoryPage> {
+    let links = get_sm_expr_links(path_string.clone())?;
+    let start = match &after {
+        None => 0,
+        Some(after) => match links.iter().position(|lnk| lnk.create_link_hash == *after) {
+            Some(idx) => idx + 1,
+            None => {
+                return Err(WidgetError::EntryNotFound(format!(
+                    "get_sm_config_history: no change {} to {}",
+                    after, path_string
+                ))
+                .into())
+            }
+        },
+    };
+    let mut changes = Vec::new();
+    let mut next_after = None;
+    for lnk in &links[start..] {
+        if changes.len() as u32 >= limit {
+            next_after = changes
+                .last()
+                .map(|change: &SmConfigChange| change.change_hh.clone());
+            break;
+        }
+        let source = match get_sm_expr_source(lnk) {
+            Some(source) => source,
+            None => continue,
+        };
+        // the author is only on the link's header
+        let author = match link_author(lnk) {
+            Some(author) => author,
+            None => continue,
+        };
+        changes.push(SmConfigChange {
+            link_tag_string: source.link_tag_string,
+            expr_str: source.expr_str,
+            author,
+            timestamp: lnk.timestamp,
+            change_hh: lnk.create_link_hash.clone(),
+        });
+    }
+    let mut set_outside_widget = Vec::new();
+    if after.is_none() {
+        for (link_tag_string, live_eh) in live {
+            if get_sm_expr(path_string.clone(), &link_tag_string, live_eh)?
+                == SmExpr::SetOutsideWidget
+            {
+                set_outside_widget.push(link_tag_string);
+            }
+        }
+    }
+    Ok(SmConfigHistoryPage {
+        changes,
+        set_outside_widget,
+        next_after,
+    })
+}
+
+/// the change `change_hh` to `path_string`, as `get_sm_config_history` has it.
+pub fn get_sm_config_change(
+    path_string: String,
+    change_hh: HeaderHash,
+) -> ExternResult<Option<SmConfigChange>> {
+    let lnk = match get_sm_expr_links(path_string)?
+        .into_iter()
+        .find(|lnk| lnk.create_link_hash == change_hh)
+    {
+        None => return Ok(None),
+        Some(lnk) => lnk,
+    };
+    let source = match get_sm_expr_source(&lnk) {
+        None => return Ok(None),
+        Some(source) => source,
+    };
+    Ok(link_author(&lnk).map(|author| SmConfigChange {
+        link_tag_string: source.link_tag_string,
+        expr_str: source.expr_str,
+        author,
+        timestamp: lnk.timestamp,
+        change_hh,
+    }))
+}
+
+/// changes to sm_init & sm_comp expressions are proposed, then approved by admins, and set once
+/// enough have. each proposal is a `SmExprProposal`, linked from here.
+fn sm_proposal_anchor(path_string: String) -> ExternResult<EntryHash> {
+    anchor("sm_proposals".into(), path_string)
+}
+
+/// on the links from `sm_proposal_anchor` to each `SmExprProposal`.
+pub const SM_PROPOSAL_TAG: &str = "sm_proposal";
+/// on the links from a `SmExprProposal` to itself, one for each admin who approved it, the
+/// proposer included.
+pub const SM_APPROVAL_TAG: &str = "sm_approval";
+/// on the link from a `SmExprProposal` to itself, once it has been set.
+pub const SM_APPLIED_TAG: &str = "sm_applied";
+
+/// a proposed change to a path's sm_init or sm_comp.
+#[hdk_entry(id = "sm_expr_proposal")]
+#[derive(Clone)]
+pub struct SmExprProposal {
+    pub path_string: String,
+    /// `SM_INIT_TAG` or `SM_COMP_TAG`.
+    pub link_tag_string: String,
+    pub expr_str: String,
+    /// the sensemaker entry in use when the change was proposed. once another one is, the
+    /// proposal was approved against something it no longer replaces, so it is dropped.
+    pub base_eh: Option<EntryHash>,
+}
+
+/// a change to a path's sm_init or sm_comp which hasn't been set yet.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SmProposal {
+    /// `SM_INIT_TAG` or `SM_COMP_TAG`.
+    pub link_tag_string: String,
+    pub expr_str: String,
+    pub proposer: AgentPubKey,
+    pub timestamp: Timestamp,
+    /// the admins who approved, the proposer first.
+    pub approvals: Vec<AgentPubKey>,
+    pub approvals_required: u32,
+    /// the hash of the `SmExprProposal`, which identifies the proposal.
+    pub proposal_eh: EntryHash,
+}
+
+impl SmProposal {
+    pub fn is_approved(&self) -> bool {
+        self.approvals.len() as u32 >= self.approvals_required
+    }
+}
+
+/// the admins who approved `proposal_eh`, oldest first, with when they first did.
+fn get_sm_approvals(
+    proposal_eh: EntryHash,
+    admins: &[AgentPubKey],
+) -> ExternResult<Vec<(AgentPubKey, Timestamp)>> {
+    let mut links = get_exact_links(proposal_eh, SM_APPROVAL_TAG)?;
+    links.sort_by_key(|lnk| lnk.timestamp);
+    let mut approvals: Vec<(AgentPubKey, Timestamp)> = Vec::new();
+    for lnk in links {
+        match link_author(&lnk) {
+            Some(approver)
+                if may_configure(admins, &approver)
+                    && !approvals.iter().any(|(approved, _)| *approved == approver) =>
+            {
+                approvals.push((approver, lnk.timestamp))
+            }
+            _ => {}
+        }
+    }
+    Ok(approvals)
+}
+
+/// `proposal_eh` as a pending proposal for `path_string`, `None` if there is no such proposal, it
+/// has been set, or its base isn't in `live` any more.
+fn get_sm_proposal(
+    path_string: &str,
+    proposal_eh: EntryHash,
+    live: &LiveSmEntries,
+    properties: &SensemakerProperties,
+    admins: &[AgentPubKey],
+) -> ExternResult<Option<SmProposal>> {
+    let proposal: SmExprProposal = match get(proposal_eh.clone(), GetOptions::content())?
+        .and_then(|element| element.entry().to_app_option().ok().flatten())
+    {
+        None => return Ok(None),
+        Some(proposal) => proposal,
+    };
+    let is_current = live.iter().any(|(link_tag_string, live_eh)| {
+        *link_tag_string == proposal.link_tag_string && *live_eh == proposal.base_eh
+    });
+    if proposal.path_string != path_string || !is_current {
+        return Ok(None);
+    }
+    if !get_exact_links(proposal_eh.clone(), SM_APPLIED_TAG)?.is_empty() {
+        return Ok(None);
+    }
+    let approvals = get_sm_approvals(proposal_eh.clone(), admins)?;
+    let (proposer, timestamp) = match approvals.first() {
+        None => return Ok(None),
+        Some(first) => first.clone(),
+    };
+    Ok(Some(SmProposal {
+        link_tag_string: proposal.link_tag_string,
+        expr_str: proposal.expr_str,
+        proposer,
+        timestamp,
+        approvals: approvals
+            .into_iter()
+            .map(|(approver, _)| approver)
+            .collect(),
+        approvals_required: properties.sm_approvals_required,
+        proposal_eh,
+    }))
+}
+
+/// the proposals for `path_string` which haven't been set yet, newest first. proposals whose base
+/// isn't in `live` any more are dropped, and proposals & approvals by agents who aren't admins are
+/// ignored.
+pub fn get_sm_proposals(
+    path_string: String,
+    live: &LiveSmEntries,
+) -> ExternResult<Vec<SmProposal>> {
+    let properties = sensemaker_properties()?;
+    let admins = sm_admins(&properties)?;
+    let links = get_exact_links(sm_proposal_anchor(path_string.clone())?, SM_PROPOSAL_TAG)?;
+    let mut proposals: Vec<SmProposal> = Vec::new();
+    for lnk in links {
+        let proposal_eh = match lnk.target.into_entry_hash() {
+            None => continue,
+            Some(proposal_eh) => proposal_eh,
+        };
+        // admins who propose the same change link the same entry
+        if proposals
+            .iter()
+            .any(|proposal| proposal.proposal_eh == proposal_eh)
+        {
+            continue;
+        }
+        if let Some(proposal) =
+            get_sm_proposal(&path_string, proposal_eh, live, &properties, &admins)?
+        {
+            proposals.push(proposal);
+        }
+    }
+    proposals.sort_by_key(|proposal| std::cmp::Reverse(proposal.timestamp));
+    Ok(proposals)
+}
+
+/// propose `expr_str` as the `link_tag_string` state of `path_string`, in place of the sensemaker
+/// entry `base_eh`, with the agent's approval. if another admin already proposed the same, this
+/// approves theirs. checking that the agent is an admin, and that `expr_str` is well typed, is up
+/// to the caller.
+pub fn propose_sm_expr(
+    path_string: String,
+    link_tag_string: String,
+    expr_str: String,
+    base_eh: Option<EntryHash>,
+    live: &LiveSmEntries,
+) -> ExternResult<SmProposal> {
+    let proposal = SmExprProposal {
+        path_string: path_string.clone(),
+        link_tag_string: link_tag_string.clone(),
+        expr_str: expr_str.clone(),
+        base_eh,
+    };
+    let proposal_eh = hash_entry(&proposal)?;
+    if let Some(proposal) = approve_sm_proposal(path_string.clone(), proposal_eh.clone(), live)? {
+        return Ok(proposal);
+    }
+    create_entry(&proposal)?;
+    create_link(
+        sm_proposal_anchor(path_string)?,
+        proposal_eh.clone(),
+        LinkType(0),
+        LinkTag::new(SM_PROPOSAL_TAG),
+    )?;
+    create_link(
+        proposal_eh.clone(),
+        proposal_eh.clone(),
+        LinkType(0),
+        LinkTag::new(SM_APPROVAL_TAG),
+    )?;
+    let proposer = agent_info()?.agent_initial_pubkey;
+    Ok(SmProposal {
+        link_tag_string,
+        expr_str,
+        proposer: proposer.clone(),
+        timestamp: sys_time()?,
+        approvals: vec![proposer],
+        approvals_required: sensemaker_properties()?.sm_approvals_required,
+        proposal_eh,
+    })
+}
+
+/// add the agent's approval to the pending proposal `proposal_eh`, `None` if there is no such
+/// proposal. checking that the agent is an admin is up to the caller.
+pub fn approve_sm_proposal(
+    path_string: String,
+    proposal_eh: EntryHash,
+    live: &LiveSmEntries,
+) -> ExternResult<Option<SmProposal>> {
+    let properties = sensemaker_properties()?;
+    let admins = sm_admins(&properties)?;
+    let mut proposal = match get_sm_proposal(
+        &path_string,
+        proposal_eh.clone(),
+        live,
+        &properties,
+        &admins,
+    )? {
+        None => return Ok(None),
+        Some(proposal) => proposal,
+    };
+    let approver = agent_info()?.agent_initial_pubkey;
+    if !proposal.approvals.contains(&approver) {
+        create_link(
+            proposal_eh.clone(),
+            proposal_eh,
+            LinkType(0),
+            LinkTag::new(SM_APPROVAL_TAG),
+        )?;
+        proposal.approvals.push(approver);
+    }
+    Ok(Some(proposal))
+}
+
+/// take `proposal_eh` off the pending proposals, once it has been set.
+pub fn record_sm_proposal_applied(proposal_eh: EntryHash) -> ExternResult<()> {
+    create_link(
+        proposal_eh.clone(),
+        proposal_eh,
+        LinkType(0),
+        LinkTag::new(SM_APPLIED_TAG),
+    )?;
+    Ok(())
+}
+
+/// the tags of the links only admins may create: proposals, approvals, and the sources of
+/// expressions they set.
+pub const SM_ADMIN_TAGS: &[&str] = &[
+    SM_PROPOSAL_TAG,
+    SM_APPROVAL_TAG,
+    SM_APPLIED_TAG,
+    social_sensemaker_core::SM_INIT_TAG,
+    social_sensemaker_core::SM_COMP_TAG,
+];
+
+/// whether `tag` is one of `admin_tags`, or one of them with more on the end, which `get_links`
+/// would match all the same.
+fn is_admin_tag(tag: &LinkTag, admin_tags: &[&str]) -> bool {
+    admin_tags
+        .iter()
+        .any(|admin_tag| tag.0.starts_with(admin_tag.as_bytes()))
+}
+
+/// only admins may create links tagged with one of `SM_ADMIN_TAGS`, or the widget's own
+/// `admin_tags`. for a zome's `validate`, on `Op::RegisterCreateLink`.
+pub fn validate_sm_link(
+    create_link: &CreateLink,
+    admin_tags: &[&str],
+) -> ExternResult<ValidateCallbackResult> {
+    if !is_admin_tag(&create_link.tag, SM_ADMIN_TAGS) && !is_admin_tag(&create_link.tag, admin_tags)
+    {
+        return Ok(ValidateCallbackResult::Valid);
+    }
+    if is_sm_admin(&create_link.author)? {
+        Ok(ValidateCallbackResult::Valid)
+    } else {
+        Ok(ValidateCallbackResult::Invalid(format!(
+            "only sm_admins may create {} links",
+            String::from_utf8_lossy(&create_link.tag.0)
+        )))
+    }
+}
+
+/// `x` as `rep_lang` source, which has no negative literals.
+pub fn int_expr(x: i64) -> String {
+    if x < 0 {
+        format!("(- 0 {})", x.unsigned_abs())
+    } else {
+        x.to_string()
+    }
+}
+
+/// `f` applied to `args`, as `rep_lang` source.
+pub fn apply_expr(f: &str, args: &[String]) -> String {
+    if args.is_empty() {
+        f.to_string()
+    } else {
+        format!("({} {})", f, args.join(" "))
+    }
+}
+
+/// the integer `flat_value` holds, if it is one.
+pub fn int_value(flat_value: &FlatValue) -> Option<i64> {
+    match flat_value {
+        FlatValue(Value::VInt(x)) => Some(*x),
+        _ => None,
+    }
+}
+
+/// evaluate `expr_str` in place. unlike `common::create_sensemaker_entry_parse`, nothing is
+/// committed, so this is fit for previews.
+pub fn eval_value(expr_str: String) -> ExternResult<FlatValue> {
+    let se = common::mk_sensemaker_entry_parse(common::CreateSensemakerEntryInputParse {
+        expr: expr_str,
+        args: vec![],
+    })?;
+    Ok(se.output_flat_value)
+}
+
+/// like `eval_value`, `None` if the result isn't an integer.
+pub fn eval_int(expr_str: String) -> ExternResult<Option<i64>> {
+    Ok(int_value(&eval_value(expr_str)?))
+}
+
+/// the state `sm_comp` steps `state` to under `act`, without committing anything.
+pub fn step_value(sm_comp: &str, state: FlatValue, act: &str) -> ExternResult<FlatValue> {
+    let se = common::mk_sensemaker_entry_parse(common::CreateSensemakerEntryInputParse {
+        expr: format!("(lam [st] ({} st {}))", sm_comp, act),
+        args: vec![common::SensemakerOperand::OtherOperand(state)],
+    })?;
+    Ok(se.output_flat_value)
+}
+
+/// parse & typecheck `expr_str` as the `link_tag_string` state of a path whose sm_init is
+/// `sm_init`, so that what can't be set is rejected before anyone approves it. an sm_comp must
+/// take that state as its first argument.
+pub fn check_sm_expr(
+    link_tag_string: &str,
+    expr_str: &str,
+    sm_init: FlatValue,
+) -> ExternResult<()> {
+    let checked = if link_tag_string == social_sensemaker_core::SM_COMP_TAG {
+        common::mk_sensemaker_entry_parse(common::CreateSensemakerEntryInputParse {
+            expr: format!("(lam [st] ({} st))", expr_str),
+            args: vec![common::SensemakerOperand::OtherOperand(sm_init)],
+        })
+    } else {
+        common::mk_sensemaker_entry_parse(common::CreateSensemakerEntryInputParse {
+            expr: expr_str.into(),
+            args: vec![],
+        })
+    };
+    checked.map(|_se| ()).map_err(|err| {
+        WidgetError::InvalidExpression(format!("{}: {}", link_tag_string, err)).into()
+    })
+}
+
+/// the state `sm_comp` steps `init` to under `acts`, in order. each step is evaluated on its own,
+/// from the value the last one left, so long histories don't make for deeply nested expressions.
+pub fn replay(init: FlatValue, sm_comp: &str, acts: &[String]) -> ExternResult<FlatValue> {
+    acts.iter()
+        .try_fold(init, |state, act| step_value(sm_comp, state, act))
+}
+
+/// generates a widget zome's sensemaker plumbing, given its sensemaker `paths` and the `tags` of
+/// them which admins may set, e.g.
+///
+/// ```ignore
+/// widget_sensemaker_fns! {
+///     paths: [ANNOTATIONZ_PATH, AGENT_PATH],
+///     tags: [SM_INIT_TAG, SM_COMP_TAG],
+/// }
+/// ```
+///
+/// the externs reject other paths & tags. see `SmTargets`. they are:
+/// - `sensemaker_cell_id` & `sensemaker_error`, which report failures as `WidgetError`s
+/// - the `get_bundled_sensemaker_cell_id` & `get_current_sensemaker_cell_id` externs. see
+///   `bundled_sensemaker_cell_id`.
+/// - `get_sm_generic` & `get_sm_generic_with_hh`, for the widget's own sensemaker lookups
+/// - the `get_sm_init`, `get_sm_comp`, `get_sm_exprs`, `set_sm_init`, `set_sm_comp`,
+///   `step_sm_remote` & `step_sm_path_remote` externs
+/// - the `get_sm_config_history` & `rollback_sm_expr` externs, and `live_sm_entries`. see
+///   `get_sm_config_history`.
+/// - the `get_sm_proposals` & `approve_sm_proposal` externs, and `ensure_sm_admin`. see
+///   `get_sm_proposals`.
+/// - `run_sm_op`, and the `get_pending_sm_ops`, `get_failed_sm_ops` & `retry_pending_sm_ops`
+///   externs. see `sm_ops::SmOpQueue`.
+///
+/// `set_sm_init`, `set_sm_comp` & `rollback_sm_expr` only propose the change, which is set once
+/// `SensemakerProperties::sm_approvals_required` admins have approved it. they return whether it
+/// was set. expressions which don't typecheck aren't proposed. see `check_sm_expr`.
+///
+/// the zome must also invoke `sensemaker_cell_id_fns! {}`, import `hdk::prelude::*`, list
+/// `SmExprSource` & `SmExprProposal`, and `PendingSmOp`, `ArmedSmOp`, `CompletedSmOp` &
+/// `FailedSmOp` from `sm_ops`, in its `entry_defs!`, and check links with `validate_sm_link` in its `validate`. links
+/// of the widget's own admin-only tags should be read with `get_exact_links`.
+#[macro_export]
+macro_rules! widget_sensemaker_fns {
+    (paths: [$($path:expr),* $(,)?], tags: [$($tag:expr),* $(,)?] $(,)?) => {
+        const SM_TARGETS: $crate::SmTargets = $crate::SmTargets {
+            paths: &[$($path),*],
+            tags: &[$($tag),*],
+        };
+
+        /// like `get_current_sensemaker_cell_id`, with a missing cell id reported as a widget
+        /// error.
+        fn sensemaker_cell_id() -> ExternResult<CellId> {
+            get_current_sensemaker_cell_id(())?.ok_or_else(|| {
+                $crate::widget_core::error::WidgetError::SensemakerCellNotSet.into()
+            })
+        }
+
+        #[hdk_extern]
+        /// the bundled sensemaker cell if there is one, otherwise the one stored by
+        /// `set_sensemaker_cell_id`. `None` only if neither is, other failures are passed on.
+        fn get_current_sensemaker_cell_id(_: ()) -> ExternResult<Option<CellId>> {
+            if let Some(cell_id) = $crate::bundled_sensemaker_cell_id()? {
+                return Ok(Some(cell_id));
+            }
+            if get_links(sensemaker_cell_id_anchor()?, None)?.is_empty() {
+                return Ok(None);
+            }
+            get_sensemaker_cell_id(()).map(Some)
+        }
+
+        #[hdk_extern]
+        fn get_bundled_sensemaker_cell_id(_: ()) -> ExternResult<Option<CellId>> {
+            $crate::bundled_sensemaker_cell_id()
+        }
+
+        /// see `WidgetError::from_sensemaker_call`.
+        fn sensemaker_error(err: WasmError) -> WasmError {
+            $crate::widget_core::error::WidgetError::from_sensemaker_call(err.to_string()).into()
+        }
+
+        #[hdk_extern]
+        fn get_sm_init(
+            path_string: String,
+        ) -> ExternResult<Option<(EntryHash, $crate::common::SensemakerEntry)>> {
+            SM_TARGETS.check_path(&path_string)?;
+            get_sm_generic(
+                path_string,
+                $crate::social_sensemaker_core::SM_INIT_TAG.into(),
+            )
+        }
+
+        #[hdk_extern]
+        fn get_sm_comp(
+            path_string: String,
+        ) -> ExternResult<Option<(EntryHash, $crate::common::SensemakerEntry)>> {
+            SM_TARGETS.check_path(&path_string)?;
+            get_sm_generic(
+                path_string,
+                $crate::social_sensemaker_core::SM_COMP_TAG.into(),
+            )
+        }
+
+        fn get_sm_generic(
+            path_string: String,
+            link_tag_string: String,
+        ) -> ExternResult<Option<(EntryHash, $crate::common::SensemakerEntry)>> {
+            let cell_id = sensemaker_cell_id()?;
+            $crate::common::remote_get_sensemaker_entry_by_path(
+                cell_id,
+                None,
+                (path_string, link_tag_string),
+            )
+            .map_err(sensemaker_error)
+        }
+
+        fn get_sm_generic_with_hh(
+            path_string: String,
+            link_tag_string: String,
+        ) -> ExternResult<Option<(EntryHash, HeaderHash, $crate::common::SensemakerEntry)>> {
+            let cell_id = sensemaker_cell_id()?;
+            $crate::common::remote_get_sensemaker_entry_by_path_with_hh(
+                cell_id,
+                None,
+                (path_string, link_tag_string),
+            )
+            .map_err(sensemaker_error)
+        }
+
+        #[hdk_extern]
+        /// set the sm_init state for the path_string to the `rep_lang` interpretation of `expr_str`
+        pub fn set_sm_init((path_string, expr_str): (String, String)) -> ExternResult<bool> {
+            propose_sensemaker_entry(
+                path_string,
+                $crate::social_sensemaker_core::SM_INIT_TAG.into(),
+                expr_str,
+            )
+        }
+
+        #[hdk_extern]
+        /// set the sm_comp state for the path_string to the `rep_lang` interpretation of `expr_str`
+        pub fn set_sm_comp((path_string, expr_str): (String, String)) -> ExternResult<bool> {
+            propose_sensemaker_entry(
+                path_string,
+                $crate::social_sensemaker_core::SM_COMP_TAG.into(),
+                expr_str,
+            )
+        }
+
+        fn set_sensemaker_entry(
+            path_string: String,
+            link_tag_string: String,
+            expr_str: String,
+        ) -> ExternResult<bool> {
+            let cell_id = sensemaker_cell_id()?;
+            let live_eh = get_sm_generic(path_string.clone(), link_tag_string.clone())?
+                .map(|(eh, _se)| eh);
+            $crate::record_outside_sm_expr(path_string.clone(), link_tag_string.clone(), live_eh)?;
+            $crate::common::remote_set_sensemaker_entry_parse_rl_expr(
+                cell_id,
+                None,
+                (
+                    path_string.clone(),
+                    link_tag_string.clone(),
+                    expr_str.clone(),
+                ),
+            )
+            .map_err(sensemaker_error)?;
+            let (sm_entry_eh, _se) = get_sm_generic(path_string.clone(), link_tag_string.clone())?
+                .ok_or_else(|| {
+                    WasmError::from($crate::widget_core::error::WidgetError::EntryNotFound(
+                        format!("{} of {} once set", link_tag_string, path_string),
+                    ))
+                })?;
+            $crate::record_sm_expr(path_string, link_tag_string, Some(expr_str), sm_entry_eh)?;
+            Ok(true)
+        }
+
+        /// only the `sm_admins` in the DNA properties, or the `progenitor` if there are none, may
+        /// change how the community is scored. the sensemaker itself doesn't check this, so it
+        /// only holds for changes made through the widget. see `widget_sensemaker::is_sm_admin`.
+        fn ensure_sm_admin() -> ExternResult<()> {
+            if $crate::is_sm_admin(&agent_info()?.agent_initial_pubkey)? {
+                Ok(())
+            } else {
+                Err($crate::widget_core::error::WidgetError::NotAuthorized(
+                    "only sm_admins, or the progenitor if there are none, may change sensemaker expressions"
+                        .into(),
+                )
+                .into())
+            }
+        }
+
+        fn propose_sensemaker_entry(
+            path_string: String,
+            link_tag_string: String,
+            expr_str: String,
+        ) -> ExternResult<bool> {
+            SM_TARGETS.check_path(&path_string)?;
+            SM_TARGETS.check_tag(&link_tag_string)?;
+            ensure_sm_admin()?;
+            let sm_init = match get_sm_init(path_string.clone())? {
+                Some((_eh, se)) => se.output_flat_value,
+                None => $crate::eval_value(
+                    $crate::widget_core::config::STARTER_SM_INIT_EXPR.into(),
+                )?,
+            };
+            $crate::check_sm_expr(&link_tag_string, &expr_str, sm_init)?;
+            let base_eh =
+                get_sm_generic(path_string.clone(), link_tag_string.clone())?.map(|(eh, _se)| eh);
+            let live = live_sm_entries(path_string.clone())?;
+            let proposal = $crate::propose_sm_expr(
+                path_string.clone(),
+                link_tag_string,
+                expr_str,
+                base_eh,
+                &live,
+            )?;
+            set_sm_proposal_if_approved(path_string, proposal)
+        }
+
+        fn set_sm_proposal_if_approved(
+            path_string: String,
+            proposal: $crate::SmProposal,
+        ) -> ExternResult<bool> {
+            if !proposal.is_approved() {
+                return Ok(false);
+            }
+            set_sensemaker_entry(path_string, proposal.link_tag_string, proposal.expr_str)?;
+            $crate::record_sm_proposal_applied(proposal.proposal_eh)?;
+            Ok(true)
+        }
+
+        #[hdk_extern]
+        /// see `widget_sensemaker::get_sm_proposals`.
+        fn get_sm_proposals(path_string: String) -> ExternResult<Vec<$crate::SmProposal>> {
+            SM_TARGETS.check_path(&path_string)?;
+            let live = live_sm_entries(path_string.clone())?;
+            $crate::get_sm_proposals(path_string, &live)
+        }
+
+        #[hdk_extern]
+        /// approve the pending proposal `proposal_eh`, setting it if that makes enough approvals.
+        /// returns whether it was set.
+        fn approve_sm_proposal(
+            (path_string, proposal_eh): (String, EntryHash),
+        ) -> ExternResult<bool> {
+            SM_TARGETS.check_path(&path_string)?;
+            ensure_sm_admin()?;
+            let live = live_sm_entries(path_string.clone())?;
+            let proposal =
+                $crate::approve_sm_proposal(path_string.clone(), proposal_eh.clone(), &live)?
+                    .ok_or_else(|| {
+                        WasmError::from($crate::widget_core::error::WidgetError::EntryNotFound(
+                            format!(
+                                "approve_sm_proposal: no pending proposal {} for {}",
+                                proposal_eh, path_string
+                            ),
+                        ))
+                    })?;
+            set_sm_proposal_if_approved(path_string, proposal)
+        }
+
+        /// the sensemaker entries in use for `path_string`. see `widget_sensemaker::LiveSmEntries`.
+        fn live_sm_entries(path_string: String) -> ExternResult<$crate::LiveSmEntries> {
+            Ok(vec![
+                (
+                    $crate::social_sensemaker_core::SM_INIT_TAG.into(),
+                    get_sm_init(path_string.clone())?.map(|(eh, _se)| eh),
+                ),
+                (
+                    $crate::social_sensemaker_core::SM_COMP_TAG.into(),
+                    get_sm_comp(path_string)?.map(|(eh, _se)| eh),
+                ),
+            ])
+        }
+
+        #[hdk_extern]
+        /// (sm_init, sm_comp) for `path_string`, as `rep_lang` source. see
+        /// `widget_sensemaker::get_sm_expr`.
+        fn get_sm_exprs(
+            path_string: String,
+        ) -> ExternResult<($crate::SmExpr, $crate::SmExpr)> {
+            let sm_init_eh = get_sm_init(path_string.clone())?.map(|(eh, _se)| eh);
+            let sm_comp_eh = get_sm_comp(path_string.clone())?.map(|(eh, _se)| eh);
+            Ok((
+                $crate::get_sm_expr(
+                    path_string.clone(),
+                    $crate::social_sensemaker_core::SM_INIT_TAG,
+                    sm_init_eh,
+                )?,
+                $crate::get_sm_expr(
+                    path_string,
+                    $crate::social_sensemaker_core::SM_COMP_TAG,
+                    sm_comp_eh,
+                )?,
+            ))
+        }
+
+        #[hdk_extern]
+        /// see `widget_sensemaker::get_sm_config_history`.
+        fn get_sm_config_history(
+            (path_string, after, limit): (String, Option<HeaderHash>, u32),
+        ) -> ExternResult<$crate::SmConfigHistoryPage> {
+            SM_TARGETS.check_path(&path_string)?;
+            let live = live_sm_entries(path_string.clone())?;
+            $crate::get_sm_config_history(path_string, live, after, limit)
+        }
+
+        #[hdk_extern]
+        /// propose setting `path_string`'s sm_init or sm_comp back to what the change `change_hh`
+        /// set it to. the rollback is recorded as a change of its own. changes made outside the
+        /// widget can't be rolled back to, as their source isn't known.
+        fn rollback_sm_expr((path_string, change_hh): (String, HeaderHash)) -> ExternResult<bool> {
+            SM_TARGETS.check_path(&path_string)?;
+            let change = $crate::get_sm_config_change(path_string.clone(), change_hh.clone())?
+                .ok_or_else(|| {
+                    WasmError::from($crate::widget_core::error::WidgetError::EntryNotFound(
+                        format!(
+                            "rollback_sm_expr: no change {} to {}",
+                            change_hh, path_string
+                        ),
+                    ))
+                })?;
+            let expr_str = change.expr_str.ok_or_else(|| {
+                WasmError::from($crate::widget_core::error::WidgetError::InvalidExpression(
+                    format!(
+                        "rollback_sm_expr: change {} was made outside the widget, so its source isn't known",
+                        change_hh
+                    ),
+                ))
+            })?;
+            propose_sensemaker_entry(path_string, change.link_tag_string, expr_str)
+        }
+
+        #[hdk_extern]
+        fn step_sm_remote(
+            (path_string, entry_hash, act): (String, EntryHash, String),
+        ) -> ExternResult<()> {
+            SM_TARGETS.check_path(&path_string)?;
+            let cell_id = sensemaker_cell_id()?;
+            $crate::common::remote_step_sm(cell_id, None, (path_string, entry_hash, act))
+                .map_err(sensemaker_error)
+        }
+
+        #[hdk_extern]
+        fn step_sm_path_remote(
+            (path_string, target, act): (String, String, String),
+        ) -> ExternResult<()> {
+            SM_TARGETS.check_path(&path_string)?;
+            let cell_id = sensemaker_cell_id()?;
+            $crate::common::remote_step_sm_path(cell_id, None, (path_string, target, act))
+                .map_err(sensemaker_error)
+        }
+
+        fn sm_op_store() -> ExternResult<$crate::sm_ops::ChainSmOpStore> {
+            Ok($crate::sm_ops::ChainSmOpStore {
+                pending_type: entry_type!(PendingSmOp)?,
+                armed_type: entry_type!(ArmedSmOp)?,
+                completed_type: entry_type!(CompletedSmOp)?,
+                failed_type: entry_type!(FailedSmOp)?,
+            })
+        }
+
+        /// the sensemaker cell, as `SmOpQueue` sees it.
+        struct SmOpSensemaker;
+
+        impl $crate::sm_ops::SmOpRemote for SmOpSensemaker {
+            fn apply(&self, op: &$crate::sm_ops::SmOp) -> ExternResult<()> {
+                apply_sm_op(op.clone())
+            }
+
+            fn sm_data_hh(&self, op: &$crate::sm_ops::SmOp) -> ExternResult<Option<HeaderHash>> {
+                Ok(get_sm_generic_with_hh(
+                    op.sm_data_path(),
+                    $crate::social_sensemaker_core::SM_DATA_TAG.into(),
+                )?
+                .map(|(_eh, hh, _sm_entry)| hh))
+            }
+        }
+
+        fn with_sm_op_queue<T>(
+            f: impl FnOnce(&$crate::sm_ops::SmOpQueue) -> ExternResult<T>,
+        ) -> ExternResult<T> {
+            let store = sm_op_store()?;
+            f(&$crate::sm_ops::SmOpQueue {
+                store: &store,
+                remote: &SmOpSensemaker,
+            })
+        }
+
+        fn apply_sm_op(op: $crate::sm_ops::SmOp) -> ExternResult<()> {
+            let cell_id = sensemaker_cell_id()?;
+            match op {
+                $crate::sm_ops::SmOp::InitSmData(path_string, target_eh) => {
+                    // e.g. if `reconcile_sm_data` got there first
+                    let sm_data_path =
+                        $crate::common::compose_entry_hash_path(&path_string, target_eh.clone());
+                    if get_sm_generic(
+                        sm_data_path,
+                        $crate::social_sensemaker_core::SM_DATA_TAG.into(),
+                    )?
+                    .is_some()
+                    {
+                        return Ok(());
+                    }
+                    $crate::common::remote_initialize_sm_data(
+                        cell_id,
+                        None,
+                        (path_string, target_eh),
+                    )
+                }
+                $crate::sm_ops::SmOp::StepSm(path_string, target_eh, act) => {
+                    $crate::common::remote_step_sm(cell_id, None, (path_string, target_eh, act))
+                }
+                $crate::sm_ops::SmOp::StepSmPath(path_string, target, act) => {
+                    $crate::common::remote_step_sm_path(cell_id, None, (path_string, target, act))
+                }
+            }
+            .map_err(sensemaker_error)
+        }
+
+        /// see `SmOpQueue::run`. it fails if the sensemaker rejects `op`, so the zome call which
+        /// caused it fails too.
+        fn run_sm_op(op: $crate::sm_ops::SmOp) -> ExternResult<()> {
+            with_sm_op_queue(|queue| queue.run(op))
+        }
+
+        #[hdk_extern]
+        /// the sensemaker operations which are yet to be applied, oldest first.
+        fn get_pending_sm_ops(
+            _: (),
+        ) -> ExternResult<Vec<(EntryHash, $crate::sm_ops::PendingSmOp)>> {
+            with_sm_op_queue(|queue| queue.pending())
+        }
+
+        #[hdk_extern]
+        /// the sensemaker operations which were given up on, with why, oldest first.
+        fn get_failed_sm_ops(_: ()) -> ExternResult<Vec<($crate::sm_ops::PendingSmOp, String)>> {
+            with_sm_op_queue(|queue| queue.failed())
+        }
+
+        #[hdk_extern]
+        /// see `SmOpQueue::retry`.
+        fn retry_pending_sm_ops(_: ()) -> ExternResult<$crate::sm_ops::SmOpRetry> {
+            with_sm_op_queue(|queue| queue.retry())
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use social_sensemaker_core::{SM_COMP_TAG, SM_INIT_TAG};
+
+    // the externs `widget_sensemaker_fns` generates only differ between widgets in their
+    // `SM_TARGETS`, so these are the widgets' own.
+    const MEMEZ_TARGETS: SmTargets = SmTargets {
+        paths: &[
+            memez_core::MEMEZ_PATH,
+            memez_core::MODERATION_PATH,
+            memez_core::BATTLE_PATH,
+        ],
+        tags: &[SM_INIT_TAG, SM_COMP_TAG],
+    };
+    const PAPERZ_TARGETS: SmTargets = SmTargets {
+        paths: &[paperz_core::ANNOTATIONZ_PATH, paperz_core::AGENT_PATH],
+        tags: &[SM_INIT_TAG, SM_COMP_TAG],
+    };
+
+    #[test]
+    fn targets_accept_only_their_widgets_paths() {
+        for targets in [&MEMEZ_TARGETS, &PAPERZ_TARGETS] {
+            for path in targets.paths {
+                assert_eq!(targets.check_path(path), Ok(()));
+            }
+        }
+        assert!(matches!(
+            MEMEZ_TARGETS.check_path(paperz_core::AGENT_PATH),
+            Err(WidgetError::ValidationFailed(_))
+        ));
+        assert!(matches!(
+            PAPERZ_TARGETS.check_path(memez_core::MEMEZ_PATH),
+            Err(WidgetError::ValidationFailed(_))
+        ));
+        assert!(PAPERZ_TARGETS.check_path("").is_err());
+    }
+
+    #[test]
+    fn targets_check_tags_alike() {
+        for targets in [&MEMEZ_TARGETS, &PAPERZ_TARGETS] {
+            assert_eq!(targets.check_tag(SM_INIT_TAG), Ok(()));
+            assert_eq!(targets.check_tag(SM_COMP_TAG), Ok(()));
+            assert!(matches!(
+                targets.check_tag(social_sensemaker_core::SM_DATA_TAG),
+                Err(WidgetError::ValidationFailed(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn sm_admins_fall_back_to_the_progenitor_only() {
+        let agent = |byte: u8| AgentPubKey::from_raw_36(vec![byte; 36]);
+        let b64 = |agent: &AgentPubKey| AgentPubKeyB64::from(agent.clone()).to_string();
+        let mut properties = SensemakerProperties::default();
+        assert_eq!(sm_admins(&properties), Ok(vec![]));
+        assert!(may_configure(&[], &agent(1)));
+        properties.progenitor = Some(b64(&agent(1)));
+        assert_eq!(sm_admins(&properties), Ok(vec![agent(1)]));
+        assert!(!may_configure(&[agent(1)], &agent(2)));
+        properties.sm_admins = vec![b64(&agent(2)), b64(&agent(3))];
+        assert_eq!(sm_admins(&properties), Ok(vec![agent(2), agent(3)]));
+        properties.sm_admins = vec!["not an agent".into()];
+        assert!(sm_admins(&properties).is_err());
+    }
+
+    #[test]
+    fn admin_tags_match_by_prefix() {
+        for tag in SM_ADMIN_TAGS {
+            assert!(is_admin_tag(&LinkTag::new(*tag), SM_ADMIN_TAGS));
+            assert!(is_admin_tag(
+                &LinkTag::new(format!("{}X", tag)),
+                SM_ADMIN_TAGS
+            ));
+        }
+        assert!(is_admin_tag(
+            &LinkTag::new("memez_hide_comp"),
+            &["memez_hide_comp"]
+        ));
+        assert!(!is_admin_tag(&LinkTag::new("memez_clap"), SM_ADMIN_TAGS));
+        assert!(!is_admin_tag(
+            &LinkTag::new(social_sensemaker_core::SM_DATA_TAG),
+            SM_ADMIN_TAGS
+        ));
+    }
+
+    #[test]
+    fn int_expr_has_no_negative_literals() {
+        assert_eq!(int_expr(7), "7");
+        assert_eq!(int_expr(0), "0");
+        assert_eq!(int_expr(-7), "(- 0 7)");
+        assert_eq!(
+            int_expr(i64::MIN),
+            format!("(- 0 {})", i64::MIN.unsigned_abs())
+        );
+    }
+
+    #[test]
+    fn apply_expr_applies() {
+        assert_eq!(apply_expr("f", &[]), "f");
+        assert_eq!(
+            apply_expr("f", &[int_expr(1), int_expr(-2)]),
+            "(f 1 (- 0 2))"
+        );
+    }
+}
@@ -0,0 +1,688 @@
+//! sensemaker operations which outlast an unreachable sensemaker. see `SmOpQueue`.
+
+use hdk::prelude::*;
+use widget_core::error::WidgetError;
+
+/// a sensemaker operation, as recorded by a `PendingSmOp`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SmOp {
+    /// `initialize_sm_data` of (path, target)
+    InitSmData(String, EntryHash),
+    /// `step_sm` of (path, target, act)
+    StepSm(String, EntryHash, String),
+    /// `step_sm_path` of (path, target, act)
+    StepSmPath(String, String, String),
+}
+
+impl SmOp {
+    /// the path of the sensemaker data the operation changes. operations on the same path must be
+    /// applied in order, those on different paths needn't be.
+    pub fn sm_data_path(&self) -> String {
+        match self {
+            SmOp::InitSmData(path_string, target_eh) | SmOp::StepSm(path_string, target_eh, _) => {
+                common::compose_entry_hash_path(path_string, target_eh.clone())
+            }
+            SmOp::StepSmPath(path_string, target, _) => common::compose_paths(path_string, target),
+        }
+    }
+}
+
+/// a sensemaker operation which couldn't be applied when it happened, kept on the agent's own chain
+/// until it can be.
+#[hdk_entry(id = "pending_sm_op", visibility = "private")]
+#[derive(Clone)]
+pub struct PendingSmOp {
+    /// random, and unique to this operation. see `ArmedSmOp`, `CompletedSmOp` & `FailedSmOp`.
+    pub idempotency_key: String,
+    pub op: SmOp,
+}
+
+/// committed when the sensemaker data of the `PendingSmOp` with this key was last seen, in a zome
+/// call before the one which applies it. see `SmOpQueue::retry`.
+#[hdk_entry(id = "armed_sm_op", visibility = "private")]
+#[derive(Clone)]
+pub struct ArmedSmOp {
+    pub idempotency_key: String,
+    /// the header of the sensemaker data then in use, `None` if there was none.
+    pub sm_data_hh: Option<HeaderHash>,
+}
+
+/// committed in the same zome call which applies the `PendingSmOp` with this key.
+#[hdk_entry(id = "completed_sm_op", visibility = "private")]
+#[derive(Clone)]
+pub struct CompletedSmOp {
+    pub idempotency_key: String,
+}
+
+/// committed when the `PendingSmOp` with this key failed for a reason which retrying won't fix, e.g.
+/// its target has no sensemaker data. it is never retried, but kept so that it can be looked into.
+#[hdk_entry(id = "failed_sm_op", visibility = "private")]
+#[derive(Clone)]
+pub struct FailedSmOp {
+    pub idempotency_key: String,
+    pub error: String,
+}
+
+/// any of the entries which make up a `SmOpQueue`.
+#[derive(Clone)]
+pub enum SmOpRecord {
+    Pending(PendingSmOp),
+    Armed(ArmedSmOp),
+    Completed(CompletedSmOp),
+    Failed(FailedSmOp),
+}
+
+/// where a `SmOpQueue` keeps its records. see `ChainSmOpStore`.
+pub trait SmOpStore {
+    /// every record, oldest first within each kind.
+    fn records(&self) -> ExternResult<Vec<(EntryHash, SmOpRecord)>>;
+    fn commit(&self, record: SmOpRecord) -> ExternResult<()>;
+    /// a fresh idempotency key.
+    fn new_key(&self) -> ExternResult<String>;
+}
+
+/// the sensemaker, as a `SmOpQueue` sees it. `widget_sensemaker_fns` implements it for the zome's
+/// sensemaker cell.
+pub trait SmOpRemote {
+    fn apply(&self, op: &SmOp) -> ExternResult<()>;
+    /// the header of the sensemaker data at `op`'s `sm_data_path`, `None` if there is none.
+    fn sm_data_hh(&self, op: &SmOp) -> ExternResult<Option<HeaderHash>>;
+}
+
+/// whether `err` may go away by itself, i.e. the sensemaker couldn't be reached at all.
+pub fn is_transient(err: &WasmError) -> bool {
+    matches!(
+        WidgetError::decode(&err.to_string()),
+        Some(WidgetError::SensemakerMissing(_)) | Some(WidgetError::SensemakerCellNotSet)
+    )
+}
+
+/// the app entries of type `T` on the agent's own chain, oldest first.
+pub fn query_own_entries<T>(entry_type: EntryType) -> ExternResult<Vec<(EntryHash, T)>>
+where
+    T: TryFrom<SerializedBytes, Error = SerializedBytesError>,
+{
+    let filter = ChainQueryFilter::new()
+        .entry_type(entry_type)
+        .include_entries(true);
+    let mut entries = Vec::new();
+    for element in query(filter)? {
+        let eh = element
+            .header()
+            .entry_hash()
+            .expect("should be an Entry.")
+            .clone();
+        if let Some(entry) = element
+            .entry()
+            .to_app_option::<T>()
+            .map_err(WasmError::Serialize)?
+        {
+            entries.push((eh, entry));
+        }
+    }
+    Ok(entries)
+}
+
+/// a zome's queue, kept as private entries on the agent's own chain. `entry_type!` only works
+/// within the zome, so this is built by `widget_sensemaker_fns`.
+pub struct ChainSmOpStore {
+    pub pending_type: EntryType,
+    pub armed_type: EntryType,
+    pub completed_type: EntryType,
+    pub failed_type: EntryType,
+}
+
+impl SmOpStore for ChainSmOpStore {
+    fn records(&self) -> ExternResult<Vec<(EntryHash, SmOpRecord)>> {
+        let mut records: Vec<(EntryHash, SmOpRecord)> = Vec::new();
+        records.extend(
+            query_own_entries(self.pending_type.clone())?
+                .into_iter()
+                .map(|(eh, pending)| (eh, SmOpRecord::Pending(pending))),
+        );
+        records.extend(
+            query_own_entries(self.armed_type.clone())?
+                .into_iter()
+                .map(|(eh, armed)| (eh, SmOpRecord::Armed(armed))),
+        );
+        records.extend(
+            query_own_entries(self.completed_type.clone())?
+                .into_iter()
+                .map(|(eh, completed)| (eh, SmOpRecord::Completed(completed))),
+        );
+        records.extend(
+            query_own_entries(self.failed_type.clone())?
+                .into_iter()
+                .map(|(eh, failed)| (eh, SmOpRecord::Failed(failed))),
+        );
+        Ok(records)
+    }
+
+    fn commit(&self, record: SmOpRecord) -> ExternResult<()> {
+        match record {
+            SmOpRecord::Pending(pending) => create_entry(&pending),
+            SmOpRecord::Armed(armed) => create_entry(&armed),
+            SmOpRecord::Completed(completed) => create_entry(&completed),
+            SmOpRecord::Failed(failed) => create_entry(&failed),
+        }?;
+        Ok(())
+    }
+
+    fn new_key(&self) -> ExternResult<String> {
+        Ok(random_bytes(16)?
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect())
+    }
+}
+
+/// what `SmOpQueue::retry` did.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SmOpRetry {
+    /// the idempotency keys of the operations which were applied.
+    pub applied: Vec<String>,
+    /// the idempotency keys of the operations which were armed, to be applied by the next retry.
+    pub armed: Vec<String>,
+    /// the operations which failed, by their `PendingSmOp`, and why.
+    pub errors: Vec<(EntryHash, String)>,
+}
+
+/// a zome's queue of sensemaker operations.
+///
+/// the sensemaker's API has no room for an idempotency key, so whether a queued operation was
+/// already applied is told from the sensemaker data it changes: it is only applied in a later zome
+/// call than the one which armed it, and only if that data is still what it was when it was armed.
+/// had a zome call applied it and then failed, taking its `CompletedSmOp` with it, the data would
+/// have moved on, and the operation is given up on rather than applied twice. that also happens if
+/// another agent changed the data in between, which `get_failed_sm_ops` shows.
+pub struct SmOpQueue<'a> {
+    pub store: &'a dyn SmOpStore,
+    pub remote: &'a dyn SmOpRemote,
+}
+
+impl<'a> SmOpQueue<'a> {
+    /// the operations which are yet to be applied, oldest first, and the latest `ArmedSmOp`s.
+    fn outstanding(&self) -> ExternResult<(Vec<(EntryHash, PendingSmOp)>, Vec<ArmedSmOp>)> {
+        let records = self.store.records()?;
+        let done_keys: Vec<&String> = records
+            .iter()
+            .filter_map(|(_eh, record)| match record {
+                SmOpRecord::Completed(completed) => Some(&completed.idempotency_key),
+                SmOpRecord::Failed(failed) => Some(&failed.idempotency_key),
+                _ => None,
+            })
+            .collect();
+        let mut armed: Vec<ArmedSmOp> = Vec::new();
+        for (_eh, record) in &records {
+            if let SmOpRecord::Armed(arm) = record {
+                armed.retain(|earlier| earlier.idempotency_key != arm.idempotency_key);
+                armed.push(arm.clone());
+            }
+        }
+        let pending = records
+            .iter()
+            .filter_map(|(eh, record)| match record {
+                SmOpRecord::Pending(pending) if !done_keys.contains(&&pending.idempotency_key) => {
+                    Some((eh.clone(), pending.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        Ok((pending, armed))
+    }
+
+    /// the operations which are yet to be applied, oldest first.
+    pub fn pending(&self) -> ExternResult<Vec<(EntryHash, PendingSmOp)>> {
+        Ok(self.outstanding()?.0)
+    }
+
+    /// the operations which were given up on, with why, oldest first.
+    pub fn failed(&self) -> ExternResult<Vec<(PendingSmOp, String)>> {
+        let records = self.store.records()?;
+        Ok(records
+            .iter()
+            .filter_map(|(_eh, record)| match record {
+                SmOpRecord::Failed(failed) => Some(failed),
+                _ => None,
+            })
+            .filter_map(|failed| {
+                records.iter().find_map(|(_eh, record)| match record {
+                    SmOpRecord::Pending(pending)
+                        if pending.idempotency_key == failed.idempotency_key =>
+                    {
+                        Some((pending.clone(), failed.error.clone()))
+                    }
+                    _ => None,
+                })
+            })
+            .collect())
+    }
+
+    /// apply the pending operations which were armed by an earlier retry, oldest first, if their
+    /// sensemaker data is still what they were armed against, and arm the rest against the data as
+    /// it is now. see `SmOpQueue`. an operation which fails for good, or may have been applied
+    /// already, is recorded as a `FailedSmOp` and skipped. if the sensemaker can't be reached, the
+    /// rest stay pending.
+    ///
+    /// it must be the only thing its zome call does, as it would apply operations armed earlier in
+    /// the same call.
+    pub fn retry(&self) -> ExternResult<SmOpRetry> {
+        let (pending_ops, armed) = self.outstanding()?;
+        // operations on the same data are retried in order, those on different data independently
+        let mut by_sm_data_path: Vec<(String, Vec<(EntryHash, PendingSmOp)>)> = Vec::new();
+        for (pending_eh, pending) in pending_ops {
+            let sm_data_path = pending.op.sm_data_path();
+            match by_sm_data_path
+                .iter_mut()
+                .find(|(path, _ops)| *path == sm_data_path)
+            {
+                Some((_path, ops)) => ops.push((pending_eh, pending)),
+                None => by_sm_data_path.push((sm_data_path, vec![(pending_eh, pending)])),
+            }
+        }
+        let mut retry = SmOpRetry::default();
+        for (sm_data_path, ops) in by_sm_data_path {
+            if !self.retry_sm_data_path(&sm_data_path, ops, &armed, &mut retry)? {
+                debug!("sensemaker unreachable, keeping ops pending");
+                break;
+            }
+        }
+        Ok(retry)
+    }
+
+    /// `retry` for the operations on `sm_data_path`. returns whether the sensemaker could be
+    /// reached throughout.
+    fn retry_sm_data_path(
+        &self,
+        sm_data_path: &str,
+        ops: Vec<(EntryHash, PendingSmOp)>,
+        armed: &[ArmedSmOp],
+        retry: &mut SmOpRetry,
+    ) -> ExternResult<bool> {
+        let armed_against = |pending: &PendingSmOp| {
+            armed
+                .iter()
+                .find(|arm| arm.idempotency_key == pending.idempotency_key)
+                .map(|arm| arm.sm_data_hh.clone())
+        };
+        let start = match self.remote.sm_data_hh(&ops[0].1.op) {
+            Ok(sm_data_hh) => sm_data_hh,
+            Err(err) => {
+                retry.errors.push((ops[0].0.clone(), err.to_string()));
+                return Ok(!is_transient(&err));
+            }
+        };
+        // the data as of the last operation this retry applied
+        let mut latest = start.clone();
+        let mut reachable = true;
+        let mut ops = ops.into_iter().peekable();
+        while let Some((pending_eh, pending)) = ops.peek().cloned() {
+            if armed_against(&pending) != Some(start.clone()) {
+                break;
+            }
+            match self.remote.apply(&pending.op) {
+                Ok(()) => {
+                    ops.next();
+                    self.store.commit(SmOpRecord::Completed(CompletedSmOp {
+                        idempotency_key: pending.idempotency_key.clone(),
+                    }))?;
+                    retry.applied.push(pending.idempotency_key);
+                    match self.remote.sm_data_hh(&pending.op) {
+                        Ok(sm_data_hh) => latest = sm_data_hh,
+                        Err(err) => {
+                            // the rest can't be armed against data which isn't known
+                            retry.errors.push((pending_eh, err.to_string()));
+                            return Ok(!is_transient(&err));
+                        }
+                    }
+                }
+                Err(err) if is_transient(&err) => {
+                    retry.errors.push((pending_eh, err.to_string()));
+                    reachable = false;
+                    break;
+                }
+                Err(err) => {
+                    ops.next();
+                    retry.errors.push((pending_eh, err.to_string()));
+                    self.give_up(pending.idempotency_key, err.to_string())?;
+                }
+            }
+        }
+        for (pending_eh, pending) in ops {
+            match armed_against(&pending) {
+                Some(sm_data_hh) if sm_data_hh == latest => {}
+                // only this retry has changed the data since it was armed
+                Some(sm_data_hh) if sm_data_hh == start => self.arm(&pending, &latest, retry)?,
+                None => self.arm(&pending, &latest, retry)?,
+                Some(_) => {
+                    let error = format!(
+                        "the sensemaker data at {} changed after the operation was armed, so it \
+                         may have been applied already",
+                        sm_data_path
+                    );
+                    retry.errors.push((pending_eh, error.clone()));
+                    self.give_up(pending.idempotency_key, error)?;
+                }
+            }
+        }
+        Ok(reachable)
+    }
+
+    fn arm(
+        &self,
+        pending: &PendingSmOp,
+        sm_data_hh: &Option<HeaderHash>,
+        retry: &mut SmOpRetry,
+    ) -> ExternResult<()> {
+        self.store.commit(SmOpRecord::Armed(ArmedSmOp {
+            idempotency_key: pending.idempotency_key.clone(),
+            sm_data_hh: sm_data_hh.clone(),
+        }))?;
+        retry.armed.push(pending.idempotency_key.clone());
+        Ok(())
+    }
+
+    fn give_up(&self, idempotency_key: String, error: String) -> ExternResult<()> {
+        debug!("giving up on sensemaker op: {}", error);
+        self.store.commit(SmOpRecord::Failed(FailedSmOp {
+            idempotency_key,
+            error,
+        }))
+    }
+
+    /// apply `op` to the sensemaker, unless operations on its sensemaker data are still pending, in
+    /// which case it is queued behind them. if the sensemaker can't be reached, `op` is queued as a
+    /// `PendingSmOp` instead, so that whatever caused it still succeeds. see `retry`. if it fails
+    /// for any other reason, that error is returned, failing the zome call along with whatever
+    /// caused `op`, so nothing is queued.
+    pub fn run(&self, op: SmOp) -> ExternResult<()> {
+        let sm_data_path = op.sm_data_path();
+        let is_behind = self
+            .pending()?
+            .iter()
+            .any(|(_eh, pending)| pending.op.sm_data_path() == sm_data_path);
+        if !is_behind {
+            match self.remote.apply(&op) {
+                Ok(()) => return Ok(()),
+                Err(err) if is_transient(&err) => {
+                    debug!("run: sensemaker unreachable: {}", err)
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        debug!("run: queueing {:?}", op);
+        self.store.commit(SmOpRecord::Pending(PendingSmOp {
+            idempotency_key: self.store.new_key()?,
+            op,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    #[derive(Default)]
+    struct MemStore {
+        records: RefCell<Vec<(EntryHash, SmOpRecord)>>,
+    }
+
+    impl MemStore {
+        /// drop the records committed after the first `len`, as a failed zome call would.
+        fn roll_back_to(&self, len: usize) {
+            self.records.borrow_mut().truncate(len);
+        }
+    }
+
+    impl SmOpStore for MemStore {
+        fn records(&self) -> ExternResult<Vec<(EntryHash, SmOpRecord)>> {
+            Ok(self.records.borrow().clone())
+        }
+
+        fn commit(&self, record: SmOpRecord) -> ExternResult<()> {
+            let mut records = self.records.borrow_mut();
+            let eh = EntryHash::from_raw_36(vec![records.len() as u8; 36]);
+            records.push((eh, record));
+            Ok(())
+        }
+
+        fn new_key(&self) -> ExternResult<String> {
+            Ok(format!("key{}", self.records.borrow().len()))
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Reach {
+        Reachable,
+        Unreachable,
+        /// every operation fails for good.
+        Rejecting,
+    }
+
+    struct FakeSensemaker {
+        reach: Cell<Reach>,
+        /// how many operations it applies before it can't be reached any more.
+        apply_limit: Cell<usize>,
+        applied: RefCell<Vec<SmOp>>,
+        /// the number of times each sensemaker data path was changed.
+        versions: RefCell<Vec<(String, u8)>>,
+    }
+
+    impl FakeSensemaker {
+        fn new() -> Self {
+            Self {
+                reach: Cell::new(Reach::Reachable),
+                apply_limit: Cell::new(usize::MAX),
+                applied: RefCell::new(Vec::new()),
+                versions: RefCell::new(Vec::new()),
+            }
+        }
+
+        fn check_reach(&self) -> ExternResult<()> {
+            match self.reach.get() {
+                Reach::Reachable => Ok(()),
+                Reach::Unreachable => {
+                    Err(WidgetError::SensemakerMissing("CellMissing".into()).into())
+                }
+                Reach::Rejecting => Err(WidgetError::SmDataMissing("no sm_data".into()).into()),
+            }
+        }
+
+        /// another agent's change to the sensemaker data at `sm_data_path`.
+        fn bump(&self, sm_data_path: String) {
+            let mut versions = self.versions.borrow_mut();
+            match versions.iter_mut().find(|(path, _)| *path == sm_data_path) {
+                Some((_, version)) => *version += 1,
+                None => versions.push((sm_data_path, 1)),
+            }
+        }
+    }
+
+    impl SmOpRemote for FakeSensemaker {
+        fn apply(&self, op: &SmOp) -> ExternResult<()> {
+            if self.applied.borrow().len() >= self.apply_limit.get() {
+                self.reach.set(Reach::Unreachable);
+            }
+            self.check_reach()?;
+            self.applied.borrow_mut().push(op.clone());
+            self.bump(op.sm_data_path());
+            Ok(())
+        }
+
+        fn sm_data_hh(&self, op: &SmOp) -> ExternResult<Option<HeaderHash>> {
+            if self.reach.get() == Reach::Unreachable {
+                self.check_reach()?;
+            }
+            let sm_data_path = op.sm_data_path();
+            Ok(self
+                .versions
+                .borrow()
+                .iter()
+                .find(|(path, _)| *path == sm_data_path)
+                .map(|(_, version)| HeaderHash::from_raw_36(vec![*version; 36])))
+        }
+    }
+
+    fn clap(target: u8) -> SmOp {
+        SmOp::StepSm(
+            "memez".into(),
+            EntryHash::from_raw_36(vec![target; 36]),
+            "1".into(),
+        )
+    }
+
+    #[test]
+    fn runs_straight_through_while_reachable() {
+        let (store, sensemaker) = (MemStore::default(), FakeSensemaker::new());
+        let queue = SmOpQueue {
+            store: &store,
+            remote: &sensemaker,
+        };
+        assert_eq!(queue.run(clap(1)), Ok(()));
+        assert_eq!(*sensemaker.applied.borrow(), vec![clap(1)]);
+        assert!(store.records.borrow().is_empty());
+    }
+
+    #[test]
+    fn queues_while_unreachable_and_applies_once_reachable() {
+        let (store, sensemaker) = (MemStore::default(), FakeSensemaker::new());
+        let queue = SmOpQueue {
+            store: &store,
+            remote: &sensemaker,
+        };
+        sensemaker.reach.set(Reach::Unreachable);
+        assert_eq!(queue.run(clap(1)), Ok(()));
+        assert_eq!(queue.run(clap(1)), Ok(()));
+        assert_eq!(queue.pending().unwrap().len(), 2);
+
+        let retry = queue.retry().unwrap();
+        assert!(retry.applied.is_empty() && retry.armed.is_empty());
+        assert_eq!(retry.errors.len(), 1);
+
+        sensemaker.reach.set(Reach::Reachable);
+        // a new op on the same data waits its turn, one on other data needn't
+        assert_eq!(queue.run(clap(1)), Ok(()));
+        assert_eq!(queue.run(clap(2)), Ok(()));
+        assert_eq!(*sensemaker.applied.borrow(), vec![clap(2)]);
+
+        assert_eq!(queue.retry().unwrap().armed.len(), 3);
+        assert_eq!(queue.retry().unwrap().applied.len(), 3);
+        assert_eq!(
+            *sensemaker.applied.borrow(),
+            vec![clap(2), clap(1), clap(1), clap(1)]
+        );
+        assert!(queue.pending().unwrap().is_empty());
+        assert_eq!(queue.retry().unwrap(), SmOpRetry::default());
+    }
+
+    #[test]
+    fn returns_permanent_failures_without_queueing() {
+        let (store, sensemaker) = (MemStore::default(), FakeSensemaker::new());
+        let queue = SmOpQueue {
+            store: &store,
+            remote: &sensemaker,
+        };
+        sensemaker.reach.set(Reach::Rejecting);
+        let err = queue.run(clap(1)).unwrap_err();
+        assert!(matches!(
+            WidgetError::decode(&err.to_string()),
+            Some(WidgetError::SmDataMissing(_))
+        ));
+        assert!(queue.pending().unwrap().is_empty());
+    }
+
+    #[test]
+    fn dead_letters_queued_ops_which_fail_for_good() {
+        let (store, sensemaker) = (MemStore::default(), FakeSensemaker::new());
+        let queue = SmOpQueue {
+            store: &store,
+            remote: &sensemaker,
+        };
+        sensemaker.reach.set(Reach::Unreachable);
+        queue.run(clap(1)).unwrap();
+        sensemaker.reach.set(Reach::Reachable);
+        queue.retry().unwrap();
+        sensemaker.reach.set(Reach::Rejecting);
+        let retry = queue.retry().unwrap();
+        assert_eq!(retry.errors.len(), 1);
+        assert!(queue.pending().unwrap().is_empty());
+        let failed = queue.failed().unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0.op, clap(1));
+    }
+
+    #[test]
+    fn replays_after_a_rolled_back_retry_are_no_ops() {
+        let (store, sensemaker) = (MemStore::default(), FakeSensemaker::new());
+        let queue = SmOpQueue {
+            store: &store,
+            remote: &sensemaker,
+        };
+        sensemaker.reach.set(Reach::Unreachable);
+        queue.run(clap(1)).unwrap();
+        sensemaker.reach.set(Reach::Reachable);
+        queue.retry().unwrap();
+
+        // the op is applied, but the zome call fails afterwards, losing its `CompletedSmOp`
+        let len = store.records.borrow().len();
+        assert_eq!(queue.retry().unwrap().applied.len(), 1);
+        store.roll_back_to(len);
+        assert_eq!(queue.pending().unwrap().len(), 1);
+
+        // the data moved since the op was armed, so it may have been applied, and isn't again
+        let retry = queue.retry().unwrap();
+        assert!(retry.applied.is_empty() && retry.armed.is_empty());
+        assert_eq!(retry.errors.len(), 1);
+        assert!(queue.pending().unwrap().is_empty());
+        assert_eq!(queue.failed().unwrap().len(), 1);
+        for _ in 0..3 {
+            queue.retry().unwrap();
+        }
+        assert_eq!(*sensemaker.applied.borrow(), vec![clap(1)]);
+    }
+
+    #[test]
+    fn gives_up_on_ops_whose_data_another_agent_changed() {
+        let (store, sensemaker) = (MemStore::default(), FakeSensemaker::new());
+        let queue = SmOpQueue {
+            store: &store,
+            remote: &sensemaker,
+        };
+        sensemaker.reach.set(Reach::Unreachable);
+        queue.run(clap(1)).unwrap();
+        sensemaker.reach.set(Reach::Reachable);
+        queue.retry().unwrap();
+        sensemaker.bump(clap(1).sm_data_path());
+        assert_eq!(queue.retry().unwrap().errors.len(), 1);
+        assert!(sensemaker.applied.borrow().is_empty());
+        assert_eq!(queue.failed().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rearms_ops_left_behind_by_an_unreachable_sensemaker() {
+        let (store, sensemaker) = (MemStore::default(), FakeSensemaker::new());
+        let queue = SmOpQueue {
+            store: &store,
+            remote: &sensemaker,
+        };
+        sensemaker.reach.set(Reach::Unreachable);
+        queue.run(clap(1)).unwrap();
+        queue.run(clap(1)).unwrap();
+        sensemaker.reach.set(Reach::Reachable);
+        assert_eq!(queue.retry().unwrap().armed.len(), 2);
+
+        // the second op finds the sensemaker gone, after the first changed the data
+        sensemaker.apply_limit.set(1);
+        let retry = queue.retry().unwrap();
+        assert_eq!(retry.applied.len(), 1);
+        assert_eq!(retry.armed.len(), 1);
+        assert_eq!(retry.errors.len(), 1);
+
+        sensemaker.apply_limit.set(usize::MAX);
+        sensemaker.reach.set(Reach::Reachable);
+        assert_eq!(queue.retry().unwrap().applied.len(), 1);
+        assert_eq!(*sensemaker.applied.borrow(), vec![clap(1), clap(1)]);
+        assert!(queue.failed().unwrap().is_empty());
+    }
+}
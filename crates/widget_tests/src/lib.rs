@@ -0,0 +1,57 @@
+//! sweettests for the externs `widget_sensemaker_fns` generates, run against the packed widget
+//! DNAs and the sensemaker DNA. see `npm run sweettest`.
+
+use std::path::Path;
+
+use hdk::prelude::*;
+use holochain::sweettest::{SweetConductor, SweetDnaFile, SweetZome};
+
+/// where `npm run <widget>:pack` puts the widget DNAs, and where the README has the sensemaker DNA
+/// copied to.
+pub const SENSEMAKER_DNA: &str = "../../happs/social_sensemaker.dna";
+pub const MEMEZ_DNA: &str = "../../happs/memez/memez.dna";
+pub const PAPERZ_DNA: &str = "../../happs/paperz/paperz.dna";
+
+pub struct WidgetApp {
+    pub conductor: SweetConductor,
+    pub zome: SweetZome,
+    pub agent_pk: AgentPubKey,
+    pub sensemaker_cell_id: CellId,
+}
+
+/// one agent with the widget DNA at `widget_dna` and the sensemaker DNA installed side by side, as
+/// in `happs/*/with_sensemaker`, but without the widget being pointed at the sensemaker yet.
+pub async fn install_widget(widget_dna: &str, zome_name: &str) -> WidgetApp {
+    let widget_dna = SweetDnaFile::from_bundle(Path::new(widget_dna))
+        .await
+        .expect("the widget DNA to be packed");
+    let sensemaker_dna = SweetDnaFile::from_bundle(Path::new(SENSEMAKER_DNA))
+        .await
+        .expect("the sensemaker DNA to be in place");
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let app = conductor
+        .setup_app("widget", &[widget_dna, sensemaker_dna])
+        .await
+        .expect("the app to install");
+    let (widget_cell, sensemaker_cell) = app.into_tuple();
+    WidgetApp {
+        zome: widget_cell.zome(zome_name),
+        agent_pk: widget_cell.agent_pubkey().clone(),
+        sensemaker_cell_id: sensemaker_cell.cell_id().clone(),
+        conductor,
+    }
+}
+
+impl WidgetApp {
+    /// point the widget at its sensemaker, as the UI does with `set_sensemaker_cell_id`.
+    pub async fn set_sensemaker(&self) {
+        let _: HeaderHash = self
+            .conductor
+            .call(
+                &self.zome,
+                "set_sensemaker_cell_id",
+                self.sensemaker_cell_id.clone(),
+            )
+            .await;
+    }
+}
@@ -0,0 +1,121 @@
+use hdk::prelude::*;
+use memez_core::{BATTLE_PATH, MEMEZ_PATH, MODERATION_PATH};
+use widget_core::config::{STARTER_SM_COMP_EXPR, STARTER_SM_INIT_EXPR};
+use widget_sensemaker::SmExpr;
+use widget_tests::{install_widget, MEMEZ_DNA};
+
+const MEMEZ_ZOME_NAME: &str = "memez_main_zome";
+
+type SmEntry = Option<(EntryHash, widget_sensemaker::common::SensemakerEntry)>;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs the packed DNAs, see `npm run sweettest`"]
+async fn sm_externs_set_and_get_each_path_as_before() {
+    let app = install_widget(MEMEZ_DNA, MEMEZ_ZOME_NAME).await;
+    let get_sm_comp: Result<SmEntry, _> = app
+        .conductor
+        .call_fallible(&app.zome, "get_sm_comp", MEMEZ_PATH.to_string())
+        .await;
+    assert!(get_sm_comp.is_err(), "no sensemaker to ask yet");
+
+    app.set_sensemaker().await;
+    for path in [MEMEZ_PATH, MODERATION_PATH, BATTLE_PATH] {
+        let sm_init: SmEntry = app
+            .conductor
+            .call(&app.zome, "get_sm_init", path.to_string())
+            .await;
+        assert!(sm_init.is_none(), "{} has no sm_init yet", path);
+        // with the shipped properties anyone may configure, and one approval sets a change
+        for (set_fn, expr) in [
+            ("set_sm_init", STARTER_SM_INIT_EXPR),
+            ("set_sm_comp", STARTER_SM_COMP_EXPR),
+        ] {
+            let set: bool = app
+                .conductor
+                .call(&app.zome, set_fn, (path.to_string(), expr.to_string()))
+                .await;
+            assert!(set, "{} of {}", set_fn, path);
+        }
+        let sm_init: SmEntry = app
+            .conductor
+            .call(&app.zome, "get_sm_init", path.to_string())
+            .await;
+        let sm_comp: SmEntry = app
+            .conductor
+            .call(&app.zome, "get_sm_comp", path.to_string())
+            .await;
+        assert!(sm_init.is_some());
+        assert!(sm_comp.is_some());
+        let sm_exprs: (SmExpr, SmExpr) = app
+            .conductor
+            .call(&app.zome, "get_sm_exprs", path.to_string())
+            .await;
+        assert_eq!(
+            sm_exprs,
+            (
+                SmExpr::Source(STARTER_SM_INIT_EXPR.into()),
+                SmExpr::Source(STARTER_SM_COMP_EXPR.into()),
+            )
+        );
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs the packed DNAs, see `npm run sweettest`"]
+async fn sm_externs_pass_sensemaker_failures_on() {
+    let app = install_widget(MEMEZ_DNA, MEMEZ_ZOME_NAME).await;
+    app.set_sensemaker().await;
+    // a meme which was never uploaded has no sensemaker data to step
+    let stepped: Result<(), _> = app
+        .conductor
+        .call_fallible(
+            &app.zome,
+            "step_sm_remote",
+            (
+                MEMEZ_PATH.to_string(),
+                EntryHash::from_raw_36(vec![0; 36]),
+                "1".to_string(),
+            ),
+        )
+        .await;
+    assert!(stepped.is_err());
+    // nor is an expression which doesn't parse set
+    let set: Result<bool, _> = app
+        .conductor
+        .call_fallible(
+            &app.zome,
+            "set_sm_init",
+            (MEMEZ_PATH.to_string(), "(lam [".to_string()),
+        )
+        .await;
+    assert!(set.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs the packed DNAs, see `npm run sweettest`"]
+async fn sm_externs_refuse_other_widgets_paths() {
+    let app = install_widget(MEMEZ_DNA, MEMEZ_ZOME_NAME).await;
+    app.set_sensemaker().await;
+    let get_sm_init: Result<SmEntry, _> = app
+        .conductor
+        .call_fallible(
+            &app.zome,
+            "get_sm_init",
+            paperz_core::AGENT_PATH.to_string(),
+        )
+        .await;
+    assert!(get_sm_init.is_err());
+    let stepped: Result<(), _> = app
+        .conductor
+        .call_fallible(
+            &app.zome,
+            "step_sm_path_remote",
+            (
+                paperz_core::AGENT_PATH.to_string(),
+                "target".to_string(),
+                "1".to_string(),
+            ),
+        )
+        .await;
+    assert!(stepped.is_err());
+}
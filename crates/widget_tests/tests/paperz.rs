@@ -0,0 +1,121 @@
+use hdk::prelude::*;
+use paperz_core::{AGENT_PATH, PAPERZ_ZOME_NAME};
+use widget_core::config::{STARTER_SM_COMP_EXPR, STARTER_SM_INIT_EXPR};
+use widget_sensemaker::SmExpr;
+use widget_tests::{install_widget, PAPERZ_DNA};
+
+type SmEntry = Option<(EntryHash, widget_sensemaker::common::SensemakerEntry)>;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs the packed DNAs, see `npm run sweettest`"]
+async fn sm_externs_set_get_and_step_as_before() {
+    let app = install_widget(PAPERZ_DNA, PAPERZ_ZOME_NAME).await;
+    let get_sm_init: Result<SmEntry, _> = app
+        .conductor
+        .call_fallible(&app.zome, "get_sm_init", AGENT_PATH.to_string())
+        .await;
+    assert!(get_sm_init.is_err(), "no sensemaker to ask yet");
+
+    app.set_sensemaker().await;
+    let sm_init: SmEntry = app
+        .conductor
+        .call(&app.zome, "get_sm_init", AGENT_PATH.to_string())
+        .await;
+    assert!(sm_init.is_none());
+
+    // with the shipped properties anyone may configure, and one approval sets a change
+    let set: bool = app
+        .conductor
+        .call(
+            &app.zome,
+            "set_sm_init",
+            (AGENT_PATH.to_string(), STARTER_SM_INIT_EXPR.to_string()),
+        )
+        .await;
+    assert!(set);
+    let set: bool = app
+        .conductor
+        .call(
+            &app.zome,
+            "set_sm_comp",
+            (AGENT_PATH.to_string(), STARTER_SM_COMP_EXPR.to_string()),
+        )
+        .await;
+    assert!(set);
+    let sm_init: SmEntry = app
+        .conductor
+        .call(&app.zome, "get_sm_init", AGENT_PATH.to_string())
+        .await;
+    let sm_comp: SmEntry = app
+        .conductor
+        .call(&app.zome, "get_sm_comp", AGENT_PATH.to_string())
+        .await;
+    assert!(sm_init.is_some());
+    assert!(sm_comp.is_some());
+    let sm_exprs: (SmExpr, SmExpr) = app
+        .conductor
+        .call(&app.zome, "get_sm_exprs", AGENT_PATH.to_string())
+        .await;
+    assert_eq!(
+        sm_exprs,
+        (
+            SmExpr::Source(STARTER_SM_INIT_EXPR.into()),
+            SmExpr::Source(STARTER_SM_COMP_EXPR.into()),
+        )
+    );
+
+    let agent_b64 = AgentPubKeyB64::from(app.agent_pk.clone()).to_string();
+    let _: () = app
+        .conductor
+        .call(
+            &app.zome,
+            "init_agent_sm_data",
+            (AGENT_PATH.to_string(), agent_b64.clone()),
+        )
+        .await;
+    let _: () = app
+        .conductor
+        .call(
+            &app.zome,
+            "step_sm_path_remote",
+            (AGENT_PATH.to_string(), agent_b64, "1".to_string()),
+        )
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs the packed DNAs, see `npm run sweettest`"]
+async fn sm_externs_refuse_other_widgets_paths() {
+    let app = install_widget(PAPERZ_DNA, PAPERZ_ZOME_NAME).await;
+    app.set_sensemaker().await;
+    let get_sm_init: Result<SmEntry, _> = app
+        .conductor
+        .call_fallible(&app.zome, "get_sm_init", memez_core::MEMEZ_PATH.to_string())
+        .await;
+    assert!(get_sm_init.is_err());
+    let set: Result<bool, _> = app
+        .conductor
+        .call_fallible(
+            &app.zome,
+            "set_sm_init",
+            (
+                memez_core::MEMEZ_PATH.to_string(),
+                STARTER_SM_INIT_EXPR.to_string(),
+            ),
+        )
+        .await;
+    assert!(set.is_err());
+    let stepped: Result<(), _> = app
+        .conductor
+        .call_fallible(
+            &app.zome,
+            "step_sm_path_remote",
+            (
+                memez_core::MEMEZ_PATH.to_string(),
+                "target".to_string(),
+                "1".to_string(),
+            ),
+        )
+        .await;
+    assert!(stepped.is_err());
+}